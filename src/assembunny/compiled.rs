@@ -0,0 +1,342 @@
+// assembunny/compiled.rs
+// Programs compiled to closures, one per instruction, with their operands already bound
+//
+// The interpreter matches on each instruction every time it runs it. Compiling does that once:
+// every instruction becomes a closure that only touches the registers it names and tells the
+// runner where to go next. tgl changes a single instruction, so only the closures it could
+// affect are compiled again.
+
+use super::{ADD_LEN, Arg, Instr, MUL_LEN, REGISTER_COUNT, Registers, Signal, fuse_at, fused_around};
+
+// What a compiled instruction needs from the runner, besides moving the program counter
+enum Event {
+    None,
+    Toggle(i64), // the index of the instruction to toggle
+    Output(i32),
+    // a fused loop that can't take its shortcut: run the instruction it replaced instead
+    Original,
+}
+
+// Runs one instruction and sets the program counter to the next one to run
+type Op = Box<dyn Fn(&mut Registers, &mut usize) -> Event>;
+
+pub struct CompiledProgram {
+    pub registers: Registers,
+    pub program_counter: usize,
+    instrs: Vec<Instr>,
+    // instrs compiled, and optimize(instrs) compiled if optimization is turned on
+    plain: Vec<Op>,
+    fused: Option<Vec<Op>>,
+}
+
+impl CompiledProgram {
+    pub fn new(instrs: Vec<Instr>) -> CompiledProgram {
+        CompiledProgram {
+            registers: [0; REGISTER_COUNT],
+            program_counter: 0,
+            plain: instrs.iter().enumerate().map(|(idx, &instr)| compile(instr, idx)).collect(),
+            fused: None,
+            instrs,
+        }
+    }
+
+    // Like new, but runs the optimized version of the program
+    pub fn optimized(instrs: Vec<Instr>) -> CompiledProgram {
+        let mut program = CompiledProgram::new(instrs);
+        program.fused = Some((0..program.instrs.len()).map(|idx| compile(fuse_at(&program.instrs, idx), idx)).collect());
+        program
+    }
+
+    // The program as tgl has left it
+    pub fn instrs(&self) -> &[Instr] {
+        &self.instrs
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.program_counter >= self.instrs.len()
+    }
+
+    // Execute the next instruction, returning the value sent by an out instruction
+    pub fn step(&mut self) -> Option<i32> {
+        let program_counter = self.program_counter;
+        let event = match self.fused {
+            Some(ref fused) => fused[program_counter](&mut self.registers, &mut self.program_counter),
+            None => self.plain[program_counter](&mut self.registers, &mut self.program_counter),
+        };
+        self.handle(event, program_counter)
+    }
+
+    // Carry out what the instruction at program_counter asked for
+    fn handle(&mut self, event: Event, program_counter: usize) -> Option<i32> {
+        match event {
+            Event::None => None,
+            Event::Toggle(target) => {
+                self.toggle(target);
+                None
+            }
+            Event::Output(val) => Some(val),
+            Event::Original => {
+                // fused loops only replace inc, dec and cpy, which have nothing to ask
+                self.plain[program_counter](&mut self.registers, &mut self.program_counter);
+                None
+            }
+        }
+    }
+
+    fn toggle(&mut self, target: i64) {
+        if target < 0 || target as usize >= self.instrs.len() {
+            return;
+        }
+        let target = target as usize;
+        self.instrs[target] = self.instrs[target].toggled();
+        self.plain[target] = compile(self.instrs[target], target);
+        if let Some(ref mut fused) = self.fused {
+            for idx in fused_around(target) {
+                fused[idx] = compile(fuse_at(&self.instrs, idx), idx);
+            }
+        }
+    }
+
+    // Run until the program halts, ignoring anything it sends
+    pub fn run(&mut self) {
+        self.run_with_output(|_, _| Signal::Continue);
+    }
+
+    // Run until the program halts or the output sink asks it to stop
+    pub fn run_with_output<F>(&mut self, mut out: F)
+        where F: FnMut(i32, &CompiledProgram) -> Signal
+    {
+        while !self.is_halted() {
+            let (event, program_counter) = self.run_until_event();
+            if let Some(val) = self.handle(event, program_counter) {
+                if out(val, self) == Signal::Stop {
+                    break;
+                }
+            }
+        }
+    }
+
+    // Run instructions until one asks something of the runner or the program halts, without
+    // checking which code to run every step. Returns the request and where it came from.
+    fn run_until_event(&mut self) -> (Event, usize) {
+        let ops = match self.fused {
+            Some(ref fused) => fused,
+            None => &self.plain,
+        };
+        while self.program_counter < ops.len() {
+            let program_counter = self.program_counter;
+            match ops[program_counter](&mut self.registers, &mut self.program_counter) {
+                Event::None => {}
+                event => return (event, program_counter),
+            }
+        }
+        (Event::None, self.program_counter)
+    }
+}
+
+// An argument's value: rarer instructions read their operands through this
+fn operand(arg: Arg) -> Box<dyn Fn(&Registers) -> i32> {
+    match arg {
+        Arg::Reg(reg_idx) => Box::new(move |registers: &Registers| registers[reg_idx]),
+        Arg::Imm(imm) => Box::new(move |_: &Registers| imm),
+    }
+}
+
+// The closure for the instruction at idx, with jump targets worked out where they're fixed.
+// The common instructions get a closure for each kind of operand.
+fn compile(instr: Instr, idx: usize) -> Op {
+    let next = idx + 1;
+    // jumping before the first instruction wraps around to a huge value and halts. Targets are
+    // worked out in i64 so they can't overflow, and registers wrap around like the interpreter's.
+    let target = move |offset: i32| (idx as i64 + i64::from(offset)) as usize;
+
+    match instr {
+        Instr::Cpy(Arg::Reg(src), Arg::Reg(dest)) => {
+            Box::new(move |registers: &mut Registers, program_counter: &mut usize| {
+                registers[dest] = registers[src];
+                *program_counter = next;
+                Event::None
+            })
+        }
+        Instr::Cpy(Arg::Imm(val), Arg::Reg(dest)) => {
+            Box::new(move |registers: &mut Registers, program_counter: &mut usize| {
+                registers[dest] = val;
+                *program_counter = next;
+                Event::None
+            })
+        }
+        Instr::Inc(Arg::Reg(reg_idx)) => {
+            Box::new(move |registers: &mut Registers, program_counter: &mut usize| {
+                registers[reg_idx] = registers[reg_idx].wrapping_add(1);
+                *program_counter = next;
+                Event::None
+            })
+        }
+        Instr::Dec(Arg::Reg(reg_idx)) => {
+            Box::new(move |registers: &mut Registers, program_counter: &mut usize| {
+                registers[reg_idx] = registers[reg_idx].wrapping_sub(1);
+                *program_counter = next;
+                Event::None
+            })
+        }
+        Instr::Jnz(Arg::Imm(0), _) => {
+            Box::new(move |_: &mut Registers, program_counter: &mut usize| {
+                *program_counter = next;
+                Event::None
+            })
+        }
+        Instr::Jnz(Arg::Imm(_), Arg::Imm(offset)) => {
+            let target = target(offset);
+            Box::new(move |_: &mut Registers, program_counter: &mut usize| {
+                *program_counter = target;
+                Event::None
+            })
+        }
+        Instr::Jnz(Arg::Reg(cond), Arg::Imm(offset)) => {
+            let target = target(offset);
+            Box::new(move |registers: &mut Registers, program_counter: &mut usize| {
+                *program_counter = if registers[cond] != 0 { target } else { next };
+                Event::None
+            })
+        }
+        Instr::Jnz(cond, offset) => {
+            let (cond, offset) = (operand(cond), operand(offset));
+            Box::new(move |registers: &mut Registers, program_counter: &mut usize| {
+                *program_counter = if cond(registers) != 0 { target(offset(registers)) } else { next };
+                Event::None
+            })
+        }
+        Instr::Tgl(arg) => {
+            let offset = operand(arg);
+            Box::new(move |registers: &mut Registers, program_counter: &mut usize| {
+                *program_counter = next;
+                Event::Toggle(idx as i64 + i64::from(offset(registers)))
+            })
+        }
+        Instr::Out(arg) => {
+            let val = operand(arg);
+            Box::new(move |registers: &mut Registers, program_counter: &mut usize| {
+                *program_counter = next;
+                Event::Output(val(registers))
+            })
+        }
+        Instr::Add(src, dest) => {
+            Box::new(move |registers: &mut Registers, program_counter: &mut usize| {
+                // the loop only ends by counting src down to exactly 0
+                if registers[src] <= 0 {
+                    return Event::Original;
+                }
+                registers[dest] = registers[dest].wrapping_add(registers[src]);
+                registers[src] = 0;
+                *program_counter = idx + ADD_LEN;
+                Event::None
+            })
+        }
+        Instr::Mul(factor, scratch, counter, dest) => {
+            let factor = operand(factor);
+            Box::new(move |registers: &mut Registers, program_counter: &mut usize| {
+                let factor = factor(registers);
+                if factor <= 0 || registers[counter] <= 0 {
+                    return Event::Original;
+                }
+                registers[dest] = registers[dest].wrapping_add(factor.wrapping_mul(registers[counter]));
+                registers[scratch] = 0;
+                registers[counter] = 0;
+                *program_counter = idx + MUL_LEN;
+                Event::None
+            })
+        }
+        // toggled into something invalid
+        Instr::Dec(Arg::Imm(_)) | Instr::Inc(Arg::Imm(_)) | Instr::Cpy(_, Arg::Imm(_)) => {
+            Box::new(move |_: &mut Registers, program_counter: &mut usize| {
+                *program_counter = next;
+                Event::None
+            })
+        }
+    }
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assembunny::{ProgramState, parse_program};
+
+    // Run a program on the interpreter and compiled, with and without optimization, which
+    // should all end the same way
+    fn run_all(instrs: Vec<Instr>, registers: Registers) -> Registers {
+        let mut interpreted = ProgramState::new(instrs.clone());
+        interpreted.registers = registers;
+        interpreted.run();
+
+        for &optimized in &[false, true] {
+            let mut compiled = if optimized {
+                CompiledProgram::optimized(instrs.clone())
+            } else {
+                CompiledProgram::new(instrs.clone())
+            };
+            compiled.registers = registers;
+            compiled.run();
+            assert_eq!(interpreted.registers, compiled.registers);
+            assert_eq!(interpreted.program_counter, compiled.program_counter);
+            assert_eq!(&interpreted.instrs[..], compiled.instrs());
+        }
+        interpreted.registers
+    }
+
+    fn run_all_source(source: &str, registers: Registers) -> Registers {
+        run_all(parse_program(source).unwrap(), registers)
+    }
+
+    #[test]
+    fn test_compiled_matches_interpreter() {
+        // day 12's example
+        assert_eq!([42, 0, 0, 0], run_all_source("cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a", [0; 4]));
+        // day 23's example, which toggles
+        assert_eq!([3, 0, 0, 0],
+                   run_all_source("cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a", [0; 4]));
+        // a multiply loop, jumped into the middle of, and a jump from a register
+        let multiply = "cpy 2 b\njnz b 2\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5\ncpy -12 b\njnz 1 b";
+        assert_eq!([9, -12, 0, 0], run_all_source(multiply, [0, 0, 3, 4]));
+    }
+
+    #[test]
+    fn test_compiled_wraps_around() {
+        // inc and dec past the ends, then an add loop and a multiply loop that overflow
+        assert_eq!([i32::MIN, i32::MAX, 0, 0], run_all_source("inc a\ndec b", [i32::MAX, i32::MIN, 0, 0]));
+        assert_eq!([i32::MIN + 4, 0, 0, 0], run_all_source("inc a\ndec b\njnz b -2", [i32::MAX, 5, 0, 0]));
+        let multiply = "cpy 3 b\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5";
+        assert_eq!([i32::MIN + 5, 3, 0, 0], run_all_source(multiply, [i32::MAX, 0, 0, 2]));
+        // jumps and toggles as far as an i32 reaches either way
+        assert_eq!([1, i32::MAX, 0, 0], run_all_source("inc a\njnz 1 b\ninc a", [0, i32::MAX, 0, 0]));
+        assert_eq!([1, i32::MIN, 0, 0], run_all_source("inc a\njnz 1 b\ninc a", [0, i32::MIN, 0, 0]));
+        assert_eq!([2, i32::MAX, 0, 0], run_all_source("inc a\ntgl b\ninc a", [0, i32::MAX, 0, 0]));
+    }
+
+    #[test]
+    fn test_compiled_toggle() {
+        // the toggle breaks the add loop, as in the interpreter's test
+        assert_eq!([1, 3, 4, 0], run_all_source("tgl b\ninc a\ndec c\njnz c -2", [0, 3, 5, 0]));
+        // and here it makes one
+        let mut instrs = parse_program("tgl b\ninc a\ndec c").unwrap();
+        instrs.push(Instr::Cpy(Arg::Reg(2), Arg::Imm(-2)));
+        assert_eq!([5, 3, 0, 0], run_all(instrs, [0, 3, 5, 0]));
+        // toggling the last instruction of a multiply loop makes the loop, which starts 5 back
+        let mut instrs = parse_program("tgl a\ncpy b c\ninc a\ndec c\njnz c -2\ndec d").unwrap();
+        instrs.push(Instr::Cpy(Arg::Reg(3), Arg::Imm(-5)));
+        assert_eq!([12, 2, 0, 0], run_all(instrs, [6, 2, 0, 3]));
+    }
+
+    #[test]
+    fn test_compiled_output() {
+        let mut compiled = CompiledProgram::optimized(parse_program("out a\ninc a\njnz 1 -2").unwrap());
+        let mut sent = vec![];
+        compiled.run_with_output(|val, _| {
+            sent.push(val);
+            if sent.len() == 3 { Signal::Stop } else { Signal::Continue }
+        });
+        assert_eq!(vec![0, 1, 2], sent);
+        assert_eq!(1, compiled.program_counter);
+    }
+}
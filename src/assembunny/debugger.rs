@@ -0,0 +1,373 @@
+// assembunny/debugger.rs
+// Step-through debugger for assembunny programs, driven by text commands
+//
+// Commands come from any reader, one per line, so a session can be typed at the terminal or
+// scripted in a test. Lines are numbered from 1 like the input file. The program runs
+// unoptimized so that every instruction can be stepped through.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::iter;
+use super::{Instr, ProgramState, RegIdx, Registers, reg_from_name, reg_name, snapshot, REGISTER_COUNT};
+
+const HELP: &str = "commands:
+  step [n]       run the next instruction (or n instructions)
+  continue       run until a breakpoint, a watched register changes or the program halts
+  break <line>   stop before running this line
+  delete <line>  remove a breakpoint
+  watch <reg>    stop after a register's value changes
+  unwatch <reg>  stop watching a register
+  regs           show the registers and where the program is
+  list           show the program as it is now, with toggled lines marked
+  save <file>    write a snapshot of the program to a file, to resume it later
+  quit";
+
+pub struct Debugger {
+    state: ProgramState,
+    // the program as it was loaded, to show what tgl has changed
+    original: Vec<Instr>,
+    // instruction indices, not line numbers
+    breakpoints: BTreeSet<usize>,
+    watches: BTreeSet<RegIdx>,
+}
+
+// Why the program stopped running
+enum Stop {
+    Done,
+    Breakpoint,
+    Watch(RegIdx, i32, i32),
+    Halted,
+}
+
+impl Debugger {
+    pub fn new(instrs: Vec<Instr>, registers: Registers) -> Debugger {
+        let mut state = ProgramState::new(instrs.clone());
+        state.registers = registers;
+        Debugger {
+            state,
+            original: instrs,
+            breakpoints: BTreeSet::new(),
+            watches: BTreeSet::new(),
+        }
+    }
+
+    // Carry on debugging a program saved in a snapshot, where original is the program as it was
+    // loaded. Every line of the snapshot must be that line of the program, or what tgl can make
+    // of it, so a snapshot of some other program is refused. The debugger always runs
+    // unoptimized, whatever the snapshot was taken from.
+    pub fn resume(original: Vec<Instr>, mut state: ProgramState) -> Result<Debugger, String> {
+        if state.instrs.len() != original.len() {
+            return Err(format!("the snapshot has {} instructions but the program has {}",
+                               state.instrs.len(),
+                               original.len()));
+        }
+        for (idx, (&instr, &loaded)) in state.instrs.iter().zip(&original).enumerate() {
+            if !can_toggle_to(loaded, instr) {
+                return Err(format!("line {} of the snapshot is {} but the program has {}",
+                                   idx + 1,
+                                   instr,
+                                   loaded));
+            }
+        }
+        state.code = None;
+        Ok(Debugger {
+            state,
+            original,
+            breakpoints: BTreeSet::new(),
+            watches: BTreeSet::new(),
+        })
+    }
+
+    pub fn state(&self) -> &ProgramState {
+        &self.state
+    }
+
+    // Read and carry out commands until quit or the end of the input
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, output: &mut W) -> io::Result<()> {
+        self.show_location(output)?;
+        write!(output, "(bunny) ")?;
+        output.flush()?;
+
+        for line in input.lines() {
+            let line = line?;
+            if !self.command(line.trim(), output)? {
+                return Ok(());
+            }
+            write!(output, "(bunny) ")?;
+            output.flush()?;
+        }
+        // finish the last prompt's line
+        writeln!(output)
+    }
+
+    // Returns false when it's time to quit
+    fn command<W: Write>(&mut self, line: &str, output: &mut W) -> io::Result<bool> {
+        let words: Vec<_> = line.split_whitespace().collect();
+        match (words.first().cloned(), words.len()) {
+            (None, _) => {}
+            (Some("step"), 1) | (Some("s"), 1) => self.step(1, output)?,
+            (Some("step"), 2) | (Some("s"), 2) => {
+                match words[1].parse() {
+                    Ok(count) => self.step(count, output)?,
+                    Err(_) => writeln!(output, "invalid step count '{}'", words[1])?,
+                }
+            }
+            (Some("continue"), 1) | (Some("c"), 1) => self.cont(output)?,
+            (Some("break"), 2) | (Some("b"), 2) => {
+                match self.parse_line(words[1]) {
+                    Some(idx) => {
+                        self.breakpoints.insert(idx);
+                        writeln!(output, "breakpoint at line {}", idx + 1)?;
+                    }
+                    None => writeln!(output, "no line '{}'", words[1])?,
+                }
+            }
+            (Some("delete"), 2) | (Some("d"), 2) => {
+                match self.parse_line(words[1]) {
+                    Some(idx) if self.breakpoints.remove(&idx) => {
+                        writeln!(output, "removed breakpoint at line {}", idx + 1)?
+                    }
+                    _ => writeln!(output, "no breakpoint at line '{}'", words[1])?,
+                }
+            }
+            (Some("watch"), 2) | (Some("w"), 2) => {
+                match reg_from_name(words[1]) {
+                    Some(reg_idx) => {
+                        self.watches.insert(reg_idx);
+                        writeln!(output, "watching {}", reg_name(reg_idx))?;
+                    }
+                    None => writeln!(output, "no register '{}'", words[1])?,
+                }
+            }
+            (Some("unwatch"), 2) => {
+                match reg_from_name(words[1]) {
+                    Some(reg_idx) if self.watches.remove(&reg_idx) => {
+                        writeln!(output, "stopped watching {}", reg_name(reg_idx))?
+                    }
+                    _ => writeln!(output, "not watching '{}'", words[1])?,
+                }
+            }
+            (Some("regs"), 1) | (Some("r"), 1) => {
+                self.show_registers(output)?;
+                self.show_location(output)?;
+            }
+            (Some("list"), 1) | (Some("l"), 1) => self.show_listing(output)?,
+            (Some("save"), 2) => {
+                match fs::write(words[1], snapshot::save(&self.state)) {
+                    Ok(()) => writeln!(output, "saved to {}", words[1])?,
+                    Err(err) => writeln!(output, "can't save to {}: {}", words[1], err)?,
+                }
+            }
+            (Some("help"), 1) | (Some("h"), 1) => writeln!(output, "{}", HELP)?,
+            (Some("quit"), 1) | (Some("q"), 1) => return Ok(false),
+            _ => writeln!(output, "unknown command '{}', try 'help'", line)?,
+        }
+        Ok(true)
+    }
+
+    // "3" -> Some(2) if the program has a line 3
+    fn parse_line(&self, line: &str) -> Option<usize> {
+        match line.parse::<usize>() {
+            Ok(line) if line >= 1 && line <= self.state.instrs.len() => Some(line - 1),
+            _ => None,
+        }
+    }
+
+    // Run one instruction, noting any watched register it changes
+    fn execute_one(&mut self) -> Stop {
+        if self.state.is_halted() {
+            return Stop::Halted;
+        }
+        let before = self.state.registers;
+        self.state.step();
+
+        for &reg_idx in &self.watches {
+            if self.state.registers[reg_idx] != before[reg_idx] {
+                return Stop::Watch(reg_idx, before[reg_idx], self.state.registers[reg_idx]);
+            }
+        }
+        if self.state.is_halted() {
+            Stop::Halted
+        } else {
+            Stop::Done
+        }
+    }
+
+    fn step<W: Write>(&mut self, count: usize, output: &mut W) -> io::Result<()> {
+        let mut stop = Stop::Done;
+        for _ in 0..count {
+            stop = self.execute_one();
+            if !matches!(stop, Stop::Done) {
+                break;
+            }
+        }
+        self.report(stop, output)
+    }
+
+    fn cont<W: Write>(&mut self, output: &mut W) -> io::Result<()> {
+        loop {
+            let stop = match self.execute_one() {
+                Stop::Done if self.breakpoints.contains(&self.state.program_counter) => Stop::Breakpoint,
+                Stop::Done => continue,
+                stop => stop,
+            };
+            return self.report(stop, output);
+        }
+    }
+
+    fn report<W: Write>(&self, stop: Stop, output: &mut W) -> io::Result<()> {
+        match stop {
+            Stop::Done | Stop::Halted => {}
+            Stop::Breakpoint => write!(output, "breakpoint, ")?,
+            Stop::Watch(reg_idx, old, new) => {
+                writeln!(output, "{} changed: {} -> {}", reg_name(reg_idx), old, new)?
+            }
+        }
+        self.show_location(output)
+    }
+
+    fn show_location<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        match self.state.instrs.get(self.state.program_counter) {
+            Some(instr) => writeln!(output, "line {}: {}", self.state.program_counter + 1, instr),
+            None => writeln!(output, "program halted"),
+        }
+    }
+
+    fn show_registers<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        let registers: Vec<_> = (0..REGISTER_COUNT)
+            .map(|reg_idx| format!("{}={}", reg_name(reg_idx), self.state.registers[reg_idx]))
+            .collect();
+        writeln!(output, "{}", registers.join(" "))
+    }
+
+    // "=>" marks the next line to run, "*" a breakpoint
+    fn show_listing<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        for (idx, instr) in self.state.instrs.iter().enumerate() {
+            let current = if idx == self.state.program_counter { "=>" } else { "  " };
+            let breakpoint = if self.breakpoints.contains(&idx) { '*' } else { ' ' };
+            write!(output, "{}{}{:>3}  {}", current, breakpoint, idx + 1, instr)?;
+            if *instr != self.original[idx] {
+                write!(output, "  (toggled from {})", self.original[idx])?;
+            }
+            writeln!(output)?;
+        }
+        Ok(())
+    }
+}
+
+// Whether toggling loaded any number of times can give instr. After one toggle an instruction
+// only swaps back and forth (tgl and out become inc, and inc and dec swap), so three cover it.
+fn can_toggle_to(loaded: Instr, instr: Instr) -> bool {
+    iter::successors(Some(loaded), |instr| Some(instr.toggled())).take(3).any(|toggled| toggled == instr)
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+    use assembunny::parse_program;
+
+    // Run a debugging session, returning everything the debugger printed without its prompts
+    fn session(program: &str, registers: Registers, commands: &str) -> (Debugger, String) {
+        let mut debugger = Debugger::new(parse_program(program).unwrap(), registers);
+        let mut output = vec![];
+        debugger.run(commands.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap().replace("(bunny) ", "");
+        (debugger, output)
+    }
+
+    const DAY12_EXAMPLE: &str = "cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a";
+
+    #[test]
+    fn test_step() {
+        let (debugger, output) = session(DAY12_EXAMPLE, [0; 4], "step\nstep 2\nregs\nstep 10\nstep\n");
+        assert_eq!("line 1: cpy 41 a
+line 2: inc a
+line 4: dec a
+a=43 b=0 c=0 d=0
+line 4: dec a
+program halted
+program halted
+
+",
+                   output);
+        assert_eq!(42, debugger.state().registers[0]);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let (_, output) = session(DAY12_EXAMPLE, [0; 4], "break 4\nb 9\ncontinue\nc\ndelete 4\ndelete 4\n");
+        assert_eq!("line 1: cpy 41 a
+breakpoint at line 4
+no line '9'
+breakpoint, line 4: dec a
+program halted
+removed breakpoint at line 4
+no breakpoint at line '4'
+
+",
+                   output);
+    }
+
+    #[test]
+    fn test_watch() {
+        let program = "cpy 2 b\ninc a\ndec b\njnz b -2";
+        let (debugger, output) = session(program, [0; 4], "watch a\nwatch e\nc\nc\nunwatch a\nc\nq\nstep\n");
+        assert_eq!("line 1: cpy 2 b
+watching a
+no register 'e'
+a changed: 0 -> 1
+line 3: dec b
+a changed: 1 -> 2
+line 3: dec b
+stopped watching a
+program halted
+",
+                   output);
+        // nothing runs after quit
+        assert!(debugger.state().is_halted());
+    }
+
+    #[test]
+    fn test_list_toggled() {
+        let program = "tgl a\ninc b\ndec b";
+        let (_, output) = session(program, [1, 0, 0, 0], "b 3\nstep\nlist\nfly\n");
+        assert_eq!("line 1: tgl a
+breakpoint at line 3
+line 2: dec b
+     1  tgl a
+=>   2  dec b  (toggled from inc b)
+  *  3  dec b
+unknown command 'fly', try 'help'
+
+",
+                   output);
+    }
+
+    #[test]
+    fn test_save_and_resume() {
+        let path = env::temp_dir().join(format!("assembunny-debugger-{}.txt", process::id()));
+        let program = "tgl a\ninc b\ndec b";
+        let commands = format!("step\nsave {}\n", path.display());
+        let (debugger, output) = session(program, [1, 0, 0, 0], &commands);
+        assert!(output.ends_with(&format!("saved to {}\n\n", path.display())));
+
+        let state = snapshot::restore(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(debugger.state(), &state);
+        let original = parse_program(program).unwrap();
+        let mut resumed = Debugger::resume(original, state).unwrap();
+        let mut output = vec![];
+        resumed.run("list\n".as_bytes(), &mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("=>   2  dec b  (toggled from inc b)\n"));
+
+        assert!(Debugger::resume(vec![], debugger.state().clone()).is_err());
+        // as long as another program, with the same number of lines
+        let other = parse_program("tgl a\ninc b\ninc c").unwrap();
+        assert_eq!(Err("line 3 of the snapshot is dec b but the program has inc c".to_string()),
+                   Debugger::resume(other, debugger.state().clone()).map(|_| ()));
+    }
+}
@@ -0,0 +1,765 @@
+// assembunny/decompile.rs
+// Decompiling a program into formulas for its final registers, by symbolic execution
+//
+// Every register starts out as a symbol for its starting value, and instructions build
+// expressions from those. A real start (the witness) is followed alongside to decide branches,
+// except that a jump on a register's bare starting value splits the analysis into a case where
+// it's 0 and a case where it isn't. Counting loops with a recognizable body (a running total or
+// a Fibonacci pair) are summarized in one step; any other loop is unrolled, which is how day
+// 23's shrinking multiply loop becomes a product that's recognized as a factorial.
+//
+// Unrolling follows the witness, so a formula might only hold for starts that loop as often.
+// Each case is checked against real runs from nearby starts, and registers whose formula
+// didn't hold are marked.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use super::{ADD_LEN, Arg, Instr, Limits, MUL_LEN, Outcome, REGISTER_COUNT, RegIdx, Registers, execute_with_limits,
+            fuse_at, fused_around, optimize, reg_name};
+
+// give up on programs that take longer than this to follow, after summarizing loops
+const MAX_SYMBOLIC_STEPS: u64 = 1_000_000;
+// and skip checking against runs that take longer than this
+const MAX_CHECK_STEPS: u64 = 10_000_000;
+
+// An integer expression over the registers' starting values. It's kept as a sum of terms with
+// integer coefficients plus a constant, so equal expressions compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Expr {
+    terms: BTreeMap<Atom, i64>, // no zero coefficients
+    constant: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Atom {
+    Reg(RegIdx),
+    Product(Vec<Expr>), // two or more factors, none of them constant, sorted
+    Factorial(Box<Expr>),
+    Fib(Box<Expr>), // fib(1) = fib(2) = 1
+}
+
+impl Expr {
+    pub fn constant(value: i64) -> Expr {
+        Expr {
+            terms: BTreeMap::new(),
+            constant: value,
+        }
+    }
+
+    // A register's starting value
+    pub fn reg(reg_idx: RegIdx) -> Expr {
+        Expr::atom(Atom::Reg(reg_idx))
+    }
+
+    fn atom(atom: Atom) -> Expr {
+        let mut terms = BTreeMap::new();
+        terms.insert(atom, 1);
+        Expr { terms, constant: 0 }
+    }
+
+    pub fn factorial(n: &Expr) -> Expr {
+        match n.as_constant() {
+            Some(n) if n <= 1 => Expr::constant(1),
+            _ => Expr::atom(Atom::Factorial(Box::new(n.clone()))),
+        }
+    }
+
+    pub fn fib(n: &Expr) -> Expr {
+        match n.as_constant() {
+            Some(0) => Expr::constant(0),
+            Some(1) | Some(2) => Expr::constant(1),
+            _ => Expr::atom(Atom::Fib(Box::new(n.clone()))),
+        }
+    }
+
+    pub fn as_constant(&self) -> Option<i64> {
+        if self.terms.is_empty() { Some(self.constant) } else { None }
+    }
+
+    // The register, if this is a multiple of its starting value and nothing else
+    fn as_reg_multiple(&self) -> Option<RegIdx> {
+        match (self.terms.iter().next(), self.terms.len(), self.constant) {
+            (Some((&Atom::Reg(reg_idx), _)), 1, 0) => Some(reg_idx),
+            _ => None,
+        }
+    }
+
+    pub fn plus(&self, other: &Expr) -> Expr {
+        let mut sum = self.clone();
+        sum.constant = sum.constant.wrapping_add(other.constant);
+        for (atom, &coef) in &other.terms {
+            add_term(&mut sum.terms, atom.clone(), coef);
+        }
+        sum.merge_fibs();
+        sum
+    }
+
+    pub fn minus(&self, other: &Expr) -> Expr {
+        self.plus(&other.scale(-1))
+    }
+
+    pub fn scale(&self, factor: i64) -> Expr {
+        if factor == 0 {
+            return Expr::constant(0);
+        }
+        Expr {
+            terms: self.terms.iter().map(|(atom, &coef)| (atom.clone(), coef.wrapping_mul(factor))).collect(),
+            constant: self.constant.wrapping_mul(factor),
+        }
+    }
+
+    pub fn times(&self, other: &Expr) -> Expr {
+        match (self.as_constant(), other.as_constant()) {
+            (Some(value), _) => other.scale(value),
+            (_, Some(value)) => self.scale(value),
+            _ => {
+                let (coef1, mut factors) = self.factors();
+                let (coef2, factors2) = other.factors();
+                factors.extend(factors2);
+                factors.sort();
+                Expr::atom(Atom::Product(factors)).scale(coef1.wrapping_mul(coef2))
+            }
+        }
+    }
+
+    // A non-constant expression as a coefficient and the factors it multiplies
+    fn factors(&self) -> (i64, Vec<Expr>) {
+        if self.constant == 0 && self.terms.len() == 1 {
+            let (atom, &coef) = self.terms.iter().next().unwrap();
+            return match *atom {
+                Atom::Product(ref factors) => (coef, factors.clone()),
+                _ => (coef, vec![Expr::atom(atom.clone())]),
+            };
+        }
+        (1, vec![self.clone()])
+    }
+
+    // fib(n) + fib(n + 1) = fib(n + 2), as often as it applies
+    fn merge_fibs(&mut self) {
+        let fibs: Vec<(Expr, i64)> = self.terms
+            .iter()
+            .filter_map(|(atom, &coef)| match *atom {
+                Atom::Fib(ref n) if coef > 0 => Some(((**n).clone(), coef)),
+                _ => None,
+            })
+            .collect();
+        let pair = fibs.iter()
+            .flat_map(|low| fibs.iter().map(move |high| (low, high)))
+            .find(|&(low, high)| high.0.minus(&low.0).as_constant() == Some(1));
+
+        if let Some((&(ref low, low_coef), &(ref high, high_coef))) = pair {
+            let count = low_coef.min(high_coef);
+            add_term(&mut self.terms, Atom::Fib(Box::new(low.clone())), -count);
+            add_term(&mut self.terms, Atom::Fib(Box::new(high.clone())), -count);
+            let next = Expr::fib(&high.plus(&Expr::constant(1)));
+            // plus merges whatever's left
+            *self = self.plus(&next.scale(count));
+        }
+    }
+
+    // The value for the given starting registers
+    pub fn eval(&self, start: &Registers) -> i64 {
+        self.terms.iter().fold(self.constant, |sum, (atom, &coef)| sum.wrapping_add(coef.wrapping_mul(atom.eval(start))))
+    }
+
+    // This expression with each register's starting value replaced by values[reg_idx]
+    fn substitute(&self, values: &[Expr]) -> Expr {
+        self.terms.iter().fold(Expr::constant(self.constant),
+                               |sum, (atom, &coef)| sum.plus(&atom.substitute(values).scale(coef)))
+    }
+
+    fn mentions(&self, reg_idx: RegIdx) -> bool {
+        self.terms.keys().any(|atom| atom.mentions(reg_idx))
+    }
+
+    // Whether this needs parentheses as a factor or function argument
+    fn is_simple(&self) -> bool {
+        match (self.terms.values().next(), self.terms.len()) {
+            (None, _) => self.constant >= 0,
+            (Some(&coef), 1) => coef == 1 && self.constant == 0,
+            _ => false,
+        }
+    }
+}
+
+fn add_term(terms: &mut BTreeMap<Atom, i64>, atom: Atom, coef: i64) {
+    let total = terms.get(&atom).cloned().unwrap_or(0).wrapping_add(coef);
+    if total == 0 {
+        terms.remove(&atom);
+    } else {
+        terms.insert(atom, total);
+    }
+}
+
+impl Atom {
+    fn eval(&self, start: &Registers) -> i64 {
+        match *self {
+            Atom::Reg(reg_idx) => start[reg_idx] as i64,
+            Atom::Product(ref factors) => factors.iter().fold(1, |product, factor| product.wrapping_mul(factor.eval(start))),
+            Atom::Factorial(ref n) => (1..=n.eval(start)).fold(1, i64::wrapping_mul),
+            Atom::Fib(ref n) => {
+                let (mut fib, mut next) = (0i64, 1i64);
+                for _ in 0..n.eval(start) {
+                    let after = fib.wrapping_add(next);
+                    fib = next;
+                    next = after;
+                }
+                fib
+            }
+        }
+    }
+
+    fn substitute(&self, values: &[Expr]) -> Expr {
+        match *self {
+            Atom::Reg(reg_idx) => values[reg_idx].clone(),
+            Atom::Product(ref factors) => {
+                factors.iter().fold(Expr::constant(1), |product, factor| product.times(&factor.substitute(values)))
+            }
+            Atom::Factorial(ref n) => Expr::factorial(&n.substitute(values)),
+            Atom::Fib(ref n) => Expr::fib(&n.substitute(values)),
+        }
+    }
+
+    fn mentions(&self, reg_idx: RegIdx) -> bool {
+        match *self {
+            Atom::Reg(other) => other == reg_idx,
+            Atom::Product(ref factors) => factors.iter().any(|factor| factor.mentions(reg_idx)),
+            Atom::Factorial(ref n) | Atom::Fib(ref n) => n.mentions(reg_idx),
+        }
+    }
+}
+
+// Expressions display as arithmetic on register names: "2*a*(b + 1) - 3", "a! + 6600", "fib(28)"
+impl Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (atom, &coef)) in self.terms.iter().enumerate() {
+            match (i, coef < 0) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {}
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+            if coef.abs() != 1 {
+                write!(f, "{}*", coef.abs())?;
+            }
+            write!(f, "{}", atom)?;
+        }
+        match (self.terms.is_empty(), self.constant) {
+            (true, constant) => write!(f, "{}", constant),
+            (false, 0) => Ok(()),
+            (false, constant) if constant < 0 => write!(f, " - {}", -constant),
+            (false, constant) => write!(f, " + {}", constant),
+        }
+    }
+}
+
+impl Display for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn factor(expr: &Expr) -> String {
+            if expr.is_simple() { expr.to_string() } else { format!("({})", expr) }
+        }
+
+        match *self {
+            Atom::Reg(reg_idx) => write!(f, "{}", reg_name(reg_idx)),
+            Atom::Product(ref factors) => {
+                let factors: Vec<_> = factors.iter().map(factor).collect();
+                write!(f, "{}", factors.join("*"))
+            }
+            Atom::Factorial(ref n) => write!(f, "{}!", factor(n)),
+            Atom::Fib(ref n) => write!(f, "fib({})", n),
+        }
+    }
+}
+
+// Which way a split went: whether the register started at 0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Condition {
+    pub reg_idx: RegIdx,
+    pub is_zero: bool,
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} 0", reg_name(self.reg_idx), if self.is_zero { "==" } else { "!=" })
+    }
+}
+
+// Formulas for the final registers, for starts that meet all the conditions
+#[derive(Debug)]
+pub struct Case {
+    pub conditions: Vec<Condition>,
+    pub registers: Vec<Expr>,
+    // the start that was followed
+    pub witness: Registers,
+    // false for a register whose formula didn't match a run from another start
+    pub general: [bool; REGISTER_COUNT],
+}
+
+#[derive(Debug)]
+pub struct Decompiled {
+    pub cases: Vec<Case>,
+}
+
+impl Decompiled {
+    // A register's final value for a start, worked out from the formulas. None if no case
+    // covers the start, or if the register's formula only held for its witness.
+    pub fn final_value(&self, reg_idx: RegIdx, start: &Registers) -> Option<i64> {
+        let case = self.cases
+            .iter()
+            .find(|case| case.conditions.iter().all(|cond| (start[cond.reg_idx] == 0) == cond.is_zero))?;
+        if case.general[reg_idx] || *start == case.witness {
+            Some(case.registers[reg_idx].eval(start))
+        } else {
+            None
+        }
+    }
+}
+
+// One register per line, under an "if" line when the case has conditions:
+//
+//   if c == 0:
+//       a = fib(28) + 182
+//       b = a - 6    (only for a=7 b=0 c=0 d=0)
+impl Display for Decompiled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for case in &self.cases {
+            let indent = if case.conditions.is_empty() { "" } else { "    " };
+            if !case.conditions.is_empty() {
+                let conditions: Vec<_> = case.conditions.iter().map(|cond| cond.to_string()).collect();
+                writeln!(f, "if {}:", conditions.join(" and "))?;
+            }
+            for (reg_idx, expr) in case.registers.iter().enumerate() {
+                write!(f, "{}{} = {}", indent, reg_name(reg_idx), expr)?;
+                if !case.general[reg_idx] {
+                    let witness: Vec<_> = (0..REGISTER_COUNT)
+                        .map(|reg_idx| format!("{}={}", reg_name(reg_idx), case.witness[reg_idx]))
+                        .collect();
+                    write!(f, "    (only for {})", witness.join(" "))?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// One way through the program, followed symbolically
+#[derive(Clone)]
+struct Path {
+    instrs: Vec<Instr>,
+    code: Vec<Instr>, // optimize(instrs)
+    program_counter: usize,
+    registers: Vec<Expr>,
+    witness: Registers,
+    conditions: Vec<Condition>,
+}
+
+impl Path {
+    fn value(&self, arg: Arg) -> Expr {
+        match arg {
+            Arg::Reg(reg_idx) => self.registers[reg_idx].clone(),
+            Arg::Imm(imm) => Expr::constant(imm as i64),
+        }
+    }
+
+    // What expr is when the program starts from the witness
+    fn concrete(&self, expr: &Expr) -> i64 {
+        expr.eval(&self.witness)
+    }
+
+    fn is_halted(&self) -> bool {
+        self.program_counter >= self.instrs.len()
+    }
+
+    // Follow the next instruction, returning the other side of a split
+    fn step(&mut self) -> Result<Option<Path>, String> {
+        let instr = self.code[self.program_counter];
+        self.execute(instr)
+    }
+
+    // Like ProgramState::execute_instr, on expressions
+    fn execute(&mut self, instr: Instr) -> Result<Option<Path>, String> {
+        let program_counter = self.program_counter;
+        let one = Expr::constant(1);
+        match instr {
+            Instr::Jnz(cond_arg, offset_arg) => {
+                let (taken, split) = self.branch(&self.value(cond_arg));
+                if taken {
+                    let target = program_counter as i64 + self.concrete(&self.value(offset_arg));
+                    self.program_counter = match (cond_arg, target >= 0 && (target as usize) < program_counter) {
+                        (Arg::Reg(counter), true) => {
+                            match self.summarize_loop(target as usize, counter) {
+                                Some(registers) => {
+                                    self.registers = registers;
+                                    program_counter + 1
+                                }
+                                None => target as usize,
+                            }
+                        }
+                        // jumping before the first instruction halts
+                        _ if target < 0 => self.instrs.len(),
+                        _ => target as usize,
+                    };
+                    return Ok(split);
+                }
+                self.program_counter += 1;
+                return Ok(split);
+            }
+            Instr::Dec(Arg::Reg(reg_idx)) => self.registers[reg_idx] = self.registers[reg_idx].minus(&one),
+            Instr::Inc(Arg::Reg(reg_idx)) => self.registers[reg_idx] = self.registers[reg_idx].plus(&one),
+            Instr::Cpy(arg, Arg::Reg(reg_idx)) => self.registers[reg_idx] = self.value(arg),
+            Instr::Tgl(arg) => {
+                let target = program_counter as i64 + self.concrete(&self.value(arg));
+                if target >= 0 && (target as usize) < self.instrs.len() {
+                    let target = target as usize;
+                    self.instrs[target] = self.instrs[target].toggled();
+                    for idx in fused_around(target) {
+                        self.code[idx] = fuse_at(&self.instrs, idx);
+                    }
+                }
+            }
+            Instr::Out(_) => return Err(format!("line {}: out can't be decompiled", program_counter + 1)),
+            Instr::Add(src, dest) => {
+                if self.concrete(&self.registers[src]) <= 0 {
+                    return self.execute_original();
+                }
+                self.registers[dest] = self.registers[dest].plus(&self.registers[src]);
+                self.registers[src] = Expr::constant(0);
+                self.program_counter += ADD_LEN;
+                return Ok(None);
+            }
+            Instr::Mul(factor_arg, scratch, counter, dest) => {
+                let factor = self.value(factor_arg);
+                if self.concrete(&factor) <= 0 || self.concrete(&self.registers[counter]) <= 0 {
+                    return self.execute_original();
+                }
+                self.registers[dest] = self.registers[dest].plus(&factor.times(&self.registers[counter]));
+                self.registers[scratch] = Expr::constant(0);
+                self.registers[counter] = Expr::constant(0);
+                self.program_counter += MUL_LEN;
+                return Ok(None);
+            }
+            // toggled into something invalid
+            Instr::Dec(Arg::Imm(_)) | Instr::Inc(Arg::Imm(_)) | Instr::Cpy(_, Arg::Imm(_)) => {}
+        }
+        self.program_counter += 1;
+        Ok(None)
+    }
+
+    fn execute_original(&mut self) -> Result<Option<Path>, String> {
+        let instr = self.instrs[self.program_counter];
+        self.execute(instr)
+    }
+
+    // Whether a jump on cond is taken. If cond is a multiple of a register's starting value that
+    // no split has decided yet, this path takes the witness's side and the other side is
+    // returned, still before the jump.
+    fn branch(&mut self, cond: &Expr) -> (bool, Option<Path>) {
+        let mut split = None;
+        if let Some(reg_idx) = cond.as_reg_multiple() {
+            if self.conditions.iter().all(|cond| cond.reg_idx != reg_idx) {
+                let mut other = self.clone();
+                other.witness[reg_idx] = if self.witness[reg_idx] == 0 { 1 } else { 0 };
+                other.assume(reg_idx);
+                self.assume(reg_idx);
+                split = Some(other);
+            }
+        }
+        (self.concrete(cond) != 0, split)
+    }
+
+    // Record whether reg_idx started at 0 like the witness's, dropping it from the expressions
+    // if it did
+    fn assume(&mut self, reg_idx: RegIdx) {
+        let is_zero = self.witness[reg_idx] == 0;
+        self.conditions.push(Condition { reg_idx, is_zero });
+        if is_zero {
+            let values: Vec<_> = (0..REGISTER_COUNT)
+                .map(|other| if other == reg_idx { Expr::constant(0) } else { Expr::reg(other) })
+                .collect();
+            self.registers = self.registers.iter().map(|expr| expr.substitute(&values)).collect();
+        }
+    }
+
+    // The registers once a counting loop from target back to the jump here has finished, if its
+    // body is straight-line code that counts counter down by 1 and does something recognizable
+    // to the other registers. The loop has run once already, so it runs counter more times.
+    fn summarize_loop(&self, target: usize, counter: RegIdx) -> Option<Vec<Expr>> {
+        let end = self.program_counter;
+        let remaining = self.registers[counter].clone();
+        if self.concrete(&remaining) <= 0 {
+            return None;
+        }
+
+        // run the body once, starting from symbols for the registers at the top of the loop
+        let mut witness = [0; REGISTER_COUNT];
+        for (reg_idx, expr) in self.registers.iter().enumerate() {
+            witness[reg_idx] = self.concrete(expr) as i32;
+        }
+        let mut body = Path {
+            program_counter: target,
+            registers: (0..REGISTER_COUNT).map(Expr::reg).collect(),
+            witness,
+            conditions: vec![],
+            ..self.clone()
+        };
+        while body.program_counter < end {
+            match body.code[body.program_counter] {
+                Instr::Jnz(..) | Instr::Tgl(_) | Instr::Out(_) => return None,
+                _ => body.step().ok()?,
+            };
+        }
+        let after = body.registers;
+        if body.program_counter != end || after[counter] != Expr::reg(counter).minus(&Expr::constant(1)) {
+            return None;
+        }
+
+        let changed: Vec<_> = (0..REGISTER_COUNT).map(|reg_idx| after[reg_idx] != Expr::reg(reg_idx)).collect();
+        let is_fixed = |expr: &Expr| (0..REGISTER_COUNT).all(|reg_idx| !changed[reg_idx] || !expr.mentions(reg_idx));
+        // x' = x + y and y' = x
+        let fib_pair = (0..REGISTER_COUNT)
+            .flat_map(|x| (0..REGISTER_COUNT).map(move |y| (x, y)))
+            .find(|&(x, y)| x != y && after[x] == Expr::reg(x).plus(&Expr::reg(y)) && after[y] == Expr::reg(x));
+        let one = Expr::constant(1);
+
+        let mut registers = Vec::with_capacity(REGISTER_COUNT);
+        for reg_idx in 0..REGISTER_COUNT {
+            let before = &self.registers[reg_idx];
+            let change = after[reg_idx].minus(&Expr::reg(reg_idx));
+            let value = if reg_idx == counter {
+                Expr::constant(0)
+            } else if !changed[reg_idx] {
+                before.clone()
+            } else if is_fixed(&change) {
+                // a running total
+                before.plus(&change.substitute(&self.registers).times(&remaining))
+            } else if is_fixed(&after[reg_idx]) {
+                after[reg_idx].substitute(&self.registers)
+            } else {
+                // after n more steps of a Fibonacci pair starting from x and y,
+                // x is fib(n + 1)*x + fib(n)*y, and y (and any copy of x) is fib(n)*x + fib(n - 1)*y
+                let (x, y) = fib_pair?;
+                let shift = if reg_idx == x {
+                    1
+                } else if after[reg_idx] == Expr::reg(x) {
+                    0
+                } else {
+                    return None;
+                };
+                let n = remaining.plus(&Expr::constant(shift));
+                Expr::fib(&n).times(&self.registers[x]).plus(&Expr::fib(&n.minus(&one)).times(&self.registers[y]))
+            };
+            registers.push(value);
+        }
+        Some(registers)
+    }
+
+    fn finish(self, original: &[Instr]) -> Case {
+        let registers: Vec<_> = self.registers.iter().map(|expr| find_factorials(expr, &self.witness)).collect();
+        let general = check(original, &self.conditions, &registers, self.witness);
+        Case {
+            conditions: self.conditions,
+            registers,
+            witness: self.witness,
+            general,
+        }
+    }
+}
+
+// Rewrite products of consecutive factors counting down to 1 or 2 (in the witness) as
+// factorials: a*(a - 1)*(a - 2) is a! when a is 3
+fn find_factorials(expr: &Expr, witness: &Registers) -> Expr {
+    expr.terms.iter().fold(Expr::constant(expr.constant), |sum, (atom, &coef)| {
+        let term = match *atom {
+            Atom::Product(ref factors) => factorial_of(factors, witness),
+            _ => None,
+        };
+        sum.plus(&term.unwrap_or_else(|| Expr::atom(atom.clone())).scale(coef))
+    })
+}
+
+fn factorial_of(factors: &[Expr], witness: &Registers) -> Option<Expr> {
+    let top = factors.iter().max_by_key(|factor| factor.constant)?;
+    let mut steps_down = factors.iter().map(|factor| top.minus(factor).as_constant()).collect::<Option<Vec<_>>>()?;
+    steps_down.sort();
+    let consecutive = steps_down.iter().enumerate().all(|(i, &step)| step == i as i64);
+    let bottom = top.minus(&Expr::constant(steps_down.len() as i64 - 1)).eval(witness);
+    if consecutive && (bottom == 1 || bottom == 2) { Some(Expr::factorial(top)) } else { None }
+}
+
+// For each register, whether its formula matches real runs from the witness and from starts one
+// and two higher in each register the formulas use (where the case's conditions allow)
+fn check(instrs: &[Instr], conditions: &[Condition], registers: &[Expr], witness: Registers) -> [bool; REGISTER_COUNT] {
+    let mut starts = vec![witness];
+    for reg_idx in 0..REGISTER_COUNT {
+        if registers.iter().any(|expr| expr.mentions(reg_idx)) {
+            for bump in 1..3 {
+                let mut start = witness;
+                start[reg_idx] += bump;
+                starts.push(start);
+            }
+        }
+    }
+
+    let mut general = [true; REGISTER_COUNT];
+    let allowed = |start: &Registers| conditions.iter().all(|cond| (start[cond.reg_idx] == 0) == cond.is_zero);
+    for start in starts.into_iter().filter(allowed) {
+        if let Some(finals) = run_limited(instrs, start) {
+            for reg_idx in 0..REGISTER_COUNT {
+                if registers[reg_idx].eval(&start) as i32 != finals[reg_idx] {
+                    general[reg_idx] = false;
+                }
+            }
+        }
+    }
+    general
+}
+
+fn run_limited(instrs: &[Instr], start: Registers) -> Option<Registers> {
+    let limits = Limits {
+        max_steps: Some(MAX_CHECK_STEPS),
+        detect_loops: false,
+    };
+    match execute_with_limits(instrs, start, limits) {
+        (Outcome::Halted, program_state) => Some(program_state.registers),
+        _ => None,
+    }
+}
+
+// Work out formulas for what a program leaves in its registers, following witness where it
+// can't tell which way a branch goes
+pub fn decompile(instrs: &[Instr], witness: Registers) -> Result<Decompiled, String> {
+    let start = Path {
+        instrs: instrs.to_vec(),
+        code: optimize(instrs),
+        program_counter: 0,
+        registers: (0..REGISTER_COUNT).map(Expr::reg).collect(),
+        witness,
+        conditions: vec![],
+    };
+    let mut paths = vec![start];
+    let mut cases = vec![];
+    let mut steps = 0;
+
+    while let Some(mut path) = paths.pop() {
+        while !path.is_halted() {
+            steps += 1;
+            if steps > MAX_SYMBOLIC_STEPS {
+                return Err(format!("gave up after following {} instructions", MAX_SYMBOLIC_STEPS));
+            }
+            if let Some(other) = path.step()? {
+                paths.push(other);
+            }
+        }
+        cases.push(path.finish(instrs));
+    }
+
+    cases.sort_by_key(|case| case.conditions.iter().map(|cond| (cond.reg_idx, !cond.is_zero)).collect::<Vec<_>>());
+    Ok(Decompiled { cases })
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assembunny::parse_program;
+
+    // Made-up programs with the same loops as the puzzles' inputs, but different numbers.
+    // Day 12's: Fibonacci numbers, going further when c starts as 1, and then a product added on.
+    const FIBONACCI: &str = "cpy 1 a\ncpy 1 b\ncpy 20 d\njnz c 2\njnz 1 5\ncpy 5 c\ninc d\ndec c\njnz c -2\n\
+                             cpy a c\ninc a\ndec b\njnz b -2\ncpy c b\ndec d\njnz d -6\ncpy 9 c\ncpy 11 d\n\
+                             inc a\ndec d\njnz d -2\ndec c\njnz c -5";
+
+    // Day 23's: a factorial, then a product that's only added on once tgl has rewritten the end
+    // of the program
+    const FACTORIAL: &str = "cpy a b\ndec b\ncpy a d\ncpy 0 a\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\n\
+                             jnz d -5\ndec b\ncpy b c\ncpy c d\ndec d\ninc c\njnz d -2\ntgl c\ncpy -16 c\n\
+                             jnz 1 c\ncpy 31 c\njnz 17 d\ninc a\ninc d\njnz d -2\ninc c\njnz c -5";
+
+    fn a() -> Expr {
+        Expr::reg(0)
+    }
+
+    fn b() -> Expr {
+        Expr::reg(1)
+    }
+
+    fn num(value: i64) -> Expr {
+        Expr::constant(value)
+    }
+
+    #[test]
+    fn test_expr() {
+        assert_eq!(a().scale(2), a().plus(&num(1)).times(&num(2)).minus(&num(2)));
+        assert_eq!(num(0), a().minus(&a()));
+        assert_eq!(a().times(&b()), b().times(&a()));
+
+        let expr = a().times(&b().plus(&num(1))).scale(2).minus(&num(3));
+        assert_eq!("2*a*(b + 1) - 3", expr.to_string());
+        assert_eq!(13, expr.eval(&[2, 3, 0, 0]));
+        assert_eq!("-a + b", b().minus(&a()).to_string());
+        assert_eq!("(a - 1)!", Expr::factorial(&a().minus(&num(1))).to_string());
+        assert_eq!(24, Expr::factorial(&a()).eval(&[4, 0, 0, 0]));
+        assert_eq!(b().times(&b()).plus(&num(1)), a().plus(&num(1)).substitute(&[b().times(&b()), a(), a(), a()]));
+    }
+
+    #[test]
+    fn test_expr_fib() {
+        assert_eq!(num(1), Expr::fib(&num(2)));
+        assert_eq!("fib(7)", Expr::fib(&num(5)).plus(&Expr::fib(&num(6))).to_string());
+        assert_eq!("fib(28)", Expr::fib(&num(26)).scale(2).plus(&Expr::fib(&num(25))).to_string());
+        assert_eq!("fib(a) + fib(a + 2)",
+                   Expr::fib(&a()).scale(2).plus(&Expr::fib(&a().plus(&num(1)))).to_string());
+        assert_eq!(55, Expr::fib(&a()).eval(&[10, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_decompile_fused() {
+        let program = parse_program("inc b\ninc b\ninc b\ncpy b c\ninc d\ndec c\njnz c -2\ndec a\njnz a -5\ncpy d b")
+            .unwrap();
+        let decompiled = decompile(&program, [4, 1, 0, 0]).unwrap();
+        assert_eq!("a = 0\nb = d + a*(b + 3)\nc = 0\nd = d + a*(b + 3)\n", decompiled.to_string());
+        assert_eq!(Some(21), decompiled.final_value(1, &[3, 4, 0, 0]));
+    }
+
+    #[test]
+    fn test_decompile_fibonacci() {
+        let program = parse_program(FIBONACCI).unwrap();
+        let decompiled = decompile(&program, [0; 4]).unwrap();
+        assert_eq!("if c == 0:
+    a = fib(22) + 99
+    b = fib(21)
+    c = 0
+    d = 0
+if c != 0:
+    a = fib(27) + 99
+    b = fib(26)
+    c = 0
+    d = 0
+",
+                   decompiled.to_string());
+        assert_eq!(Some(17810), decompiled.final_value(0, &[0, 0, 0, 0]));
+        assert_eq!(Some(196517), decompiled.final_value(0, &[0, 0, 1, 0]));
+    }
+
+    #[test]
+    fn test_decompile_factorial() {
+        let program = parse_program(FACTORIAL).unwrap();
+        let decompiled = decompile(&program, [7, 0, 0, 0]).unwrap();
+        assert_eq!("a = a! + 527
+b = a - 6    (only for a=7 b=0 c=0 d=0)
+c = 0
+d = 0
+",
+                   decompiled.to_string());
+        assert_eq!(Some(479002127), decompiled.final_value(0, &[12, 0, 0, 0]));
+        assert_eq!(None, decompiled.final_value(1, &[12, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_decompile_out() {
+        let program = parse_program("out a\njnz 1 -1").unwrap();
+        assert_eq!(Err("line 1: out can't be decompiled".to_string()), decompile(&program, [0; 4]).map(|_| ()));
+    }
+}
@@ -0,0 +1,352 @@
+// assembunny/disasm.rs
+// Disassembly with labelled jump targets, and the control-flow graph as Graphviz DOT
+//
+// Both work on the program as written. tgl can change it while it runs, so they show what the
+// program does until its first toggle, which is usually enough to see what it computes.
+
+use std::collections::BTreeMap;
+use super::{Arg, Instr};
+
+// Where a jnz can go
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Jump {
+    Never, // condition is always 0
+    To(usize), // an instruction index
+    Halt, // out of the program
+    Computed, // offset held in a register
+}
+
+// Where the jnz at index idx jumps if its condition isn't 0
+fn jump_target(instrs: &[Instr], idx: usize) -> Option<Jump> {
+    match instrs[idx] {
+        Instr::Jnz(Arg::Imm(0), _) => Some(Jump::Never),
+        Instr::Jnz(_, Arg::Imm(offset)) => {
+            let target = idx as i64 + offset as i64;
+            if target >= 0 && (target as usize) < instrs.len() {
+                Some(Jump::To(target as usize))
+            } else {
+                Some(Jump::Halt)
+            }
+        }
+        Instr::Jnz(_, Arg::Reg(_)) => Some(Jump::Computed),
+        _ => None,
+    }
+}
+
+// Can execution carry on to the next instruction? Not after a jnz that always jumps
+fn falls_through(instr: &Instr) -> bool {
+    match *instr {
+        Instr::Jnz(Arg::Imm(cond), _) => cond == 0,
+        _ => true,
+    }
+}
+
+// Label names for every instruction that's a jump target, numbered in program order
+fn labels(instrs: &[Instr]) -> BTreeMap<usize, String> {
+    let mut targets: Vec<_> = (0..instrs.len())
+        .filter_map(|idx| match jump_target(instrs, idx) {
+            Some(Jump::To(target)) => Some(target),
+            _ => None,
+        })
+        .collect();
+    targets.sort();
+    targets.dedup();
+    targets.into_iter().enumerate().map(|(i, target)| (target, format!("L{}", i + 1))).collect()
+}
+
+// The program in canonical form, one instruction per line, with a label before each jump
+// target and a comment after each jump saying where it goes:
+//
+//         cpy 5 c
+// L1:     inc a
+//         dec c
+//         jnz c -2    ; L1
+//
+// Removing the labels and comments leaves assembunny that parses back to the same program.
+pub fn disassemble(instrs: &[Instr]) -> String {
+    let labels = labels(instrs);
+    let mut text = String::new();
+
+    for (idx, instr) in instrs.iter().enumerate() {
+        let label = labels.get(&idx).map_or(String::new(), |label| format!("{}:", label));
+        let instr_text = instr.to_string();
+        let comment = match jump_target(instrs, idx) {
+            Some(Jump::To(target)) => Some(labels[&target].clone()),
+            Some(Jump::Halt) => Some("halt".to_string()),
+            Some(Jump::Computed) => Some("computed".to_string()),
+            Some(Jump::Never) | None => None,
+        };
+        let line = match comment {
+            Some(comment) => format!("{:<8}{:<12}; {}", label, instr_text, comment),
+            None => format!("{:<8}{}", label, instr_text),
+        };
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
+
+// A straight run of instructions that's only entered at the top and only left at the bottom
+#[derive(Debug, PartialEq)]
+pub struct Block {
+    pub start: usize,
+    pub end: usize, // exclusive
+    pub successors: Vec<Successor>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Successor {
+    Block(usize), // index into the block list
+    Halt,
+    Computed,
+}
+
+// Split the program into basic blocks. A block starts at the top of the program, at every
+// jump target and after every jnz.
+pub fn basic_blocks(instrs: &[Instr]) -> Vec<Block> {
+    let mut starts = vec![false; instrs.len() + 1];
+    starts[0] = true;
+    for idx in 0..instrs.len() {
+        if let Some(jump) = jump_target(instrs, idx) {
+            starts[idx + 1] = true;
+            if let Jump::To(target) = jump {
+                starts[target] = true;
+            }
+        }
+    }
+    let starts: Vec<_> = (0..instrs.len()).filter(|&idx| starts[idx]).collect();
+    let block_at = |idx: usize| starts.binary_search(&idx).ok();
+
+    starts.iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = starts.get(i + 1).cloned().unwrap_or(instrs.len());
+            let last = end - 1;
+            let mut successors = vec![];
+            match jump_target(instrs, last) {
+                Some(Jump::To(target)) => successors.push(Successor::Block(block_at(target).unwrap())),
+                Some(Jump::Halt) => successors.push(Successor::Halt),
+                Some(Jump::Computed) => successors.push(Successor::Computed),
+                Some(Jump::Never) | None => {}
+            }
+            if falls_through(&instrs[last]) {
+                let next = block_at(end).map_or(Successor::Halt, Successor::Block);
+                if !successors.contains(&next) {
+                    successors.push(next);
+                }
+            }
+            Block {
+                start,
+                end,
+                successors,
+            }
+        })
+        .collect()
+}
+
+// Edges that close a loop: found by a depth-first walk, they lead back to a block that's still
+// being walked
+fn back_edges(blocks: &[Block]) -> Vec<(usize, usize)> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        New,
+        Open,
+        Done,
+    }
+
+    let mut visits = vec![Visit::New; blocks.len()];
+    let mut back = vec![];
+
+    // blocks that can't be reached from the first (until tgl changes the program) get walks of
+    // their own, so their loops are found too
+    for root in 0..blocks.len() {
+        if visits[root] != Visit::New {
+            continue;
+        }
+        visits[root] = Visit::Open;
+        // (block, index of the next successor to follow)
+        let mut stack = vec![(root, 0)];
+
+        while let Some(&mut (block, ref mut next)) = stack.last_mut() {
+            let successor = blocks[block].successors.get(*next);
+            *next += 1;
+            match successor {
+                Some(&Successor::Block(to)) => {
+                    match visits[to] {
+                        Visit::New => {
+                            visits[to] = Visit::Open;
+                            stack.push((to, 0));
+                        }
+                        Visit::Open => back.push((block, to)),
+                        Visit::Done => {}
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    visits[block] = Visit::Done;
+                    stack.pop();
+                }
+            }
+        }
+    }
+    back
+}
+
+// The control-flow graph in Graphviz DOT: a box per basic block listing its lines, with loop
+// back-edges drawn in red. Jumps out of the program go to a "halt" node, and jumps whose
+// offset is in a register go to a dashed "computed" node.
+pub fn to_dot(instrs: &[Instr]) -> String {
+    let blocks = basic_blocks(instrs);
+    let back = back_edges(&blocks);
+    let mut dot = String::from("digraph assembunny {\n    node [shape=box, fontname=\"monospace\"];\n");
+    let mut halts = false;
+    let mut computed = false;
+
+    for (i, block) in blocks.iter().enumerate() {
+        let lines: String = (block.start..block.end)
+            .map(|idx| format!("{}: {}\\l", idx + 1, instrs[idx]))
+            .collect();
+        dot.push_str(&format!("    b{} [label=\"{}\"];\n", i, lines));
+    }
+    for (i, block) in blocks.iter().enumerate() {
+        for successor in &block.successors {
+            match *successor {
+                Successor::Block(to) if back.contains(&(i, to)) => {
+                    dot.push_str(&format!("    b{} -> b{} [color=red, label=\"loop\"];\n", i, to))
+                }
+                Successor::Block(to) => dot.push_str(&format!("    b{} -> b{};\n", i, to)),
+                Successor::Halt => {
+                    halts = true;
+                    dot.push_str(&format!("    b{} -> halt;\n", i));
+                }
+                Successor::Computed => {
+                    computed = true;
+                    dot.push_str(&format!("    b{} -> computed [style=dashed];\n", i));
+                }
+            }
+        }
+    }
+
+    if halts {
+        dot.push_str("    halt [shape=oval];\n");
+    }
+    if computed {
+        dot.push_str("    computed [shape=oval, style=dashed];\n");
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assembunny::{parse_instr, parse_program};
+
+    // A made-up program with the same loops and toggles as day 23's input, but different numbers
+    const FACTORIAL: &str = "\
+cpy a b
+dec b
+cpy a d
+cpy 0 a
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5
+dec b
+cpy b c
+cpy c d
+dec d
+inc c
+jnz d -2
+tgl c
+cpy -16 c
+jnz 1 c
+cpy 31 c
+jnz 17 d
+inc a
+inc d
+jnz d -2
+inc c
+jnz c -5";
+
+    #[test]
+    fn test_disassemble() {
+        let program = parse_program("cpy 5 c\ninc a\ndec c\njnz c -2\njnz a 3\njnz 0 -4\njnz 1 b").unwrap();
+        assert_eq!("        cpy 5 c
+L1:     inc a
+        dec c
+        jnz c -2    ; L1
+        jnz a 3     ; halt
+        jnz 0 -4
+        jnz 1 b     ; computed
+",
+                   disassemble(&program));
+    }
+
+    #[test]
+    fn test_disassemble_round_trip() {
+        let program = parse_program(FACTORIAL).unwrap();
+        let text = disassemble(&program);
+        let reparsed: Vec<_> = text.lines()
+            .map(|line| {
+                let instr = line[8..].split(';').next().unwrap().trim();
+                parse_instr(instr).unwrap()
+            })
+            .collect();
+        assert_eq!(program, reparsed);
+    }
+
+    #[test]
+    fn test_basic_blocks() {
+        let program = parse_program("cpy 5 c\ninc a\ndec c\njnz c -2\njnz a 2\njnz 1 -5").unwrap();
+        assert_eq!(vec![Block {
+                            start: 0,
+                            end: 1,
+                            successors: vec![Successor::Block(1)],
+                        },
+                        Block {
+                            start: 1,
+                            end: 4,
+                            successors: vec![Successor::Block(1), Successor::Block(2)],
+                        },
+                        Block {
+                            start: 4,
+                            end: 5,
+                            successors: vec![Successor::Halt, Successor::Block(3)],
+                        },
+                        Block {
+                            start: 5,
+                            end: 6,
+                            successors: vec![Successor::Block(0)],
+                        }],
+                   basic_blocks(&program));
+        assert_eq!(vec![(1, 1), (3, 0)], back_edges(&basic_blocks(&program)));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let program = parse_program("cpy 5 c\ninc a\ndec c\njnz c -2\njnz 1 c").unwrap();
+        assert_eq!("digraph assembunny {
+    node [shape=box, fontname=\"monospace\"];
+    b0 [label=\"1: cpy 5 c\\l\"];
+    b1 [label=\"2: inc a\\l3: dec c\\l4: jnz c -2\\l\"];
+    b2 [label=\"5: jnz 1 c\\l\"];
+    b0 -> b1;
+    b1 -> b1 [color=red, label=\"loop\"];
+    b1 -> b2;
+    b2 -> computed [style=dashed];
+    computed [shape=oval, style=dashed];
+}
+",
+                   to_dot(&program));
+
+        // a nested multiply loop like day 23's, its countdown loop and the loop after the computed
+        // jump, which can't be reached until tgl changes the program
+        let dot = to_dot(&parse_program(FACTORIAL).unwrap());
+        assert_eq!(4, dot.matches("label=\"loop\"").count());
+    }
+}
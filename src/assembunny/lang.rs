@@ -0,0 +1,707 @@
+// assembunny/lang.rs
+// Bunny: a tiny structured language that compiles to assembunny, with a reference evaluator
+//
+//   a = 5
+//   b = a * 3
+//   while b != 0 {
+//       a += 2
+//       b -= 1
+//   }
+//   if a == 0 { b = 1 } else { b = a - 1 }
+//
+// The variables are registers a and b; the compiled loops need c and d. A statement assigns an
+// atom or two atoms joined by +, - or * (x += y is short for x = x + y), or runs a block while,
+// or if, a variable is or isn't 0. Atoms are variables and 32-bit numbers other than -2^31, which
+// can't be negated to count with, and arithmetic wraps around like the interpreter's.
+//
+// Assembunny can only add by counting a register down to 0, which takes about 2^32 steps from a
+// negative value. So a variable must not be negative when it's on the right of + or -, or on
+// either side of *; the evaluator reports it if one is. Numbers can be negative, but adding or
+// multiplying by one still counts through its size.
+
+use std::fmt::{self, Display};
+use ParseError;
+use super::{Arg, Instr, RegIdx, Registers, reg_from_name, reg_name};
+
+// Registers the compiled code counts with
+const INNER: RegIdx = 2;
+const OUTER: RegIdx = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Atom {
+    Var(RegIdx),
+    Num(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Expr {
+    Atom(Atom),
+    Binary(Atom, Op, Atom),
+}
+
+// "x == 0" or "x != 0"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cond {
+    pub var: RegIdx,
+    pub is_zero: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Assign(RegIdx, Expr),
+    While(Cond, Vec<Stmt>),
+    If(Cond, Vec<Stmt>, Vec<Stmt>),
+}
+
+// ///////
+// Parsing
+
+#[derive(Debug)]
+struct Token {
+    text: String,
+    line: usize,
+    column: usize,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = vec![];
+    for (i, line) in source.lines().enumerate() {
+        let chars: Vec<_> = line.chars().collect();
+        let mut column = 0;
+        while column < chars.len() {
+            let c = chars[column];
+            let len = if c.is_whitespace() {
+                column += 1;
+                continue;
+            } else if c.is_ascii_alphanumeric() || c == '_' {
+                chars[column..].iter().take_while(|&&c| c.is_ascii_alphanumeric() || c == '_').count()
+            } else if "+-*!=".contains(c) && chars.get(column + 1) == Some(&'=') {
+                2
+            } else if "{}=+-*".contains(c) {
+                1
+            } else {
+                return Err(ParseError::new(i + 1, column + 1, &c.to_string(), "a token"));
+            };
+            tokens.push(Token {
+                text: chars[column..column + len].iter().collect(),
+                line: i + 1,
+                column: column + 1,
+            });
+            column += len;
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+    // where the source ends, for errors about what's missing
+    end: (usize, usize),
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.next).map(|token| token.text.as_str())
+    }
+
+    fn error(&self, expected: &str) -> ParseError {
+        match self.tokens.get(self.next) {
+            Some(token) => ParseError::new(token.line, token.column, &token.text, expected),
+            None => ParseError::new(self.end.0, self.end.1, "", expected),
+        }
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), ParseError> {
+        if self.peek() == Some(text) {
+            self.next += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("'{}'", text)))
+        }
+    }
+
+    // Statements up to the end of the block or the source
+    fn block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut stmts = vec![];
+        while self.peek().is_some() && self.peek() != Some("}") {
+            stmts.push(self.stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    fn braced_block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        self.expect("{")?;
+        let stmts = self.block()?;
+        self.expect("}")?;
+        Ok(stmts)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, ParseError> {
+        match self.peek() {
+            Some("while") => {
+                self.next += 1;
+                let cond = self.cond()?;
+                Ok(Stmt::While(cond, self.braced_block()?))
+            }
+            Some("if") => {
+                self.next += 1;
+                let cond = self.cond()?;
+                let then = self.braced_block()?;
+                let otherwise = if self.peek() != Some("else") {
+                    vec![]
+                } else if self.tokens.get(self.next + 1).map(|token| token.text.as_str()) == Some("if") {
+                    self.next += 1;
+                    vec![self.stmt()?]
+                } else {
+                    self.next += 1;
+                    self.braced_block()?
+                };
+                Ok(Stmt::If(cond, then, otherwise))
+            }
+            _ => self.assign(),
+        }
+    }
+
+    fn assign(&mut self) -> Result<Stmt, ParseError> {
+        let var = self.var()?;
+        let start = self.next;
+        let expr = match self.peek() {
+            Some("=") => {
+                self.next += 1;
+                self.expr()?
+            }
+            Some("+=") | Some("-=") | Some("*=") => {
+                let op = if self.peek() == Some("+=") {
+                    Op::Add
+                } else if self.peek() == Some("-=") {
+                    Op::Sub
+                } else {
+                    Op::Mul
+                };
+                self.next += 1;
+                Expr::Binary(Atom::Var(var), op, self.atom()?)
+            }
+            _ => return Err(self.error("'=', '+=', '-=' or '*='")),
+        };
+        if expr == Expr::Binary(Atom::Var(var), Op::Mul, Atom::Var(var)) {
+            // it would need a third register to count with
+            self.next = start;
+            return Err(self.error("a product that isn't the variable squared in place"));
+        }
+        Ok(Stmt::Assign(var, expr))
+    }
+
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        let left = self.atom()?;
+        let op = match self.peek() {
+            Some("+") => Op::Add,
+            Some("-") => Op::Sub,
+            Some("*") => Op::Mul,
+            _ => return Ok(Expr::Atom(left)),
+        };
+        self.next += 1;
+        Ok(Expr::Binary(left, op, self.atom()?))
+    }
+
+    fn cond(&mut self) -> Result<Cond, ParseError> {
+        let var = self.var()?;
+        let is_zero = match self.peek() {
+            Some("==") => true,
+            Some("!=") => false,
+            _ => return Err(self.error("'==' or '!='")),
+        };
+        self.next += 1;
+        self.expect("0")?;
+        Ok(Cond { var, is_zero })
+    }
+
+    fn var(&mut self) -> Result<RegIdx, ParseError> {
+        match self.peek().and_then(reg_from_name) {
+            Some(reg_idx) if reg_idx < INNER => {
+                self.next += 1;
+                Ok(reg_idx)
+            }
+            _ => Err(self.error("a variable, a or b")),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Atom, ParseError> {
+        let negative = self.peek() == Some("-");
+        if negative {
+            self.next += 1;
+        }
+        let digits = self.peek().is_some_and(|text| text.chars().all(|c| c.is_ascii_digit()));
+        if digits {
+            let text = format!("{}{}", if negative { "-" } else { "" }, self.peek().unwrap());
+            let num: i32 = text.parse().map_err(|_| self.error("a 32-bit integer"))?;
+            if num == i32::MIN {
+                return Err(self.error("a number greater than -2147483648"));
+            }
+            self.next += 1;
+            Ok(Atom::Num(num))
+        } else if negative {
+            Err(self.error("a number"))
+        } else {
+            self.var().map(Atom::Var).map_err(|_| self.error("a variable or a number"))
+        }
+    }
+}
+
+pub fn parse(source: &str) -> Result<Vec<Stmt>, ParseError> {
+    let lines: Vec<_> = source.lines().collect();
+    let end = (lines.len().max(1), lines.last().map_or(0, |line| line.chars().count()) + 1);
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        next: 0,
+        end,
+    };
+    let stmts = parser.block()?;
+    match parser.peek() {
+        None => Ok(stmts),
+        Some(_) => Err(parser.error("a statement")),
+    }
+}
+
+// /////////
+// Compiling
+
+fn jump(offset: usize) -> Instr {
+    Instr::Jnz(Arg::Imm(1), Arg::Imm(offset as i32))
+}
+
+fn jump_back(offset: usize) -> Instr {
+    Instr::Jnz(Arg::Imm(1), Arg::Imm(-(offset as i32)))
+}
+
+fn arg(atom: Atom) -> Arg {
+    match atom {
+        Atom::Var(reg_idx) => Arg::Reg(reg_idx),
+        Atom::Num(num) => Arg::Imm(num),
+    }
+}
+
+// Add (or with Op::Sub, subtract) an atom to dest, using INNER to count with
+fn add_atom(code: &mut Vec<Instr>, dest: RegIdx, atom: Atom, op: Op) {
+    let (step, count) = match (op, atom) {
+        (Op::Add, Atom::Num(num)) if num < 0 => (Instr::Dec(Arg::Reg(dest)), Atom::Num(num.wrapping_neg())),
+        (Op::Sub, Atom::Num(num)) if num < 0 => (Instr::Inc(Arg::Reg(dest)), Atom::Num(num.wrapping_neg())),
+        (Op::Sub, _) => (Instr::Dec(Arg::Reg(dest)), atom),
+        _ => (Instr::Inc(Arg::Reg(dest)), atom),
+    };
+    match count {
+        Atom::Num(0) => {}
+        // a few steps are shorter written out
+        Atom::Num(num) if (0..=3).contains(&num) => code.extend((0..num).map(|_| step)),
+        _ => {
+            code.push(Instr::Cpy(arg(count), Arg::Reg(INNER)));
+            if let Atom::Var(_) = count {
+                // skip the loop if there's nothing to count
+                code.push(Instr::Jnz(Arg::Reg(INNER), Arg::Imm(2)));
+                code.push(jump(4));
+            }
+            code.push(step);
+            code.push(Instr::Dec(Arg::Reg(INNER)));
+            code.push(Instr::Jnz(Arg::Reg(INNER), Arg::Imm(-2)));
+        }
+    }
+}
+
+fn compile_assign(code: &mut Vec<Instr>, dest: RegIdx, expr: Expr) {
+    let (left, op, right) = match expr {
+        Expr::Atom(atom) => {
+            if atom != Atom::Var(dest) {
+                code.push(Instr::Cpy(arg(atom), Arg::Reg(dest)));
+            }
+            return;
+        }
+        Expr::Binary(left, op, right) => (left, op, right),
+    };
+
+    match op {
+        Op::Add | Op::Sub if right != Atom::Var(dest) || left == Atom::Var(dest) => {
+            if left != Atom::Var(dest) {
+                code.push(Instr::Cpy(arg(left), Arg::Reg(dest)));
+            }
+            add_atom(code, dest, right, op);
+        }
+        Op::Add | Op::Sub => {
+            // dest = left +/- dest: keep dest to count down with
+            let step = if op == Op::Add { Instr::Inc(Arg::Reg(dest)) } else { Instr::Dec(Arg::Reg(dest)) };
+            code.push(Instr::Cpy(Arg::Reg(dest), Arg::Reg(OUTER)));
+            code.push(Instr::Cpy(arg(left), Arg::Reg(dest)));
+            code.push(Instr::Jnz(Arg::Reg(OUTER), Arg::Imm(2)));
+            code.push(jump(4));
+            code.push(step);
+            code.push(Instr::Dec(Arg::Reg(OUTER)));
+            code.push(Instr::Jnz(Arg::Reg(OUTER), Arg::Imm(-2)));
+        }
+        Op::Mul => {
+            let (times, addend) = match (left, right) {
+                (Atom::Num(left), Atom::Num(right)) => {
+                    code.push(Instr::Cpy(Arg::Imm(left.wrapping_mul(right)), Arg::Reg(dest)));
+                    return;
+                }
+                // count with a variable when there is one, and not with dest, which gets cleared
+                (Atom::Num(_), _) => (right, left),
+                (_, Atom::Var(right_var)) if right_var == dest => (right, left),
+                _ => (left, right),
+            };
+            // dest = addend added up times times
+            let mut body = vec![];
+            add_atom(&mut body, dest, addend, Op::Add);
+            body.push(Instr::Dec(Arg::Reg(OUTER)));
+            let body_len = body.len();
+            code.push(Instr::Cpy(arg(times), Arg::Reg(OUTER)));
+            code.push(Instr::Cpy(Arg::Imm(0), Arg::Reg(dest)));
+            code.push(Instr::Jnz(Arg::Reg(OUTER), Arg::Imm(2)));
+            code.push(jump(body_len + 2));
+            code.extend(body);
+            code.push(Instr::Jnz(Arg::Reg(OUTER), Arg::Imm(-(body_len as i32))));
+        }
+    }
+}
+
+fn compile_block(code: &mut Vec<Instr>, stmts: &[Stmt]) {
+    for stmt in stmts {
+        match *stmt {
+            Stmt::Assign(dest, expr) => compile_assign(code, dest, expr),
+            Stmt::While(cond, ref body) => {
+                let mut body_code = vec![];
+                compile_block(&mut body_code, body);
+                let body_len = body_code.len();
+                if cond.is_zero {
+                    code.push(Instr::Jnz(Arg::Reg(cond.var), Arg::Imm(body_len as i32 + 2)));
+                    code.extend(body_code);
+                    code.push(jump_back(body_len + 1));
+                } else {
+                    code.push(Instr::Jnz(Arg::Reg(cond.var), Arg::Imm(2)));
+                    code.push(jump(body_len + 2));
+                    code.extend(body_code);
+                    code.push(jump_back(body_len + 2));
+                }
+            }
+            Stmt::If(cond, ref then, ref otherwise) => {
+                // "x == 0" is "x != 0" the other way round
+                let (then, otherwise) = if cond.is_zero { (otherwise, then) } else { (then, otherwise) };
+                let mut then_code = vec![];
+                compile_block(&mut then_code, then);
+                let mut otherwise_code = vec![];
+                compile_block(&mut otherwise_code, otherwise);
+
+                code.push(Instr::Jnz(Arg::Reg(cond.var), Arg::Imm(2)));
+                if otherwise_code.is_empty() {
+                    code.push(jump(then_code.len() + 1));
+                    code.extend(then_code);
+                } else {
+                    code.push(jump(then_code.len() + 2));
+                    code.extend(then_code);
+                    code.push(jump(otherwise_code.len() + 1));
+                    code.extend(otherwise_code);
+                }
+            }
+        }
+    }
+}
+
+// The program as assembunny source, one instruction per line
+pub fn compile(stmts: &[Stmt]) -> String {
+    let mut code = vec![];
+    compile_block(&mut code, stmts);
+    code.iter().map(|instr| format!("{}\n", instr)).collect()
+}
+
+// //////////
+// Evaluating
+
+// Why the evaluator couldn't give a result
+#[derive(Debug, PartialEq)]
+pub enum EvalError {
+    // a variable the compiled code would count down with was negative
+    Negative(RegIdx),
+    // more work than the evaluator was allowed
+    TooLong,
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EvalError::Negative(reg_idx) => write!(f, "{} was counted down from a negative value", reg_name(reg_idx)),
+            EvalError::TooLong => write!(f, "the program ran too long"),
+        }
+    }
+}
+
+struct Evaluator {
+    registers: Registers,
+    steps_left: u64,
+}
+
+impl Evaluator {
+    fn spend(&mut self, steps: u64) -> Result<(), EvalError> {
+        if steps > self.steps_left {
+            return Err(EvalError::TooLong);
+        }
+        self.steps_left -= steps;
+        Ok(())
+    }
+
+    fn value(&self, atom: Atom) -> i32 {
+        match atom {
+            Atom::Var(reg_idx) => self.registers[reg_idx],
+            Atom::Num(num) => num,
+        }
+    }
+
+    // The value of a variable that's counted down with, and how many counts that takes
+    fn count(&self, atom: Atom) -> Result<(i32, u64), EvalError> {
+        match atom {
+            Atom::Var(reg_idx) if self.registers[reg_idx] < 0 => Err(EvalError::Negative(reg_idx)),
+            Atom::Var(reg_idx) => Ok((self.registers[reg_idx], self.registers[reg_idx] as u64)),
+            Atom::Num(num) => Ok((num, num.unsigned_abs() as u64)),
+        }
+    }
+
+    fn eval(&mut self, expr: Expr) -> Result<i32, EvalError> {
+        let (left, op, right) = match expr {
+            Expr::Atom(atom) => return Ok(self.value(atom)),
+            Expr::Binary(left, op, right) => (left, op, right),
+        };
+        let (right_val, right_counts) = self.count(right)?;
+        match op {
+            Op::Add | Op::Sub => {
+                self.spend(right_counts)?;
+                let left_val = self.value(left);
+                Ok(if op == Op::Add { left_val.wrapping_add(right_val) } else { left_val.wrapping_sub(right_val) })
+            }
+            // the compiler works out a product of two numbers itself
+            Op::Mul if matches!((left, right), (Atom::Num(_), Atom::Num(_))) => {
+                Ok(self.value(left).wrapping_mul(right_val))
+            }
+            Op::Mul => {
+                let (left_val, left_counts) = self.count(left)?;
+                self.spend(left_counts.saturating_mul(right_counts.max(1)) + right_counts)?;
+                Ok(left_val.wrapping_mul(right_val))
+            }
+        }
+    }
+
+    fn holds(&self, cond: Cond) -> bool {
+        (self.registers[cond.var] == 0) == cond.is_zero
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) -> Result<(), EvalError> {
+        for stmt in stmts {
+            self.spend(1)?;
+            match *stmt {
+                Stmt::Assign(dest, expr) => self.registers[dest] = self.eval(expr)?,
+                Stmt::While(cond, ref body) => {
+                    while self.holds(cond) {
+                        self.block(body)?;
+                        self.spend(1)?;
+                    }
+                }
+                Stmt::If(cond, ref then, ref otherwise) => {
+                    self.block(if self.holds(cond) { then } else { otherwise })?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Run a program directly, giving up after max_steps steps of work. Each statement and loop test
+// is a step, and so is each count the compiled code's loops would make, so max_steps also
+// limits how long the compiled program runs. The registers the compiler counts with are left as
+// they were.
+pub fn evaluate(stmts: &[Stmt], registers: Registers, max_steps: u64) -> Result<Registers, EvalError> {
+    let mut evaluator = Evaluator {
+        registers,
+        steps_left: max_steps,
+    };
+    evaluator.block(stmts)?;
+    Ok(evaluator.registers)
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assembunny::{CompiledProgram, Limits, Outcome, ProgramState, execute_with_limits, parse_program};
+
+    const EXAMPLE: &str = "\
+a = 5
+b = a * 3
+while b != 0 {
+    a += 2
+    b -= 1
+}
+if a == 0 { b = 1 } else { b = a - 1 }";
+
+    // Compile a program and run it both ways, returning the variables, or None if it doesn't
+    // finish within the evaluator's limit. The compiled program must agree with the evaluator
+    // with and without optimization, and on the closure backend too.
+    fn run_both(stmts: &[Stmt], start: Registers) -> Option<[i32; 2]> {
+        let evaluated = evaluate(stmts, start, 10_000).ok()?;
+        let instrs = parse_program(&compile(stmts)).unwrap();
+        let limits = Limits {
+            max_steps: Some(1_000_000),
+            detect_loops: false,
+        };
+        let (outcome, optimized) = execute_with_limits(&instrs, start, limits);
+        assert_eq!(Outcome::Halted, outcome, "{:?}", stmts);
+        let mut plain = ProgramState::new(instrs.clone());
+        plain.registers = start;
+        assert_eq!(Outcome::Halted, plain.run_with_limits(limits), "{:?}", stmts);
+        let mut compiled = CompiledProgram::optimized(instrs);
+        compiled.registers = start;
+        compiled.run();
+
+        let variables = [evaluated[0], evaluated[1]];
+        assert_eq!(variables, [optimized.registers[0], optimized.registers[1]], "{:?}", stmts);
+        assert_eq!(variables, [plain.registers[0], plain.registers[1]], "{:?}", stmts);
+        assert_eq!(plain.registers, compiled.registers, "{:?}", stmts);
+        Some(variables)
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(vec![Stmt::Assign(0, Expr::Atom(Atom::Num(-5))),
+                        Stmt::Assign(1, Expr::Binary(Atom::Var(1), Op::Mul, Atom::Var(0))),
+                        Stmt::If(Cond {
+                                     var: 0,
+                                     is_zero: true,
+                                 },
+                                 vec![],
+                                 vec![Stmt::If(Cond {
+                                                   var: 1,
+                                                   is_zero: false,
+                                               },
+                                               vec![Stmt::Assign(0, Expr::Binary(Atom::Num(3), Op::Sub, Atom::Var(1)))],
+                                               vec![])])],
+                   parse("a = -5\nb *= a\nif a == 0 {} else if b != 0 { a = 3 - b }").unwrap());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Err(ParseError::new(1, 1, "c", "a variable, a or b")), parse("c = 1"));
+        assert_eq!(Err(ParseError::new(2, 7, "/", "a token")), parse("a = 1\nb = a / 2"));
+        assert_eq!(Err(ParseError::new(1, 12, "1", "'0'")), parse("while a != 1 {}"));
+        assert_eq!(Err(ParseError::new(1, 15, "", "'}'")), parse("while a != 0 {"));
+        assert_eq!(Err(ParseError::new(1, 6, "99999999999", "a 32-bit integer")), parse("a = -99999999999"));
+        assert_eq!(Err(ParseError::new(1, 7, "2147483648", "a number greater than -2147483648")),
+                   parse("a += -2147483648"));
+        assert_eq!(Err(ParseError::new(1, 3, "*=", "a product that isn't the variable squared in place")),
+                   parse("a *= a"));
+        assert_eq!(Err(ParseError::new(1, 1, "}", "a statement")), parse("} a = 1"));
+    }
+
+    #[test]
+    fn test_compile() {
+        let stmts = parse("a = 2\nwhile a != 0 { a -= 1 }\nb = a + 7").unwrap();
+        assert_eq!("cpy 2 a\njnz a 2\njnz 1 3\ndec a\njnz 1 -3\ncpy a b\ncpy 7 c\ninc b\ndec c\njnz c -2\n",
+                   compile(&stmts));
+        // every line parses as puzzle input does
+        let instrs = parse_program(&compile(&parse(EXAMPLE).unwrap())).unwrap();
+        assert!(instrs.iter().all(|instr| matches!(*instr, Instr::Cpy(..) | Instr::Inc(..) | Instr::Dec(..) | Instr::Jnz(..))));
+    }
+
+    #[test]
+    fn test_run_example() {
+        let stmts = parse(EXAMPLE).unwrap();
+        assert_eq!(Some([35, 34]), run_both(&stmts, [0; 4]));
+        // squares, with the variables on every side of every operator
+        let squares = parse("b = a * a\na = b * a\nb = a - b\na = a - b\nb = b + b\na = 2 * a\nb = a + b\na = 0 - a")
+            .unwrap();
+        assert_eq!(Some([-18, 54]), run_both(&squares, [3, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        let stmts = parse("a = 0 - 4\nb = 3 + a\nb = 3 - a").unwrap();
+        assert_eq!(Err(EvalError::Negative(0)), evaluate(&stmts, [0; 4], 100));
+        let stmts = parse("a = 1\nwhile a != 0 { b += 1 }").unwrap();
+        assert_eq!(Err(EvalError::TooLong), evaluate(&stmts, [0; 4], 100));
+        // adding a number counts through it, either way round
+        let stmts = parse("a = b + 1000000").unwrap();
+        assert_eq!(Err(EvalError::TooLong), evaluate(&stmts, [0; 4], 100));
+        let stmts = parse("a = b - -1000000").unwrap();
+        assert_eq!(Err(EvalError::TooLong), evaluate(&stmts, [0; 4], 100));
+        let stmts = parse("a = 1000000 * 1000").unwrap();
+        assert_eq!(Ok([1_000_000_000, 0, 0, 0]), evaluate(&stmts, [0; 4], 100));
+    }
+
+    // A small random number generator, so the programs are the same every run
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn below(&mut self, n: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % n
+        }
+    }
+
+    fn random_atom(rng: &mut XorShift) -> Atom {
+        if rng.below(2) == 0 {
+            Atom::Var(rng.below(2) as RegIdx)
+        } else {
+            Atom::Num(rng.below(11) as i32 - 3)
+        }
+    }
+
+    fn random_block(rng: &mut XorShift, depth: u32) -> Vec<Stmt> {
+        (0..1 + rng.below(4)).map(|_| random_stmt(rng, depth)).collect()
+    }
+
+    fn random_stmt(rng: &mut XorShift, depth: u32) -> Stmt {
+        let var = rng.below(2) as RegIdx;
+        let cond = Cond {
+            var,
+            is_zero: rng.below(2) == 0,
+        };
+        match rng.below(if depth == 0 { 6 } else { 8 }) {
+            0 => Stmt::Assign(var, Expr::Atom(random_atom(rng))),
+            1..=5 => {
+                let op = [Op::Add, Op::Sub, Op::Mul][rng.below(3) as usize];
+                let expr = Expr::Binary(random_atom(rng), op, random_atom(rng));
+                if expr == Expr::Binary(Atom::Var(var), Op::Mul, Atom::Var(var)) {
+                    Stmt::Assign(var, Expr::Atom(Atom::Var(var)))
+                } else {
+                    Stmt::Assign(var, expr)
+                }
+            }
+            6 => Stmt::If(cond, random_block(rng, depth - 1), random_block(rng, depth - 1)),
+            // a loop that counts down, though its body may undo that
+            _ => {
+                let mut body = random_block(rng, depth - 1);
+                body.push(Stmt::Assign(var, Expr::Binary(Atom::Var(var), Op::Sub, Atom::Num(1))));
+                Stmt::While(cond, body)
+            }
+        }
+    }
+
+    #[test]
+    fn test_differential() {
+        let mut rng = XorShift(0x2016_1225);
+        let mut finished = 0;
+        for _ in 0..300 {
+            let stmts = random_block(&mut rng, 2);
+            let start = [rng.below(5) as i32, rng.below(5) as i32, 0, 0];
+            if run_both(&stmts, start).is_some() {
+                finished += 1;
+            }
+        }
+        // most programs are usable
+        assert!(finished > 150, "only {} programs finished", finished);
+    }
+}
@@ -0,0 +1,419 @@
+// assembunny/machine.rs
+// A VM with a configurable register file: how many registers there are, their names and width
+//
+// The days run on the puzzles' four 32-bit registers a to d. Machine is a ProgramState on the
+// registers a Config describes instead, so it runs the same instructions, optimized the same way.
+// Values are held as i128 and brought back to the configured width whenever arithmetic changes
+// one: wrapped around as the machine's own integers would be or, with checked arithmetic,
+// reported as an overflow. Numbers written in the program are still 32-bit.
+
+use std::fmt::{self, Display};
+use std::rc::Rc;
+use std::str::FromStr;
+use {ParseError, parse_lines};
+use super::{Arg, Instr, Limits, Outcome, ProgramState, REGISTER_COUNT, RegIdx, RegisterFile, Syntax, reg_name};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Width {
+    I32,
+    I64,
+    I128,
+}
+
+impl Width {
+    // val wrapped around to fit in this width
+    fn wrap(self, val: i128) -> i128 {
+        match self {
+            Width::I32 => val as i32 as i128,
+            Width::I64 => val as i64 as i128,
+            Width::I128 => val,
+        }
+    }
+}
+
+impl FromStr for Width {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Width, String> {
+        match s {
+            "i32" => Ok(Width::I32),
+            "i64" => Ok(Width::I64),
+            "i128" => Ok(Width::I128),
+            _ => Err(format!("invalid width '{}'", s)),
+        }
+    }
+}
+
+impl Display for Width {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Width::I32 => write!(f, "i32"),
+            Width::I64 => write!(f, "i64"),
+            Width::I128 => write!(f, "i128"),
+        }
+    }
+}
+
+// The registers a Machine has
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Config {
+    names: Vec<String>,
+    width: Width,
+    // report overflow instead of wrapping around
+    checked: bool,
+}
+
+impl Config {
+    // Register names must be words (letters, digits and underscores, not starting with a digit)
+    // so they can't be mistaken for numbers, and there must be at least one
+    pub fn new(names: &[&str], width: Width, checked: bool) -> Result<Config, String> {
+        if names.is_empty() {
+            return Err("there must be at least one register".to_string());
+        }
+        for (i, name) in names.iter().enumerate() {
+            let starts_well = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+            if !starts_well || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("register name '{}' isn't a word", name));
+            }
+            if names[..i].contains(name) {
+                return Err(format!("register name '{}' is used twice", name));
+            }
+        }
+        Ok(Config {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            width,
+            checked,
+        })
+    }
+
+    // The same registers at another width
+    pub fn with_width(self, width: Width, checked: bool) -> Config {
+        Config {
+            width,
+            checked,
+            ..self
+        }
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn width(&self) -> Width {
+        self.width
+    }
+
+    pub fn checked(&self) -> bool {
+        self.checked
+    }
+}
+
+impl Default for Config {
+    // The puzzles' registers
+    fn default() -> Config {
+        Config {
+            names: (0..REGISTER_COUNT).map(|reg_idx| reg_name(reg_idx).to_string()).collect(),
+            width: Width::I32,
+            checked: false,
+        }
+    }
+}
+
+// Checked arithmetic went out of range: the value is left as it was, and the program counter
+// stays on the instruction that did it (the start of the loop, for a fused one)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Overflow {
+    pub program_counter: usize,
+    pub width: Width,
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {} overflowed {}", self.program_counter + 1, self.width)
+    }
+}
+
+// Parse a program that uses the config's register names
+pub fn parse_program_with(input: &str, config: &Config) -> Result<Vec<Instr>, ParseError> {
+    let syntax = Syntax::new(&config.names);
+    parse_lines(input, |line| syntax.parse_instr(line))
+}
+
+// The registers an instruction mentions
+fn registers_used(instr: &Instr) -> Vec<RegIdx> {
+    let args = match *instr {
+        Instr::Cpy(x, y) | Instr::Jnz(x, y) => vec![x, y],
+        Instr::Inc(x) | Instr::Dec(x) | Instr::Tgl(x) | Instr::Out(x) => vec![x],
+        Instr::Add(src, dest) => vec![Arg::Reg(src), Arg::Reg(dest)],
+        Instr::Mul(factor, scratch, counter, dest) => vec![factor, Arg::Reg(scratch), Arg::Reg(counter), Arg::Reg(dest)],
+    };
+    args.into_iter()
+        .filter_map(|arg| match arg {
+            Arg::Reg(reg_idx) => Some(reg_idx),
+            Arg::Imm(_) => None,
+        })
+        .collect()
+}
+
+// The registers a Config describes, each always within its width
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RegisterSet {
+    config: Rc<Config>,
+    values: Vec<i128>,
+}
+
+impl RegisterFile for RegisterSet {
+    type Error = Overflow;
+
+    fn get(&self, reg_idx: RegIdx) -> i128 {
+        self.values[reg_idx]
+    }
+
+    fn store(&mut self, reg_idx: RegIdx, checked: Option<i128>, wrapping: i128, program_counter: usize)
+             -> Result<(), Overflow> {
+        let width = self.config.width;
+        self.values[reg_idx] = if self.config.checked {
+            checked.filter(|&val| width.wrap(val) == val).ok_or(Overflow {
+                program_counter,
+                width,
+            })?
+        } else {
+            width.wrap(wrapping)
+        };
+        Ok(())
+    }
+}
+
+// A running program on a configured set of registers
+pub type Machine = ProgramState<RegisterSet>;
+
+impl ProgramState<RegisterSet> {
+    // All registers start as 0. Fails if the program uses a register the config doesn't have.
+    pub fn with_config(config: Config, instrs: Vec<Instr>) -> Result<Machine, String> {
+        for (idx, instr) in instrs.iter().enumerate() {
+            if registers_used(instr).iter().any(|&reg_idx| reg_idx >= config.names.len()) {
+                return Err(format!("line {} uses a register beyond the {} there are", idx + 1, config.names.len()));
+            }
+        }
+        let registers = RegisterSet {
+            values: vec![0; config.names.len()],
+            config: Rc::new(config),
+        };
+        Ok(ProgramState::with_registers(registers, instrs))
+    }
+
+    // Like with_config, but runs the optimized version of the program
+    pub fn optimized_with_config(config: Config, instrs: Vec<Instr>) -> Result<Machine, String> {
+        let mut machine = Machine::with_config(config, instrs)?;
+        machine.enable_optimization();
+        Ok(machine)
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.registers.config
+    }
+
+    // Register values in the order of the config's names
+    pub fn register_values(&self) -> &[i128] {
+        &self.registers.values
+    }
+
+    pub fn register(&self, name: &str) -> Option<i128> {
+        self.reg_idx(name).map(|reg_idx| self.registers.values[reg_idx])
+    }
+
+    pub fn set_register(&mut self, name: &str, val: i128) -> Result<(), String> {
+        let reg_idx = self.reg_idx(name).ok_or_else(|| format!("there's no register '{}'", name))?;
+        let width = self.registers.config.width;
+        if width.wrap(val) != val {
+            return Err(format!("{} doesn't fit in {}", val, width));
+        }
+        self.registers.values[reg_idx] = val;
+        Ok(())
+    }
+
+    fn reg_idx(&self, name: &str) -> Option<RegIdx> {
+        self.registers.config.names.iter().position(|reg| reg == name)
+    }
+
+    // Execute the next instruction, returning the value sent by an out instruction
+    pub fn step(&mut self) -> Result<Option<i128>, Overflow> {
+        self.try_step()
+    }
+
+    // Run until the program halts or overflows, ignoring anything it sends
+    pub fn run(&mut self) -> Result<(), Overflow> {
+        while !self.is_halted() {
+            self.try_step()?;
+        }
+        Ok(())
+    }
+
+    // Like ProgramState::run_with_limits
+    pub fn run_with_limits(&mut self, limits: Limits) -> Result<Outcome, Overflow> {
+        self.try_run_with_limits(limits)
+    }
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assembunny::{ProgramState, parse_program};
+
+    // Day 23's way of working out a factorial, without the toggles: a = a!
+    const FACTORIAL: &str = "\
+cpy a b
+dec b
+cpy a d
+cpy 0 a
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5
+dec b
+jnz b -9";
+
+    fn factorial(n: i128, width: Width, checked: bool) -> Result<i128, Overflow> {
+        let config = Config::new(&["a", "b", "c", "d"], width, checked).unwrap();
+        let mut machine = Machine::optimized_with_config(config, parse_program(FACTORIAL).unwrap()).unwrap();
+        machine.set_register("a", n).unwrap();
+        machine.run()?;
+        Ok(machine.register_values()[0])
+    }
+
+    #[test]
+    fn test_config() {
+        assert!(Config::new(&["x", "y_2", "_count"], Width::I64, true).is_ok());
+        assert_eq!(Err("there must be at least one register".to_string()),
+                   Config::new(&[], Width::I32, false));
+        assert_eq!(Err("register name '2x' isn't a word".to_string()),
+                   Config::new(&["a", "2x"], Width::I32, false));
+        assert_eq!(Err("register name 'b' is used twice".to_string()),
+                   Config::new(&["a", "b", "b"], Width::I32, false));
+
+        let config = Config::new(&["a", "b"], Width::I32, false).unwrap();
+        assert_eq!(Err("line 2 uses a register beyond the 2 there are".to_string()),
+                   Machine::with_config(config, parse_program("inc a\ncpy 1 c").unwrap()).map(|_| ()));
+    }
+
+    #[test]
+    fn test_with_width() {
+        let config = Config::default().with_width(Width::I64, true);
+        assert_eq!(&["a", "b", "c", "d"], config.names());
+        assert_eq!((Width::I64, true), (config.width(), config.checked()));
+        assert_eq!(Ok(Width::I128), "i128".parse());
+        assert_eq!(Err("invalid width 'i16'".to_string()), "i16".parse::<Width>());
+    }
+
+    #[test]
+    fn test_parse_program_with() {
+        let config = Config::new(&["x", "y", "count", "e"], Width::I32, false).unwrap();
+        let program = parse_program_with("cpy 3 count\ninc x\ndec count\njnz count -2\nout e", &config).unwrap();
+        assert_eq!(parse_program("cpy 3 c\ninc a\ndec c\njnz c -2\nout d").unwrap(), program);
+        assert_eq!(Err(ParseError::new(2, 1, "inc a", "an instruction")),
+                   parse_program_with("inc x\ninc a", &config));
+
+        let mut machine = Machine::optimized_with_config(config, program).unwrap();
+        machine.run().unwrap();
+        assert_eq!(Some(3), machine.register("x"));
+        assert_eq!(None, machine.register("a"));
+    }
+
+    #[test]
+    fn test_machine_matches_program_state() {
+        // day 23's example, which toggles, and jumping into the middle of a fused loop
+        let programs = ["cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a",
+                        "jnz 1 2\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5\njnz 1 -12"];
+        for source in &programs {
+            let instrs = parse_program(source).unwrap();
+            for &optimized in &[false, true] {
+                let mut program_state = if optimized {
+                    ProgramState::optimized(instrs.clone())
+                } else {
+                    ProgramState::new(instrs.clone())
+                };
+                program_state.registers = [0, 3, 4, 2];
+                program_state.run();
+
+                let mut machine = if optimized {
+                    Machine::optimized_with_config(Config::default(), instrs.clone()).unwrap()
+                } else {
+                    Machine::with_config(Config::default(), instrs.clone()).unwrap()
+                };
+                for (name, &val) in ["b", "c", "d"].iter().zip(&[3, 4, 2]) {
+                    machine.set_register(name, val).unwrap();
+                }
+                machine.run().unwrap();
+                let registers: Vec<_> = program_state.registers.iter().map(|&val| val as i128).collect();
+                assert_eq!(&registers[..], machine.register_values());
+                assert_eq!(program_state.instrs, machine.instrs);
+            }
+        }
+    }
+
+    #[test]
+    fn test_width() {
+        assert_eq!(Ok(5040), factorial(7, Width::I32, true));
+        // 13! doesn't fit in 32 bits
+        assert_eq!(Err(Overflow {
+                       program_counter: 4,
+                       width: Width::I32,
+                   }),
+                   factorial(13, Width::I32, true));
+        assert_eq!(Ok(6227020800), factorial(13, Width::I64, true));
+        assert_eq!(Ok(265252859812191058636308480000000), factorial(30, Width::I128, true));
+
+        let config = Config::new(&["a"], Width::I64, true).unwrap();
+        let mut machine = Machine::with_config(config, parse_program("inc a").unwrap()).unwrap();
+        assert_eq!(Err("9223372036854775808 doesn't fit in i64".to_string()),
+                   machine.set_register("a", 1 << 63));
+        machine.set_register("a", i64::MAX as i128).unwrap();
+        assert_eq!(Err(Overflow {
+                       program_counter: 0,
+                       width: Width::I64,
+                   }),
+                   machine.step());
+        assert_eq!("line 1 overflowed i64", machine.run().unwrap_err().to_string());
+        assert_eq!(Some(i64::MAX as i128), machine.register("a"));
+
+        // without checking, it wraps around
+        let config = Config::new(&["a"], Width::I32, false).unwrap();
+        let mut machine = Machine::with_config(config, parse_program("cpy 2147483647 a\ninc a").unwrap()).unwrap();
+        machine.run().unwrap();
+        assert_eq!(Some(i32::MIN as i128), machine.register("a"));
+    }
+
+    #[test]
+    fn test_huge_offsets() {
+        // offsets so big the target doesn't fit either: the jump halts and the toggle does nothing
+        let config = Config::default().with_width(Width::I128, false);
+        for &(source, b) in &[("inc b\njnz 1 a\ninc b", 1), ("inc b\ntgl a\ninc b", 2)] {
+            let instrs = parse_program_with(source, &config).unwrap();
+            let mut machine = Machine::with_config(config.clone(), instrs.clone()).unwrap();
+            machine.set_register("a", i128::MAX).unwrap();
+            machine.run().unwrap();
+            assert!(machine.is_halted());
+            assert_eq!(Some(b), machine.register("b"));
+            assert_eq!(instrs, machine.instrs);
+        }
+    }
+
+    #[test]
+    fn test_machine_limits() {
+        let config = Config::new(&["x"], Width::I128, false).unwrap();
+        let limits = Limits {
+            max_steps: Some(1000),
+            detect_loops: true,
+        };
+        let instrs = parse_program_with("inc x\ndec x\njnz 1 -2", &config).unwrap();
+        let mut machine = Machine::with_config(config.clone(), instrs).unwrap();
+        assert_eq!(Ok(Outcome::Looping { period: 3 }), machine.run_with_limits(limits));
+        let instrs = parse_program_with("inc x\njnz 1 -1", &config).unwrap();
+        let mut machine = Machine::with_config(config, instrs).unwrap();
+        assert_eq!(Ok(Outcome::BudgetExceeded), machine.run_with_limits(limits));
+    }
+}
@@ -0,0 +1,889 @@
+// assembunny/mod.rs
+// The assembunny language from days 12, 23 and 25: parser, instruction set and interpreter
+
+use std::convert::Infallible;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::ops::Range;
+use regex::Regex;
+use {ParseError, parse_lines};
+
+pub mod compiled;
+pub mod debugger;
+pub mod decompile;
+pub mod disasm;
+pub mod lang;
+pub mod machine;
+pub mod profile;
+pub mod snapshot;
+
+pub use self::compiled::CompiledProgram;
+pub use self::debugger::Debugger;
+pub use self::decompile::{Decompiled, decompile};
+pub use self::disasm::{disassemble, to_dot};
+pub use self::machine::{Config, Machine, Overflow, RegisterSet, Width, parse_program_with};
+pub use self::profile::{Profile, Trace, profile_until_halt};
+
+pub type RegIdx = usize;
+
+pub const REGISTER_COUNT: usize = 4;
+
+pub type Registers = [i32; REGISTER_COUNT];
+
+// all known instruction types
+//
+// The parser only accepts registers where the puzzle needs them (inc, dec, tgl and the
+// destination of cpy), but tgl can turn an instruction into nonsense like "cpy 1 2" or "inc 3".
+// Those are skipped when executed, so every argument is an Arg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instr {
+    Cpy(Arg, Arg),
+    Inc(Arg),
+    Dec(Arg),
+    Jnz(Arg, Arg),
+    Tgl(Arg),
+    Out(Arg),
+    // pseudo-instructions made by optimize, never parsed
+    Add(RegIdx, RegIdx), // add the first register to the second and clear it
+    Mul(Arg, RegIdx, RegIdx, RegIdx), // add factor * counter to dest, clearing scratch and counter
+}
+
+// generic argument, could be a register or an immediate value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Arg {
+    Reg(RegIdx),
+    Imm(i32),
+}
+
+impl Instr {
+    // What tgl turns this instruction into
+    pub fn toggled(&self) -> Instr {
+        match *self {
+            Instr::Inc(x) => Instr::Dec(x),
+            Instr::Dec(x) | Instr::Tgl(x) | Instr::Out(x) => Instr::Inc(x),
+            Instr::Jnz(x, y) => Instr::Cpy(x, y),
+            Instr::Cpy(x, y) => Instr::Jnz(x, y),
+            // tgl only ever sees the original program, never optimized code
+            Instr::Add(..) | Instr::Mul(..) => *self,
+        }
+    }
+}
+
+// Instructions display as assembunny source. The pseudo-instructions list their registers in
+// the same order as their fields: "add src dest" and "mul factor scratch counter dest".
+impl Display for Instr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Instr::Cpy(x, y) => write!(f, "cpy {} {}", x, y),
+            Instr::Inc(x) => write!(f, "inc {}", x),
+            Instr::Dec(x) => write!(f, "dec {}", x),
+            Instr::Jnz(x, y) => write!(f, "jnz {} {}", x, y),
+            Instr::Tgl(x) => write!(f, "tgl {}", x),
+            Instr::Out(x) => write!(f, "out {}", x),
+            Instr::Add(src, dest) => write!(f, "add {} {}", reg_name(src), reg_name(dest)),
+            Instr::Mul(factor, scratch, counter, dest) => {
+                write!(f,
+                       "mul {} {} {} {}",
+                       factor,
+                       reg_name(scratch),
+                       reg_name(counter),
+                       reg_name(dest))
+            }
+        }
+    }
+}
+
+impl Display for Arg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Arg::Reg(reg_idx) => write!(f, "{}", reg_name(reg_idx)),
+            Arg::Imm(imm) => write!(f, "{}", imm),
+        }
+    }
+}
+
+// 0 -> 'a', 1 -> 'b'...
+pub fn reg_name(reg_idx: RegIdx) -> char {
+    (b'a' + reg_idx as u8) as char
+}
+
+// "a" -> 0, "b" -> 1... or None if there's no such register
+pub fn reg_from_name(name: &str) -> Option<RegIdx> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c >= 'a' && (c as usize) < 'a' as usize + REGISTER_COUNT => Some(c as usize - 'a' as usize),
+        _ => None,
+    }
+}
+
+// What the output sink wants the program to do after it receives a value
+#[derive(Debug, PartialEq)]
+pub enum Signal {
+    Continue,
+    Stop,
+}
+
+// Limits on a run, so a program that never halts can't hang whoever runs it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    // give up after this many steps (a fused loop takes one, however many times it goes round)
+    pub max_steps: Option<u64>,
+    // stop as soon as the program is back in a state it has been in before
+    pub detect_loops: bool,
+}
+
+impl Default for Limits {
+    // Plenty for the puzzles' programs once they're optimized, and a few seconds at most
+    fn default() -> Limits {
+        Limits {
+            max_steps: Some(100_000_000),
+            detect_loops: true,
+        }
+    }
+}
+
+// How a run with limits ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Halted,
+    BudgetExceeded,
+    // the program got back to the state it was in period instructions earlier, so it never halts
+    Looping { period: u64 },
+}
+
+// The registers a program runs on. The interpreter does its arithmetic in i128, which holds
+// any register's value, and the register file brings each result back to its own width.
+pub trait RegisterFile: Clone + Eq + Hash + fmt::Debug {
+    // what store gives when a result doesn't fit and isn't wrapped around
+    type Error;
+    fn get(&self, reg_idx: RegIdx) -> i128;
+    // Store the result of the instruction at program_counter, given as worked out with checked
+    // and with wrapping i128 operations. On an error the register keeps its old value.
+    fn store(&mut self, reg_idx: RegIdx, checked: Option<i128>, wrapping: i128, program_counter: usize)
+             -> Result<(), Self::Error>;
+}
+
+// The puzzles' registers, which wrap around like the machine's own 32-bit integers
+impl RegisterFile for Registers {
+    type Error = Infallible;
+
+    fn get(&self, reg_idx: RegIdx) -> i128 {
+        i128::from(self[reg_idx])
+    }
+
+    fn store(&mut self, reg_idx: RegIdx, _: Option<i128>, wrapping: i128, _: usize) -> Result<(), Infallible> {
+        self[reg_idx] = wrapping as i32;
+        Ok(())
+    }
+}
+
+// Everything about a running program, including the instructions since tgl can change them.
+// The days all use the puzzles' registers; Machine is the same with a configurable set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProgramState<R = Registers> {
+    pub registers: R,
+    pub program_counter: usize,
+    pub instrs: Vec<Instr>,
+    // optimize(instrs), if optimization is turned on
+    code: Option<Vec<Instr>>,
+}
+
+impl<R: RegisterFile> ProgramState<R> {
+    pub fn with_registers(registers: R, instrs: Vec<Instr>) -> ProgramState<R> {
+        ProgramState {
+            registers,
+            program_counter: 0,
+            instrs,
+            code: None,
+        }
+    }
+
+    // Run the optimized version of the program from now on
+    pub fn enable_optimization(&mut self) {
+        self.code = Some(optimize(&self.instrs));
+    }
+
+    // A program halts when it jumps or steps outside its instructions
+    pub fn is_halted(&self) -> bool {
+        self.program_counter >= self.instrs.len()
+    }
+
+    // The instruction step will execute: from the optimized code if there is any
+    pub fn next_instr(&self) -> Instr {
+        match self.code {
+            Some(ref code) => code[self.program_counter],
+            None => self.instrs[self.program_counter],
+        }
+    }
+
+    // Like step, for any register file
+    pub fn try_step(&mut self) -> Result<Option<i128>, R::Error> {
+        let instr = self.next_instr();
+        self.try_execute_instr(instr)
+    }
+
+    // Execute instr as if it were at the program counter, returning the value sent by an out
+    // instruction
+    pub fn try_execute_instr(&mut self, instr: Instr) -> Result<Option<i128>, R::Error> {
+        let mut output = None;
+        match instr {
+            Instr::Jnz(val_arg, offset_arg) => {
+                if self.value(val_arg) != 0 {
+                    let target = (self.program_counter as i128).checked_add(self.value(offset_arg));
+                    // jumping before the first instruction wraps around to a huge value and
+                    // halts, and so does a jump too far to count
+                    self.program_counter = match target {
+                        Some(target) if target >= isize::MIN as i128 && target <= usize::MAX as i128 => target as usize,
+                        _ => usize::MAX,
+                    };
+                    return Ok(None);
+                }
+            }
+            Instr::Dec(Arg::Reg(reg_idx)) => {
+                let val = self.registers.get(reg_idx);
+                self.store(reg_idx, val.checked_sub(1), val.wrapping_sub(1))?;
+            }
+            Instr::Inc(Arg::Reg(reg_idx)) => {
+                let val = self.registers.get(reg_idx);
+                self.store(reg_idx, val.checked_add(1), val.wrapping_add(1))?;
+            }
+            Instr::Cpy(arg, Arg::Reg(reg_idx)) => {
+                let val = self.value(arg);
+                self.store(reg_idx, Some(val), val)?;
+            }
+            Instr::Tgl(arg) => {
+                if let Some(target) = self.toggle_target(arg) {
+                    let target_instr = &mut self.instrs[target];
+                    *target_instr = target_instr.toggled();
+                    // the toggle may break a fused loop or make a new one
+                    if let Some(ref mut code) = self.code {
+                        for idx in fused_around(target) {
+                            code[idx] = fuse_at(&self.instrs, idx);
+                        }
+                    }
+                }
+            }
+            Instr::Out(arg) => output = Some(self.value(arg)),
+            Instr::Add(src, dest) => {
+                // the loop only ends by counting src down to exactly 0
+                let (src_val, dest_val) = (self.registers.get(src), self.registers.get(dest));
+                if src_val <= 0 {
+                    return self.execute_original();
+                }
+                self.store(dest, dest_val.checked_add(src_val), dest_val.wrapping_add(src_val))?;
+                self.store(src, Some(0), 0)?;
+                self.program_counter += ADD_LEN;
+                return Ok(None);
+            }
+            Instr::Mul(factor_arg, scratch, counter, dest) => {
+                let factor = self.value(factor_arg);
+                let (counter_val, dest_val) = (self.registers.get(counter), self.registers.get(dest));
+                if factor <= 0 || counter_val <= 0 {
+                    return self.execute_original();
+                }
+                // the loop adds up to the total one factor at a time, so if the total fits,
+                // everything on the way did too
+                let checked = factor.checked_mul(counter_val).and_then(|product| dest_val.checked_add(product));
+                let wrapping = dest_val.wrapping_add(factor.wrapping_mul(counter_val));
+                self.store(dest, checked, wrapping)?;
+                self.store(scratch, Some(0), 0)?;
+                self.store(counter, Some(0), 0)?;
+                self.program_counter += MUL_LEN;
+                return Ok(None);
+            }
+            // toggled into something invalid
+            Instr::Dec(Arg::Imm(_)) | Instr::Inc(Arg::Imm(_)) | Instr::Cpy(_, Arg::Imm(_)) => {}
+        }
+        self.program_counter += 1;
+        Ok(output)
+    }
+
+    // The index of the instruction a tgl with this argument at the program counter would change,
+    // or None if it's outside the program, however far
+    pub fn toggle_target(&self, arg: Arg) -> Option<usize> {
+        let target = (self.program_counter as i128).checked_add(self.value(arg))?;
+        if target >= 0 && target < self.instrs.len() as i128 { Some(target as usize) } else { None }
+    }
+
+    // A fused loop that can't take its shortcut runs the instructions it replaced instead
+    fn execute_original(&mut self) -> Result<Option<i128>, R::Error> {
+        let instr = self.instrs[self.program_counter];
+        self.try_execute_instr(instr)
+    }
+
+    fn store(&mut self, reg_idx: RegIdx, checked: Option<i128>, wrapping: i128) -> Result<(), R::Error> {
+        self.registers.store(reg_idx, checked, wrapping, self.program_counter)
+    }
+
+    // Like run_with_limits, for any register file
+    pub fn try_run_with_limits(&mut self, limits: Limits) -> Result<Outcome, R::Error> {
+        run_within_limits(self, limits)
+    }
+
+    pub fn value(&self, arg: Arg) -> i128 {
+        match arg {
+            Arg::Reg(reg_idx) => self.registers.get(reg_idx),
+            Arg::Imm(imm) => i128::from(imm),
+        }
+    }
+}
+
+impl ProgramState {
+    // All four registers start as 0
+    pub fn new(instrs: Vec<Instr>) -> ProgramState {
+        ProgramState::with_registers([0; REGISTER_COUNT], instrs)
+    }
+
+    // Like new, but runs the optimized version of the program
+    pub fn optimized(instrs: Vec<Instr>) -> ProgramState {
+        let mut program_state = ProgramState::new(instrs);
+        program_state.enable_optimization();
+        program_state
+    }
+
+    // Execute the next instruction, returning the value sent by an out instruction
+    pub fn step(&mut self) -> Option<i32> {
+        let instr = self.next_instr();
+        self.execute_instr(instr)
+    }
+
+    // Execute instr as if it were at the program counter
+    pub fn execute_instr(&mut self, instr: Instr) -> Option<i32> {
+        match self.try_execute_instr(instr) {
+            // only a register's value can be sent, and registers are 32-bit
+            Ok(output) => output.map(|val| val as i32),
+            Err(never) => match never {},
+        }
+    }
+
+    // Run until the program halts, ignoring anything it sends
+    pub fn run(&mut self) {
+        while !self.is_halted() {
+            self.step();
+        }
+    }
+
+    // Run until the program halts or the output sink asks it to stop
+    pub fn run_with_output<F>(&mut self, out: F)
+        where F: FnMut(i32, &ProgramState) -> Signal
+    {
+        self.run_with_output_steps(u64::MAX, out);
+    }
+
+    // Like run_with_output, but gives up after max_steps steps. Returns how many steps it
+    // took, or None if the program was still going when they ran out.
+    pub fn run_with_output_steps<F>(&mut self, max_steps: u64, mut out: F) -> Option<u64>
+        where F: FnMut(i32, &ProgramState) -> Signal
+    {
+        let mut steps = 0;
+        while !self.is_halted() {
+            if steps == max_steps {
+                return None;
+            }
+            steps += 1;
+            if let Some(val) = self.step() {
+                if out(val, self) == Signal::Stop {
+                    break;
+                }
+            }
+        }
+        Some(steps)
+    }
+
+    // Run until the program halts, runs out of steps or, if loops are being detected, repeats a
+    // state. Output is ignored.
+    pub fn run_with_limits(&mut self, limits: Limits) -> Outcome {
+        match self.try_run_with_limits(limits) {
+            Ok(outcome) => outcome,
+            Err(never) => match never {},
+        }
+    }
+
+    pub fn val_from_arg(&self, arg: Arg) -> i32 {
+        self.value(arg) as i32
+    }
+}
+
+impl<R: RegisterFile> Stepper for ProgramState<R> {
+    type Error = R::Error;
+
+    fn is_halted(&self) -> bool {
+        ProgramState::is_halted(self)
+    }
+
+    fn try_step(&mut self) -> Result<(), R::Error> {
+        ProgramState::try_step(self).map(|_| ())
+    }
+
+    fn same_state(&self, other: &ProgramState<R>) -> bool {
+        self.program_counter == other.program_counter && self.registers == other.registers &&
+        self.instrs == other.instrs
+    }
+}
+
+// What run_within_limits needs from an interpreter
+trait Stepper: Clone {
+    type Error;
+    fn is_halted(&self) -> bool;
+    fn try_step(&mut self) -> Result<(), Self::Error>;
+    // Will the two go on to do exactly the same thing? The optimized code follows from the
+    // instructions, so it doesn't count.
+    fn same_state(&self, other: &Self) -> bool;
+}
+
+// Run until the program halts, runs out of steps or, if loops are being detected, repeats a
+// state.
+//
+// Remembering every state would take too much memory on a long run, so this uses Brent's cycle
+// finding: the state is saved after 1, 2, 4, 8... steps and each step is compared with the last
+// one saved. A loop is found within twice the steps it takes to enter it and go round once.
+// Comparing the program counter and registers first means the instructions as tgl has left
+// them rarely need comparing.
+fn run_within_limits<S: Stepper>(state: &mut S, limits: Limits) -> Result<Outcome, S::Error> {
+    let mut saved = state.clone();
+    let mut since_saved = 0;
+    let mut save_every = 1;
+    let mut steps = 0;
+
+    while !state.is_halted() {
+        if limits.max_steps.is_some_and(|max_steps| steps >= max_steps) {
+            return Ok(Outcome::BudgetExceeded);
+        }
+        state.try_step()?;
+        steps += 1;
+        if !limits.detect_loops {
+            continue;
+        }
+
+        since_saved += 1;
+        if state.same_state(&saved) {
+            return Ok(Outcome::Looping { period: since_saved });
+        }
+        if since_saved == save_every {
+            saved = state.clone();
+            since_saved = 0;
+            save_every *= 2;
+        }
+    }
+    Ok(Outcome::Halted)
+}
+
+// Run a program from the start with the given registers until it halts, which it may never do
+pub fn execute_until_halt(instructions: &[Instr], registers: Registers) -> ProgramState {
+    let mut program_state = ProgramState::optimized(instructions.to_vec());
+    program_state.registers = registers;
+    program_state.run();
+    program_state
+}
+
+// Like execute_until_halt, but gives up when the limits say so
+pub fn execute_with_limits(instructions: &[Instr], registers: Registers, limits: Limits) -> (Outcome, ProgramState) {
+    let mut program_state = ProgramState::optimized(instructions.to_vec());
+    program_state.registers = registers;
+    let outcome = program_state.run_with_limits(limits);
+    (outcome, program_state)
+}
+
+// The program when it halts after starting with the given registers, or None if it doesn't
+// within the default limits. This is how the days answer each part.
+pub fn execute_to_halt(instructions: &[Instr], registers: Registers) -> Option<ProgramState> {
+    match execute_with_limits(instructions, registers, Limits::default()) {
+        (Outcome::Halted, program_state) => Some(program_state),
+        _ => None,
+    }
+}
+
+// ////////////
+// Optimization
+//
+// Assembunny has no arithmetic beyond inc and dec, so programs add and multiply with loops:
+//
+//   inc a         cpy b c
+//   dec c         inc a
+//   jnz c -2      dec c
+//                 jnz c -2
+//                 dec d
+//                 jnz d -5
+//
+// The first adds c to a, the second adds b * d to a, and both leave their counters at 0.
+// optimize replaces the first instruction of each loop with an Add or Mul that does the same
+// in one step. The rest of the loop stays where it was, so jumps into the middle still work,
+// and a fused loop whose counters aren't positive (so it wouldn't end by counting down) just
+// runs the original instructions.
+
+const ADD_LEN: usize = 3;
+const MUL_LEN: usize = 6;
+
+// Copy of instrs with every add and multiply loop fused, instruction indices unchanged
+pub fn optimize(instrs: &[Instr]) -> Vec<Instr> {
+    (0..instrs.len()).map(|i| fuse_at(instrs, i)).collect()
+}
+
+// The fused loop starting at instrs[i], or instrs[i] if there isn't one. A loop only depends on
+// the instructions it covers, so changing one instruction can only change the MUL_LEN results
+// ending there.
+fn fuse_at(instrs: &[Instr], i: usize) -> Instr {
+    match_mul(&instrs[i..]).or_else(|| match_add(&instrs[i..])).unwrap_or(instrs[i])
+}
+
+// The indices whose fused loops can change when the instruction at target does
+fn fused_around(target: usize) -> Range<usize> {
+    target.saturating_sub(MUL_LEN - 1)..target + 1
+}
+
+// inc dest, dec src, jnz src -2 (with inc and dec either way round)
+fn match_add(instrs: &[Instr]) -> Option<Instr> {
+    if instrs.len() < ADD_LEN {
+        return None;
+    }
+    let (src, dest) = match (instrs[0], instrs[1]) {
+        (Instr::Inc(Arg::Reg(dest)), Instr::Dec(Arg::Reg(src))) |
+        (Instr::Dec(Arg::Reg(src)), Instr::Inc(Arg::Reg(dest))) => (src, dest),
+        _ => return None,
+    };
+    if src != dest && instrs[2] == Instr::Jnz(Arg::Reg(src), Arg::Imm(-2)) {
+        Some(Instr::Add(src, dest))
+    } else {
+        None
+    }
+}
+
+// cpy factor scratch, an add loop from scratch to dest, dec counter, jnz counter -5
+fn match_mul(instrs: &[Instr]) -> Option<Instr> {
+    if instrs.len() < MUL_LEN {
+        return None;
+    }
+    let (factor, scratch) = match instrs[0] {
+        Instr::Cpy(factor, Arg::Reg(scratch)) => (factor, scratch),
+        _ => return None,
+    };
+    let dest = match match_add(&instrs[1..]) {
+        Some(Instr::Add(src, dest)) if src == scratch => dest,
+        _ => return None,
+    };
+    let counter = match instrs[4] {
+        Instr::Dec(Arg::Reg(counter)) => counter,
+        _ => return None,
+    };
+    let distinct = counter != scratch && counter != dest;
+    let factor_fixed = factor != Arg::Reg(scratch) && factor != Arg::Reg(counter) && factor != Arg::Reg(dest);
+    if distinct && factor_fixed && instrs[5] == Instr::Jnz(Arg::Reg(counter), Arg::Imm(-5)) {
+        Some(Instr::Mul(factor, scratch, counter, dest))
+    } else {
+        None
+    }
+}
+
+pub fn parse_program(input: &str) -> Result<Vec<Instr>, ParseError> {
+    parse_lines(input, parse_instr)
+}
+
+pub fn parse_instr(instr: &str) -> Result<Instr, ParseError> {
+    lazy_static! {
+        static ref SYNTAX: Syntax = {
+            let names: Vec<_> = (0..REGISTER_COUNT).map(|reg_idx| reg_name(reg_idx).to_string()).collect();
+            Syntax::new(&names)
+        };
+    }
+    SYNTAX.parse_instr(instr)
+}
+
+// The instruction patterns for a set of register names
+struct Syntax {
+    names: Vec<String>,
+    cpy: Regex,
+    inc: Regex,
+    dec: Regex,
+    jnz: Regex,
+    tgl: Regex,
+    out: Regex,
+}
+
+impl Syntax {
+    fn new(names: &[String]) -> Syntax {
+        let reg = format!("({})", names.iter().map(|name| regex::escape(name)).collect::<Vec<_>>().join("|"));
+        let arg = format!(r"(?:{}|(-?\d+))", reg);
+        let pattern = |pattern: String| Regex::new(&pattern).unwrap();
+        Syntax {
+            names: names.to_vec(),
+            cpy: pattern(format!("^cpy {} {}$", arg, reg)),
+            inc: pattern(format!("^inc {}$", reg)),
+            dec: pattern(format!("^dec {}$", reg)),
+            jnz: pattern(format!("^jnz {} {}$", arg, arg)),
+            tgl: pattern(format!("^tgl {}$", arg)),
+            out: pattern(format!("^out {}$", arg)),
+        }
+    }
+
+    fn parse_instr(&self, instr: &str) -> Result<Instr, ParseError> {
+        if let Some(caps) = self.jnz.captures(instr) {
+            let val = self.parse_arg(caps.get(1), caps.get(2))?;
+            let offset = self.parse_arg(caps.get(3), caps.get(4))?;
+            Ok(Instr::Jnz(val, offset))
+        } else if let Some(caps) = self.dec.captures(instr) {
+            Ok(Instr::Dec(Arg::Reg(self.idx_from_reg(&caps[1]))))
+        } else if let Some(caps) = self.inc.captures(instr) {
+            Ok(Instr::Inc(Arg::Reg(self.idx_from_reg(&caps[1]))))
+        } else if let Some(caps) = self.cpy.captures(instr) {
+            let src_val = self.parse_arg(caps.get(1), caps.get(2))?;
+            let dest_reg_idx = self.idx_from_reg(&caps[3]);
+            Ok(Instr::Cpy(src_val, Arg::Reg(dest_reg_idx)))
+        } else if let Some(caps) = self.tgl.captures(instr) {
+            Ok(Instr::Tgl(self.parse_arg(caps.get(1), caps.get(2))?))
+        } else if let Some(caps) = self.out.captures(instr) {
+            Ok(Instr::Out(self.parse_arg(caps.get(1), caps.get(2))?))
+        } else {
+            Err(ParseError::new(1, 1, instr, "an instruction"))
+        }
+    }
+
+    fn parse_arg(&self, reg: Option<regex::Match>, imm: Option<regex::Match>) -> Result<Arg, ParseError> {
+        if let Some(imm_match) = imm {
+            imm_match.as_str()
+                .parse()
+                .map(Arg::Imm)
+                .map_err(|_| ParseError::new(1, imm_match.start() + 1, imm_match.as_str(), "a 32-bit integer"))
+        } else {
+            let src_reg_idx = self.idx_from_reg(reg.unwrap().as_str());
+            Ok(Arg::Reg(src_reg_idx))
+        }
+    }
+
+    // Only called with names the patterns matched
+    fn idx_from_reg(&self, reg: &str) -> RegIdx {
+        self.names.iter().position(|name| name == reg).unwrap()
+    }
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(lines: &[&str]) -> Vec<Instr> {
+        lines.iter().map(|line| parse_instr(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse_instr() {
+        assert_eq!(Ok(Instr::Cpy(Arg::Imm(-3), Arg::Reg(1))), parse_instr("cpy -3 b"));
+        assert_eq!(Ok(Instr::Jnz(Arg::Reg(2), Arg::Imm(2))), parse_instr("jnz c 2"));
+        assert_eq!(Ok(Instr::Tgl(Arg::Reg(0))), parse_instr("tgl a"));
+        assert_eq!(Ok(Instr::Out(Arg::Imm(1))), parse_instr("out 1"));
+        assert_eq!(Err(ParseError::new(1, 1, "cpy 1 2", "an instruction")), parse_instr("cpy 1 2"));
+        assert_eq!(Err(ParseError::new(1, 1, "inc 3", "an instruction")), parse_instr("inc 3"));
+    }
+
+    #[test]
+    fn test_parse_program() {
+        assert_eq!(Ok(program(&["inc a", "out a"])), parse_program("inc a\nout a"));
+        assert_eq!(Err(ParseError::new(2, 1, "oot a", "an instruction")), parse_program("inc a\noot a"));
+    }
+
+    #[test]
+    fn test_display() {
+        for line in &["cpy -3 b", "jnz c 2", "jnz 1 d", "inc a", "dec d", "tgl c", "out 0"] {
+            assert_eq!(*line, parse_instr(line).unwrap().to_string());
+        }
+        assert_eq!("add c a", Instr::Add(2, 0).to_string());
+        assert_eq!("mul 5 c d a", Instr::Mul(Arg::Imm(5), 2, 3, 0).to_string());
+        assert_eq!("cpy 1 2", Instr::Cpy(Arg::Imm(1), Arg::Imm(2)).to_string());
+    }
+
+    #[test]
+    fn test_reg_names() {
+        assert_eq!('c', reg_name(2));
+        assert_eq!(Some(3), reg_from_name("d"));
+        assert_eq!(None, reg_from_name("e"));
+        assert_eq!(None, reg_from_name("ab"));
+        assert_eq!(None, reg_from_name(""));
+    }
+
+    #[test]
+    fn test_toggled() {
+        let toggles = [("inc a", "dec a"),
+                       ("dec a", "inc a"),
+                       ("tgl a", "inc a"),
+                       ("out a", "inc a"),
+                       ("jnz a b", "cpy a b"),
+                       ("cpy 1 a", "jnz 1 a")];
+        for &(before, after) in &toggles {
+            assert_eq!(parse_instr(after).unwrap(), parse_instr(before).unwrap().toggled());
+        }
+        // the puzzle only has these invalid after a toggle
+        assert_eq!(Instr::Cpy(Arg::Imm(1), Arg::Imm(2)), parse_instr("jnz 1 2").unwrap().toggled());
+        assert_eq!(Instr::Inc(Arg::Imm(3)), parse_instr("out 3").unwrap().toggled());
+    }
+
+    #[test]
+    fn test_step() {
+        let mut instructions = program(&["cpy 2 a", "out a", "jnz 1 -9"]);
+        instructions.insert(1, Instr::Cpy(Arg::Imm(1), Arg::Imm(2)));
+        instructions.insert(2, Instr::Inc(Arg::Imm(3)));
+        let mut program_state = ProgramState::new(instructions);
+        assert_eq!(None, program_state.step());
+        assert_eq!(2, program_state.registers[0]);
+        // invalid instructions are skipped
+        program_state.step();
+        program_state.step();
+        assert_eq!([2, 0, 0, 0], program_state.registers);
+        assert_eq!(Some(2), program_state.step());
+        assert!(!program_state.is_halted());
+        // jumping before the start halts the program
+        program_state.step();
+        assert!(program_state.is_halted());
+    }
+
+    #[test]
+    fn test_run_with_output() {
+        let mut program_state = ProgramState::new(program(&["cpy 3 a", "out a", "dec a", "jnz a -2"]));
+        let mut output = vec![];
+        program_state.run_with_output(|val, _| {
+            output.push(val);
+            Signal::Continue
+        });
+        assert_eq!(vec![3, 2, 1], output);
+        assert!(program_state.is_halted());
+
+        // stopping early leaves the program just after the out instruction
+        let mut program_state = ProgramState::new(program_state.instrs);
+        program_state.run_with_output(|_, _| Signal::Stop);
+        assert_eq!(3, program_state.registers[0]);
+        assert_eq!(2, program_state.program_counter);
+
+        // a program that never sends anything runs out of steps
+        let mut program_state = ProgramState::new(program(&["jnz 1 0"]));
+        assert_eq!(None, program_state.run_with_output_steps(100, |_, _| Signal::Continue));
+        let mut program_state = ProgramState::new(program(&["cpy 3 a", "out a"]));
+        assert_eq!(Some(2), program_state.run_with_output_steps(2, |_, _| Signal::Continue));
+    }
+
+    #[test]
+    fn test_optimize() {
+        let instructions = program(&["cpy 2 a", "inc a", "dec c", "jnz c -2", "dec b", "inc d", "jnz b -2"]);
+        let code = optimize(&instructions);
+        assert_eq!(Instr::Add(2, 0), code[1]);
+        assert_eq!(Instr::Add(1, 3), code[4]);
+        assert_eq!(instructions[2..4], code[2..4]);
+
+        // day 23's multiply loop, with its inner add loop fused as well
+        let instructions = program(&["cpy b c", "inc a", "dec c", "jnz c -2", "dec d", "jnz d -5"]);
+        let code = optimize(&instructions);
+        assert_eq!(Instr::Mul(Arg::Reg(1), 2, 3, 0), code[0]);
+        assert_eq!(Instr::Add(2, 0), code[1]);
+
+        // loops that look similar but don't add or multiply
+        for lines in &[["inc a", "dec a", "jnz a -2"], ["inc a", "dec c", "jnz c -3"], ["inc a", "dec c", "jnz b -2"]] {
+            let instructions = program(lines);
+            assert_eq!(instructions, optimize(&instructions));
+        }
+        let instructions = program(&["cpy d c", "inc a", "dec c", "jnz c -2", "dec d", "jnz d -5"]);
+        assert_eq!(Instr::Add(2, 0), optimize(&instructions)[1]);
+        assert_eq!(instructions[0], optimize(&instructions)[0]);
+    }
+
+    // Run a program with and without optimization, which should always end the same way
+    fn run_both(lines: &[&str], registers: Registers) -> ProgramState {
+        let mut plain = ProgramState::new(program(lines));
+        plain.registers = registers;
+        plain.run();
+        let optimized = execute_until_halt(&program(lines), registers);
+        assert_eq!(plain.registers, optimized.registers);
+        assert_eq!(plain.program_counter, optimized.program_counter);
+        assert_eq!(plain.instrs, optimized.instrs);
+        optimized
+    }
+
+    #[test]
+    fn test_optimized_matches_plain() {
+        let multiply = ["cpy b c", "inc a", "dec c", "jnz c -2", "dec d", "jnz d -5"];
+        assert_eq!([12, 3, 0, 0], run_both(&multiply, [0, 3, 0, 4]).registers);
+        // jumping into the middle of a fused loop
+        let middle = ["jnz 1 2", "cpy b c", "inc a", "dec c", "jnz c -2", "dec d", "jnz d -5"];
+        assert_eq!([7, 3, 0, 0], run_both(&middle, [0, 3, 4, 2]).registers);
+    }
+
+    #[test]
+    fn test_optimized_fallback() {
+        // with a zero factor the loop never counts down to exactly 0, so there's no shortcut
+        let mut program_state = ProgramState::optimized(program(&["cpy b c", "inc a", "dec c", "jnz c -2"]));
+        program_state.registers = [0, 0, 5, 2];
+        program_state.step();
+        assert_eq!(([0, 0, 0, 2], 1), (program_state.registers, program_state.program_counter));
+        program_state.step();
+        assert_eq!(([1, 0, 0, 2], 2), (program_state.registers, program_state.program_counter));
+    }
+
+    #[test]
+    fn test_optimized_toggle() {
+        // the toggle at 0 turns "jnz c -2" into "cpy c -2", which stops the add loop being a loop:
+        // a runs inc a, dec c once and carries on
+        let breaks_loop = ["tgl b", "inc a", "dec c", "jnz c -2"];
+        assert_eq!([1, 3, 4, 0], run_both(&breaks_loop, [0, 3, 5, 0]).registers);
+        // and here the toggle makes the loop: "cpy c -2" becomes "jnz c -2"
+        let makes_loop = ["tgl b", "inc a", "dec c", "cpy c -2"];
+        let mut instructions = program(&makes_loop[..3]);
+        instructions.push(Instr::Cpy(Arg::Reg(2), Arg::Imm(-2)));
+        let mut plain = ProgramState::new(instructions.clone());
+        plain.registers = [0, 3, 5, 0];
+        plain.run();
+        let optimized = execute_until_halt(&instructions, [0, 3, 5, 0]);
+        assert_eq!([5, 3, 0, 0], optimized.registers);
+        assert_eq!(plain, ProgramState { code: None, ..optimized });
+    }
+
+    #[test]
+    fn test_execute_until_halt() {
+        // tgl changes the program while it runs; toggling outside the program does nothing
+        let instructions = program(&["cpy 2 a", "tgl a", "tgl a", "tgl a", "cpy 1 a", "dec a", "dec a"]);
+        let program_state = execute_until_halt(&instructions, [0; REGISTER_COUNT]);
+        assert_eq!(3, program_state.registers[0]);
+        assert_eq!(Instr::Inc(Arg::Reg(0)), program_state.instrs[3]);
+        // the original program isn't touched
+        assert_eq!(Instr::Tgl(Arg::Reg(0)), instructions[3]);
+    }
+
+    #[test]
+    fn test_run_with_limits() {
+        let no_limits = Limits {
+            max_steps: None,
+            detect_loops: false,
+        };
+        let mut program_state = ProgramState::new(program(&["cpy 3 c", "inc a", "dec c", "jnz c -2"]));
+        assert_eq!(Outcome::Halted, program_state.run_with_limits(no_limits));
+        assert_eq!(3, program_state.registers[0]);
+
+        // a program that counts forever runs out of steps: it never repeats a state
+        let counter = program(&["inc a", "jnz 1 -1"]);
+        let limits = Limits {
+            max_steps: Some(100),
+            detect_loops: true,
+        };
+        let (outcome, program_state) = execute_with_limits(&counter, [0; REGISTER_COUNT], limits);
+        assert_eq!(Outcome::BudgetExceeded, outcome);
+        assert_eq!(50, program_state.registers[0]);
+    }
+
+    #[test]
+    fn test_run_with_limits_looping() {
+        let limits = Limits {
+            max_steps: Some(1000),
+            detect_loops: true,
+        };
+        let stuck = program(&["jnz 1 0"]);
+        assert_eq!(Outcome::Looping { period: 1 }, execute_with_limits(&stuck, [0; REGISTER_COUNT], limits).0);
+        // the loop is only entered after the add loop, and goes round three instructions
+        let late = program(&["cpy 40 c", "inc a", "dec c", "jnz c -2", "inc b", "dec b", "jnz 1 -2"]);
+        assert_eq!(Outcome::Looping { period: 3 }, execute_with_limits(&late, [0; REGISTER_COUNT], limits).0);
+        let mut program_state = ProgramState::new(late);
+        assert_eq!(Outcome::Looping { period: 3 }, program_state.run_with_limits(limits));
+        assert_eq!(40, program_state.registers[0]);
+
+        // tgl a turns itself into inc a, so the state after the first time round is new
+        let toggles = program(&["tgl a", "dec a", "jnz 1 -2"]);
+        let mut program_state = ProgramState::new(toggles);
+        assert_eq!(Outcome::Looping { period: 3 }, program_state.run_with_limits(limits));
+        assert_eq!(Instr::Inc(Arg::Reg(0)), program_state.instrs[0]);
+    }
+}
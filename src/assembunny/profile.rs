@@ -0,0 +1,239 @@
+// assembunny/profile.rs
+// Execution profile and trace: where a program spends its time and what tgl did to it
+
+use std::io::{self, Write};
+use super::{Instr, ProgramState, REGISTER_COUNT, reg_name};
+
+// What happened while a program ran
+#[derive(Debug, PartialEq)]
+pub struct Profile {
+    // times each instruction was executed, by index
+    pub hits: Vec<u64>,
+    // total instructions executed
+    pub executed: u64,
+    // every tgl that changed the program, in order
+    pub toggles: Vec<Toggle>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Toggle {
+    // which instruction this was, counting from 1
+    pub step: u64,
+    // index of the tgl instruction
+    pub from: usize,
+    // index of the instruction it changed
+    pub target: usize,
+    pub before: Instr,
+    pub after: Instr,
+}
+
+impl Profile {
+    fn new(len: usize) -> Profile {
+        Profile {
+            hits: vec![0; len],
+            executed: 0,
+            toggles: vec![],
+        }
+    }
+
+    // Indices of the n most executed instructions with their hit counts, most first
+    pub fn hottest(&self, n: usize) -> Vec<(usize, u64)> {
+        let mut hits: Vec<_> = self.hits.iter().cloned().enumerate().filter(|&(_, hits)| hits > 0).collect();
+        hits.sort_by(|&(i1, hits1), &(i2, hits2)| hits2.cmp(&hits1).then(i1.cmp(&i2)));
+        hits.truncate(n);
+        hits
+    }
+
+    // Every line of instrs (normally the program as it ended up) with its share of the work,
+    // followed by the toggles
+    pub fn report(&self, instrs: &[Instr]) -> String {
+        let mut report = format!("{} instructions executed\n", self.executed);
+        report.push_str(&format!("{:>4}  {:>12}  {:>6}  instruction\n", "line", "hits", "share"));
+        for (idx, instr) in instrs.iter().enumerate() {
+            let hits = self.hits.get(idx).cloned().unwrap_or(0);
+            let share = if self.executed > 0 { 100.0 * hits as f64 / self.executed as f64 } else { 0.0 };
+            report.push_str(&format!("{:>4}  {:>12}  {:>5.1}%  {}\n", idx + 1, hits, share, instr));
+        }
+
+        if !self.toggles.is_empty() {
+            report.push_str("toggles:\n");
+        }
+        for toggle in &self.toggles {
+            report.push_str(&format!("  step {}: line {} changed line {} from {} to {}\n",
+                                     toggle.step,
+                                     toggle.from + 1,
+                                     toggle.target + 1,
+                                     toggle.before,
+                                     toggle.after));
+        }
+        report
+    }
+}
+
+// Writes one line per instruction executed, up to a limit so a long run doesn't fill the disk:
+// the step number, the line, the instruction and the registers after it ran
+pub struct Trace<'a> {
+    output: &'a mut dyn Write,
+    limit: u64,
+    written: u64,
+}
+
+impl<'a> Trace<'a> {
+    pub fn new(output: &'a mut dyn Write, limit: u64) -> Trace<'a> {
+        Trace {
+            output,
+            limit,
+            written: 0,
+        }
+    }
+
+    fn record(&mut self, step: u64, program_counter: usize, instr: Instr, state: &ProgramState) -> io::Result<()> {
+        if self.written == self.limit {
+            self.written += 1;
+            return writeln!(self.output, "... trace stopped after {} instructions", self.limit);
+        } else if self.written > self.limit {
+            return Ok(());
+        }
+        self.written += 1;
+
+        write!(self.output, "{:>8}  {:>4}  {:<12}", step, program_counter + 1, instr.to_string())?;
+        for reg_idx in 0..REGISTER_COUNT {
+            write!(self.output, "  {}={}", reg_name(reg_idx), state.registers[reg_idx])?;
+        }
+        writeln!(self.output)
+    }
+}
+
+// Run a program until it halts or has executed max_steps instructions, profiling it and tracing
+// it if there's a trace. Whoever runs it checks is_halted to tell which.
+// Hits are counted for the instructions actually executed, so an optimized program shows the
+// fused loops as single instructions.
+pub fn profile_until_halt(program_state: &mut ProgramState,
+                          max_steps: u64,
+                          mut trace: Option<&mut Trace>)
+                          -> io::Result<Profile> {
+    let mut profile = Profile::new(program_state.instrs.len());
+
+    while !program_state.is_halted() && profile.executed < max_steps {
+        let program_counter = program_state.program_counter;
+        let instr = program_state.next_instr();
+        let toggle_target = match instr {
+            Instr::Tgl(arg) => program_state.toggle_target(arg),
+            _ => None,
+        };
+        let before = toggle_target.map(|target| program_state.instrs[target]);
+
+        program_state.step();
+        profile.hits[program_counter] += 1;
+        profile.executed += 1;
+
+        if let (Some(target), Some(before)) = (toggle_target, before) {
+            profile.toggles.push(Toggle {
+                step: profile.executed,
+                from: program_counter,
+                target,
+                before,
+                after: program_state.instrs[target],
+            });
+        }
+        if let Some(ref mut trace) = trace {
+            trace.record(profile.executed, program_counter, instr, program_state)?;
+        }
+    }
+
+    Ok(profile)
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assembunny::{Arg, parse_program};
+
+    const ADD_LOOP: &str = "cpy 3 c\ninc a\ndec c\njnz c -2";
+
+    #[test]
+    fn test_profile() {
+        let mut program_state = ProgramState::new(parse_program(ADD_LOOP).unwrap());
+        let profile = profile_until_halt(&mut program_state, 100, None).unwrap();
+        assert_eq!(vec![1, 3, 3, 3], profile.hits);
+        assert_eq!(10, profile.executed);
+        assert!(profile.toggles.is_empty());
+        assert_eq!(vec![(1, 3), (2, 3)], profile.hottest(2));
+        assert_eq!(3, program_state.registers[0]);
+        assert!(program_state.is_halted());
+
+        // the fused loop runs once
+        let mut program_state = ProgramState::optimized(parse_program(ADD_LOOP).unwrap());
+        let profile = profile_until_halt(&mut program_state, 100, None).unwrap();
+        assert_eq!(vec![1, 1, 0, 0], profile.hits);
+        assert_eq!(vec![(0, 1), (1, 1)], profile.hottest(5));
+    }
+
+    #[test]
+    fn test_profile_max_steps() {
+        // a bad jump that goes round the same three instructions forever
+        let mut program_state = ProgramState::new(parse_program("inc a\ndec a\njnz 1 -2").unwrap());
+        let profile = profile_until_halt(&mut program_state, 100, None).unwrap();
+        assert_eq!(100, profile.executed);
+        assert_eq!(vec![34, 33, 33], profile.hits);
+        assert!(!program_state.is_halted());
+    }
+
+    #[test]
+    fn test_profile_far_toggle() {
+        // too far to toggle anything, however far the offset takes it
+        let mut program_state = ProgramState::new(parse_program("cpy 2147483647 a\ntgl a").unwrap());
+        let profile = profile_until_halt(&mut program_state, 100, None).unwrap();
+        assert_eq!(2, profile.executed);
+        assert!(profile.toggles.is_empty());
+    }
+
+    #[test]
+    fn test_profile_toggles() {
+        // day 23's example
+        let program = parse_program("cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a").unwrap();
+        let mut program_state = ProgramState::new(program);
+        let profile = profile_until_halt(&mut program_state, 100, None).unwrap();
+        assert_eq!(vec![Toggle {
+                            step: 2,
+                            from: 1,
+                            target: 3,
+                            before: Instr::Tgl(Arg::Reg(0)),
+                            after: Instr::Inc(Arg::Reg(0)),
+                        },
+                        Toggle {
+                            step: 3,
+                            from: 2,
+                            target: 4,
+                            before: Instr::Cpy(Arg::Imm(1), Arg::Reg(0)),
+                            after: Instr::Jnz(Arg::Imm(1), Arg::Reg(0)),
+                        }],
+                   profile.toggles);
+
+        let report = profile.report(&program_state.instrs);
+        assert!(report.starts_with("5 instructions executed\n"));
+        assert!(report.contains("\n   5             1   20.0%  jnz 1 a\n"));
+        assert!(report.ends_with("toggles:
+  step 2: line 2 changed line 4 from tgl a to inc a
+  step 3: line 3 changed line 5 from cpy 1 a to jnz 1 a
+"));
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut output = vec![];
+        {
+            let mut trace = Trace::new(&mut output, 3);
+            let mut program_state = ProgramState::new(parse_program(ADD_LOOP).unwrap());
+            profile_until_halt(&mut program_state, 100, Some(&mut trace)).unwrap();
+        }
+        assert_eq!("       1     1  cpy 3 c       a=0  b=0  c=3  d=0
+       2     2  inc a         a=1  b=0  c=3  d=0
+       3     3  dec c         a=1  b=0  c=2  d=0
+... trace stopped after 3 instructions
+",
+                   String::from_utf8(output).unwrap());
+    }
+}
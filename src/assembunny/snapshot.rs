@@ -0,0 +1,230 @@
+// assembunny/snapshot.rs
+// Snapshots: a running program saved as text, to be restored later or compared with another
+//
+// A snapshot is plain text, one fact per line, so two of them can also be compared with diff:
+//
+//   assembunny snapshot 1
+//   optimized yes
+//   pc 16
+//   registers 5040 3 0 0
+//   program
+//   cpy a b
+//   ...
+//
+// The first line gives the format version; restore refuses versions it doesn't know rather
+// than guessing. The program is saved as tgl has left it, which can include instructions like
+// "cpy 1 2" that the parser for puzzle input rejects.
+
+use regex::Regex;
+use ParseError;
+use super::{Arg, Instr, ProgramState, REGISTER_COUNT, optimize, reg_from_name, reg_name};
+
+pub const VERSION: u32 = 1;
+
+pub fn save(program_state: &ProgramState) -> String {
+    let registers: Vec<_> = program_state.registers.iter().map(|val| val.to_string()).collect();
+    let mut snapshot = format!("assembunny snapshot {}\noptimized {}\npc {}\nregisters {}\nprogram\n",
+                               VERSION,
+                               if program_state.code.is_some() { "yes" } else { "no" },
+                               program_state.program_counter,
+                               registers.join(" "));
+    for instr in &program_state.instrs {
+        snapshot.push_str(&format!("{}\n", instr));
+    }
+    snapshot
+}
+
+pub fn restore(snapshot: &str) -> Result<ProgramState, ParseError> {
+    lazy_static! {
+        static ref RE_HEADER: Regex = Regex::new(r"^assembunny snapshot (\d+)$").unwrap();
+        static ref RE_OPTIMIZED: Regex = Regex::new(r"^optimized (yes|no)$").unwrap();
+        static ref RE_PC: Regex = Regex::new(r"^pc (\d+)$").unwrap();
+        static ref RE_REGISTERS: Regex =
+            Regex::new(r"^registers (-?\d+) (-?\d+) (-?\d+) (-?\d+)$").unwrap();
+    }
+
+    let lines: Vec<_> = snapshot.lines().collect();
+    // the line at index i if it matches re, or an error saying what was expected there
+    let expect = |i: usize, re: &Regex, expected: &str| {
+        let line = lines.get(i).cloned().unwrap_or("");
+        re.captures(line).ok_or_else(|| ParseError::new(i + 1, 1, line, expected))
+    };
+
+    let header = expect(0, &RE_HEADER, "an assembunny snapshot header")?;
+    let version = header.get(1).unwrap();
+    if version.as_str() != VERSION.to_string() {
+        let expected = format!("snapshot version {}", VERSION);
+        return Err(ParseError::new(1, version.start() + 1, version.as_str(), &expected));
+    }
+    let optimized = &expect(1, &RE_OPTIMIZED, "optimized yes or no")?[1] == "yes";
+    let pc = expect(2, &RE_PC, "the program counter")?;
+    let program_counter = pc[1].parse().map_err(|_| ParseError::new(3, 4, &pc[1], "an instruction index"))?;
+    let reg_caps = expect(3, &RE_REGISTERS, "the registers")?;
+    let mut registers = [0; REGISTER_COUNT];
+    for (reg_idx, register) in registers.iter_mut().enumerate() {
+        let val = reg_caps.get(reg_idx + 1).unwrap();
+        *register = val.as_str()
+            .parse()
+            .map_err(|_| ParseError::new(4, val.start() + 1, val.as_str(), "a 32-bit integer"))?;
+    }
+    if lines.get(4) != Some(&"program") {
+        return Err(ParseError::new(5, 1, lines.get(4).cloned().unwrap_or(""), "program"));
+    }
+
+    let instrs = lines[5..]
+        .iter()
+        .enumerate()
+        .map(|(i, line)| parse_any_instr(line).map_err(|err| err.on_line(i + 6)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ProgramState {
+        registers,
+        program_counter,
+        code: if optimized { Some(optimize(&instrs)) } else { None },
+        instrs,
+    })
+}
+
+// Like parse_instr, but allowing registers and numbers anywhere, as tgl can leave them
+fn parse_any_instr(instr: &str) -> Result<Instr, ParseError> {
+    lazy_static! {
+        static ref RE_ONE: Regex = Regex::new(r"^(inc|dec|tgl|out) (\S+)$").unwrap();
+        static ref RE_TWO: Regex = Regex::new(r"^(cpy|jnz) (\S+) (\S+)$").unwrap();
+    }
+
+    let parse_arg = |arg: regex::Match| match reg_from_name(arg.as_str()) {
+        Some(reg_idx) => Ok(Arg::Reg(reg_idx)),
+        None => {
+            arg.as_str()
+                .parse()
+                .map(Arg::Imm)
+                .map_err(|_| ParseError::new(1, arg.start() + 1, arg.as_str(), "a register or a 32-bit integer"))
+        }
+    };
+    if let Some(caps) = RE_ONE.captures(instr) {
+        let x = parse_arg(caps.get(2).unwrap())?;
+        Ok(match &caps[1] {
+            "inc" => Instr::Inc(x),
+            "dec" => Instr::Dec(x),
+            "tgl" => Instr::Tgl(x),
+            _ => Instr::Out(x),
+        })
+    } else if let Some(caps) = RE_TWO.captures(instr) {
+        let x = parse_arg(caps.get(2).unwrap())?;
+        let y = parse_arg(caps.get(3).unwrap())?;
+        Ok(if &caps[1] == "cpy" { Instr::Cpy(x, y) } else { Instr::Jnz(x, y) })
+    } else {
+        Err(ParseError::new(1, 1, instr, "an instruction"))
+    }
+}
+
+// What changed between two states of a program, one change per line: the program counter, each
+// register, and each instruction tgl changed
+pub fn diff(before: &ProgramState, after: &ProgramState) -> String {
+    let mut changes = String::new();
+    if before.program_counter != after.program_counter {
+        changes.push_str(&format!("pc: {} -> {}\n", before.program_counter, after.program_counter));
+    }
+    for reg_idx in 0..REGISTER_COUNT {
+        if before.registers[reg_idx] != after.registers[reg_idx] {
+            changes.push_str(&format!("{}: {} -> {}\n",
+                                      reg_name(reg_idx),
+                                      before.registers[reg_idx],
+                                      after.registers[reg_idx]));
+        }
+    }
+    for (idx, (old, new)) in before.instrs.iter().zip(&after.instrs).enumerate() {
+        if old != new {
+            changes.push_str(&format!("line {}: {} -> {}\n", idx + 1, old, new));
+        }
+    }
+    if before.instrs.len() != after.instrs.len() {
+        changes.push_str(&format!("program length: {} -> {}\n", before.instrs.len(), after.instrs.len()));
+    }
+    changes
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assembunny::parse_program;
+
+    const DAY23_EXAMPLE: &str = "cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a";
+
+    #[test]
+    fn test_save() {
+        let mut program_state = ProgramState::new(parse_program(DAY23_EXAMPLE).unwrap());
+        program_state.registers = [0, -3, 0, 7];
+        program_state.step();
+        program_state.step();
+        assert_eq!("assembunny snapshot 1
+optimized no
+pc 2
+registers 2 -3 0 7
+program
+cpy 2 a
+tgl a
+tgl a
+inc a
+cpy 1 a
+dec a
+dec a
+",
+                   save(&program_state));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        // at every point of a run that toggles, optimized or not
+        for &optimized in &[false, true] {
+            let instrs = parse_program(DAY23_EXAMPLE).unwrap();
+            let mut program_state = if optimized { ProgramState::optimized(instrs) } else { ProgramState::new(instrs) };
+            loop {
+                assert_eq!(program_state, restore(&save(&program_state)).unwrap());
+                if program_state.is_halted() {
+                    break;
+                }
+                program_state.step();
+            }
+        }
+
+        // instructions tgl made invalid, and the huge program counter left by jumping before the
+        // start
+        let mut instrs = parse_program("jnz 1 -1").unwrap();
+        instrs.push(Instr::Cpy(Arg::Imm(1), Arg::Imm(2)));
+        instrs.push(Instr::Inc(Arg::Imm(-3)));
+        let mut program_state = ProgramState::new(instrs);
+        program_state.registers = [i32::MIN, 0, i32::MAX, -1];
+        program_state.step();
+        assert!(save(&program_state).contains("\ncpy 1 2\ninc -3\n"));
+        assert_eq!(program_state, restore(&save(&program_state)).unwrap());
+    }
+
+    #[test]
+    fn test_restore_errors() {
+        let snapshot = save(&ProgramState::new(parse_program(DAY23_EXAMPLE).unwrap()));
+        assert_eq!(Err(ParseError::new(1, 21, "2", "snapshot version 1")),
+                   restore(&snapshot.replace("snapshot 1", "snapshot 2")));
+        assert_eq!(Err(ParseError::new(1, 1, "cpy 2 a", "an assembunny snapshot header")),
+                   restore(DAY23_EXAMPLE));
+        assert_eq!(Err(ParseError::new(4, 1, "registers 0 0 0", "the registers")),
+                   restore(&snapshot.replace("registers 0 0 0 0", "registers 0 0 0")));
+        assert_eq!(Err(ParseError::new(4, 15, "99999999999", "a 32-bit integer")),
+                   restore(&snapshot.replace("registers 0 0 0 0", "registers 0 0 99999999999 0")));
+        assert_eq!(Err(ParseError::new(8, 5, "e", "a register or a 32-bit integer")),
+                   restore(&snapshot.replace("tgl a\ntgl a\ntgl a", "tgl a\ntgl e\ntgl a")));
+        assert_eq!(Err(ParseError::new(5, 1, "", "program")),
+                   restore("assembunny snapshot 1\noptimized no\npc 0\nregisters 0 0 0 0"));
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = ProgramState::new(parse_program(DAY23_EXAMPLE).unwrap());
+        let mut after = before.clone();
+        after.run();
+        assert_eq!("pc: 0 -> 7\na: 0 -> 3\nline 4: tgl a -> inc a\nline 5: cpy 1 a -> jnz 1 a\n",
+                   diff(&before, &after));
+        assert_eq!("", diff(&after, &restore(&save(&after)).unwrap()));
+    }
+}
@@ -0,0 +1,10 @@
+// advent11.rs
+// radioisotope generators and microchips (elevator puzzle)
+
+extern crate common;
+
+use std::process;
+
+fn main() {
+    process::exit(common::days::main_for_day(11));
+}
@@ -0,0 +1,10 @@
+// advent25.rs
+// assembly language with output, clock signal
+
+extern crate common;
+
+use std::process;
+
+fn main() {
+    process::exit(common::days::main_for_day(25));
+}
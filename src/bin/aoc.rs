@@ -0,0 +1,920 @@
+// aoc.rs
+// Single entry point that dispatches to every day's solver
+
+extern crate common;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::process;
+use common::assembunny::{self, CompiledProgram, Config, Debugger, Instr, Limits, Machine, Outcome, ProgramState,
+                         Registers, Trace, Width, snapshot};
+use common::Solution;
+use common::days::{self, Day};
+use common::days::advent1::{self, Advent1};
+use common::timing::{self, Runs, Stats};
+use common::verify::{AnswerKey, Check};
+
+// exit codes
+const EXIT_OK: i32 = 0;
+const EXIT_FAILED: i32 = 1; // some part had no answer or the wrong one, or its input couldn't be read or parsed
+const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "usage: aoc run <day|all> [--part <1|2>] [--input <file>] [--input-dir <dir>]
+       aoc verify <day|all> [--part <1|2>] [--input <file>] [--input-dir <dir>] [--answers <file>]
+       aoc time <day|all> [--part <1|2>] [--input <file>] [--input-dir <dir>]
+                [--warmup <n>] [--repeats <n>] [--format <table|csv>]
+       aoc debug <day> [--part <1|2>] [--input <file>] [--input-dir <dir>] [--restore <file>]
+       aoc profile <day> [--part <1|2>] [--input <file>] [--input-dir <dir>]
+                   [--trace <file>] [--trace-limit <n>] [--max-steps <n>]
+       aoc disasm <day> [--input <file>] [--input-dir <dir>]
+       aoc cfg <day> [--input <file>] [--input-dir <dir>]
+       aoc decompile <day> [--part <1|2>] [--input <file>] [--input-dir <dir>]
+       aoc bench <day> [--part <1|2>] [--input <file>] [--input-dir <dir>]
+                 [--warmup <n>] [--repeats <n>] [--format <table|csv>]
+       aoc route <day> [--input <file>] [--input-dir <dir>] [--format <ascii|svg>]
+       aoc exec <day> [--part <1|2>] [--input <file>] [--input-dir <dir>]
+                [--width <i32|i64|i128>] [--overflow <wrap|check>] [--set <register>=<n>]...
+
+  run <day>      solve one day, reading input from stdin unless --input is given
+  run all        solve every day, reading <dir>/input<day>.txt (default dir: input)
+  verify         solve days from their input files like 'run all' and compare with the
+                 recorded answers (default file: answers.toml)
+  time           time parsing and each part separately, reading input like 'run'
+                 (default: 1 warm-up run, then 3 timed runs)
+  debug <day>    step through an assembunny program (days 12 and 23) from its input file,
+                 reading debugger commands from stdin (default: part 1, try 'help'), or
+                 carry on from a snapshot the debugger saved
+  profile <day>  run an assembunny program unoptimized and count how often each line runs,
+                 optionally writing the first instructions executed to a trace file, and
+                 stop early if it runs too long (default: part 1, trace limit 10000,
+                 100000000 steps)
+  disasm <day>   print an assembunny program with its jump targets labelled
+  cfg <day>      print an assembunny program's control-flow graph as Graphviz DOT
+  decompile <day>
+                 work out formulas for an assembunny program's final registers, following
+                 part 1's start where it has to, and use them to answer each part
+  bench <day>    time an assembunny program on the interpreter and compiled to closures,
+                 each with and without fused loops, skipping a part that doesn't halt and
+                 checking every run ends the same way (same defaults as 'time')
+  route <day>    draw day 1's route as a text map or an SVG image, marking the start, the end
+                 and the first place visited twice (default: ascii)
+  exec <day>     run an assembunny program on registers of another width from each part's
+                 start, with any --set registers changed, and print the final registers
+                 (default: i32, wrapping around)";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let exit_code = match parse_args(&args) {
+        Ok(options) => run(&options),
+        Err(msg) => {
+            eprintln!("aoc: {}\n{}", msg, USAGE);
+            EXIT_USAGE
+        }
+    };
+
+    process::exit(exit_code);
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Run,
+    Verify,
+    Time,
+    Debug,
+    Profile,
+    Disasm,
+    Cfg,
+    Decompile,
+    Bench,
+    Route,
+    Exec,
+}
+
+#[derive(Debug, PartialEq)]
+enum Format {
+    Table,
+    Csv,
+}
+
+#[derive(Debug, PartialEq)]
+enum Drawing {
+    Ascii,
+    Svg,
+}
+
+#[derive(Debug, PartialEq)]
+enum Target {
+    Day(u32),
+    All,
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    command: Command,
+    target: Target,
+    parts: Vec<u32>,
+    input: Option<String>,
+    input_dir: String,
+    answers: Option<String>,
+    runs: Runs,
+    format: Format,
+    drawing: Drawing,
+    trace: Option<String>,
+    trace_limit: u64,
+    max_steps: u64,
+    restore: Option<String>,
+    width: Width,
+    checked: bool,
+    set: Vec<(String, i128)>,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut iter = args.iter();
+
+    let command = match iter.next().map(|s| s.as_str()) {
+        Some("run") => Command::Run,
+        Some("verify") => Command::Verify,
+        Some("time") => Command::Time,
+        Some("debug") => Command::Debug,
+        Some("profile") => Command::Profile,
+        Some("disasm") => Command::Disasm,
+        Some("cfg") => Command::Cfg,
+        Some("decompile") => Command::Decompile,
+        Some("bench") => Command::Bench,
+        Some("route") => Command::Route,
+        Some("exec") => Command::Exec,
+        Some(cmd) => return Err(format!("unknown command '{}'", cmd)),
+        None => return Err("missing command".to_string()),
+    };
+
+    let target = match iter.next().map(|s| s.as_str()) {
+        Some("all") => Target::All,
+        Some(day) => {
+            let number = day.parse().map_err(|_| format!("invalid day '{}'", day))?;
+            if days::find_day(number).is_none() {
+                return Err(format!("no solver for day {}", number));
+            }
+            Target::Day(number)
+        }
+        None => return Err("missing day".to_string()),
+    };
+
+    let mut options = Options {
+        command,
+        target,
+        parts: vec![1, 2],
+        input: None,
+        input_dir: "input".to_string(),
+        answers: None,
+        runs: Runs {
+            warmup: 1,
+            repeats: 3,
+        },
+        format: Format::Table,
+        drawing: Drawing::Ascii,
+        trace: None,
+        trace_limit: 10000,
+        max_steps: Limits::default().max_steps.unwrap_or(u64::MAX),
+        restore: None,
+        width: Width::I32,
+        checked: false,
+        set: vec![],
+    };
+    let mut timing_flags = false;
+    let mut profile_flags = false;
+    let mut register_flags = false;
+
+    while let Some(flag) = iter.next() {
+        let value = iter.next().ok_or_else(|| format!("missing value for {}", flag))?;
+        match flag.as_str() {
+            "--part" => {
+                match value.as_str() {
+                    "1" => options.parts = vec![1],
+                    "2" => options.parts = vec![2],
+                    _ => return Err(format!("invalid part '{}'", value)),
+                }
+            }
+            "--input" => options.input = Some(value.clone()),
+            "--input-dir" => options.input_dir = value.clone(),
+            "--answers" => options.answers = Some(value.clone()),
+            "--warmup" => {
+                options.runs.warmup = value.parse().map_err(|_| format!("invalid warm-up count '{}'", value))?;
+                timing_flags = true;
+            }
+            "--repeats" => {
+                options.runs.repeats = match value.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("invalid repeat count '{}'", value)),
+                };
+                timing_flags = true;
+            }
+            "--format" if options.command == Command::Route => {
+                options.drawing = match value.as_str() {
+                    "ascii" => Drawing::Ascii,
+                    "svg" => Drawing::Svg,
+                    _ => return Err(format!("invalid format '{}'", value)),
+                };
+            }
+            "--format" => {
+                options.format = match value.as_str() {
+                    "table" => Format::Table,
+                    "csv" => Format::Csv,
+                    _ => return Err(format!("invalid format '{}'", value)),
+                };
+                timing_flags = true;
+            }
+            "--trace" => {
+                options.trace = Some(value.clone());
+                profile_flags = true;
+            }
+            "--trace-limit" => {
+                options.trace_limit = value.parse().map_err(|_| format!("invalid trace limit '{}'", value))?;
+                profile_flags = true;
+            }
+            "--max-steps" => {
+                options.max_steps = value.parse().map_err(|_| format!("invalid step count '{}'", value))?;
+                profile_flags = true;
+            }
+            "--restore" => options.restore = Some(value.clone()),
+            "--width" => {
+                options.width = value.parse()?;
+                register_flags = true;
+            }
+            "--overflow" => {
+                options.checked = match value.as_str() {
+                    "wrap" => false,
+                    "check" => true,
+                    _ => return Err(format!("invalid overflow '{}'", value)),
+                };
+                register_flags = true;
+            }
+            "--set" => {
+                let setting = value.split_once('=').and_then(|(name, n)| Some((name.to_string(), n.parse().ok()?)));
+                options.set.push(setting.ok_or_else(|| format!("invalid register setting '{}'", value))?);
+                register_flags = true;
+            }
+            _ => return Err(format!("unknown option '{}'", flag)),
+        }
+    }
+
+    if options.target == Target::All && options.input.is_some() {
+        return Err("--input can't be used with 'all'".to_string());
+    }
+    if options.command != Command::Verify && options.answers.is_some() {
+        return Err("--answers can only be used with 'verify'".to_string());
+    }
+    if options.command != Command::Time && options.command != Command::Bench && timing_flags {
+        return Err("--warmup and --repeats can only be used with 'time' and 'bench', and --format with those and 'route'"
+            .to_string());
+    }
+    if options.command != Command::Profile && profile_flags {
+        return Err("--trace, --trace-limit and --max-steps can only be used with 'profile'".to_string());
+    }
+    if options.command != Command::Debug && options.restore.is_some() {
+        return Err("--restore can only be used with 'debug'".to_string());
+    }
+    if options.command != Command::Exec && register_flags {
+        return Err("--width, --overflow and --set can only be used with 'exec'".to_string());
+    }
+    let assembunny_only = [Command::Debug,
+                           Command::Profile,
+                           Command::Disasm,
+                           Command::Cfg,
+                           Command::Decompile,
+                           Command::Bench,
+                           Command::Exec];
+    if assembunny_only.contains(&options.command) {
+        match options.target {
+            Target::Day(number) if days::find_assembunny_day(number).is_some() => {}
+            _ => return Err(format!("'{}' only works with the assembunny days, 12 and 23", args[0])),
+        }
+    }
+    if options.command == Command::Route && options.target != Target::Day(1) {
+        return Err("'route' only works with day 1".to_string());
+    }
+
+    Ok(options)
+}
+
+fn run(options: &Options) -> i32 {
+    match options.command {
+        Command::Run => run_days(options),
+        Command::Verify => verify_days(options),
+        Command::Time => time_days(options),
+        Command::Debug => debug_day(options),
+        Command::Profile => profile_day(options),
+        Command::Disasm => disassemble_day(options),
+        Command::Cfg => cfg_day(options),
+        Command::Decompile => decompile_day(options),
+        Command::Bench => bench_day(options),
+        Command::Route => route_day(options),
+        Command::Exec => exec_day(options),
+    }
+}
+
+fn selected_days(options: &Options) -> Vec<&'static Day> {
+    match options.target {
+        Target::Day(number) => days::find_day(number).into_iter().collect(),
+        Target::All => days::DAYS.iter().collect(),
+    }
+}
+
+fn run_days(options: &Options) -> i32 {
+    let mut exit_code = EXIT_OK;
+    for day in selected_days(options) {
+        let input = match read_input(day, options) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("day {}: can't read input: {}", day.number, err);
+                exit_code = EXIT_FAILED;
+                continue;
+            }
+        };
+
+        if !days::run_day(day, &options.parts, &input) {
+            exit_code = EXIT_FAILED;
+        }
+    }
+
+    exit_code
+}
+
+// Solve each day from its input file and compare every part with the answer key.
+// Parts without a recorded answer are reported but don't count as failures.
+fn verify_days(options: &Options) -> i32 {
+    let path = options.answers.as_ref().map_or("answers.toml", |path| path.as_str());
+    let key = match read_file(path).map_err(|err| err.to_string()).and_then(|s| AnswerKey::parse(&s)) {
+        Ok(key) => key,
+        Err(err) => {
+            eprintln!("can't read answers from {}: {}", path, err);
+            return EXIT_FAILED;
+        }
+    };
+
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    for day in selected_days(options) {
+        let answers = match read_input(day, options) {
+            Ok(input) => (day.solve)(&input, &options.parts).map_err(|err| format!("invalid input: {}", err)),
+            Err(err) => Err(format!("can't read input: {}", err)),
+        };
+        let answers = match answers {
+            Ok(answers) => answers,
+            Err(err) => {
+                eprintln!("day {}: {}", day.number, err);
+                failed += options.parts.len();
+                continue;
+            }
+        };
+
+        for (&part, answer) in options.parts.iter().zip(answers) {
+            match key.check(day.number, part, answer.as_deref()) {
+                Check::Pass => {
+                    println!("day {} part {}: pass", day.number, part);
+                    passed += 1;
+                }
+                Check::Fail(diff) => {
+                    print!("day {} part {}: FAIL\n{}", day.number, part, diff);
+                    failed += 1;
+                }
+                Check::Missing(answer) => {
+                    let answer = answer.unwrap_or_else(|| "no answer".to_string());
+                    if answer.contains('\n') {
+                        println!("day {} part {}: missing, solver found:\n{}", day.number, part, answer);
+                    } else {
+                        println!("day {} part {}: missing, solver found {}", day.number, part, answer);
+                    }
+                    missing += 1;
+                }
+            }
+        }
+    }
+
+    println!("{} passed, {} failed, {} missing", passed, failed, missing);
+    if failed > 0 { EXIT_FAILED } else { EXIT_OK }
+}
+
+// Print a row for each phase of each day as soon as it's been timed
+fn time_days(options: &Options) -> i32 {
+    match options.format {
+        Format::Table => println!("{:>3}  {:<6} {:>10} {:>10} {:>10}", "day", "phase", "min", "median", "max"),
+        Format::Csv => println!("day,phase,min_ns,median_ns,max_ns"),
+    }
+
+    let mut exit_code = EXIT_OK;
+    for day in selected_days(options) {
+        let timing = match read_input(day, options) {
+            Ok(input) => {
+                (day.time)(&input, &options.parts, &options.runs)
+                    .map_err(|err| format!("invalid input: {}", err))
+            }
+            Err(err) => Err(format!("can't read input: {}", err)),
+        };
+        let timing = match timing {
+            Ok(timing) => timing,
+            Err(err) => {
+                eprintln!("day {}: {}", day.number, err);
+                exit_code = EXIT_FAILED;
+                continue;
+            }
+        };
+
+        print_timing_row(&options.format, day.number, "parse", &timing.parse);
+        for &(part, ref stats) in &timing.parts {
+            print_timing_row(&options.format, day.number, &format!("part{}", part), stats);
+        }
+    }
+
+    exit_code
+}
+
+fn print_timing_row(format: &Format, day: u32, phase: &str, stats: &Stats) {
+    match *format {
+        Format::Table => {
+            println!("{:>3}  {:<6} {:>10} {:>10} {:>10}",
+                     day,
+                     phase,
+                     timing::format_duration(stats.min),
+                     timing::format_duration(stats.median),
+                     timing::format_duration(stats.max))
+        }
+        Format::Csv => {
+            println!("{},{},{},{},{}",
+                     day,
+                     phase,
+                     stats.min.as_nanos(),
+                     stats.median.as_nanos(),
+                     stats.max.as_nanos())
+        }
+    }
+}
+
+// An assembunny day's program, from its input file
+fn read_program(day: &Day, options: &Options) -> Result<Vec<Instr>, String> {
+    match read_input(day, options) {
+        Ok(input) => assembunny::parse_program(&input).map_err(|err| format!("invalid input: {}", err)),
+        Err(err) => Err(format!("can't read input: {}", err)),
+    }
+}
+
+// Debug the first requested part of an assembunny day, taking commands from stdin
+fn debug_day(options: &Options) -> i32 {
+    let day = selected_days(options)[0];
+    let start_registers = days::find_assembunny_day(day.number).expect("not an assembunny day");
+    let program = match read_program(day, options) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("day {}: {}", day.number, err);
+            return EXIT_FAILED;
+        }
+    };
+
+    let debugger = match options.restore {
+        Some(ref path) => restore_debugger(program, path),
+        None => Ok(Debugger::new(program, start_registers(options.parts[0]))),
+    };
+    let mut debugger = match debugger {
+        Ok(debugger) => debugger,
+        Err(err) => {
+            eprintln!("day {}: {}", day.number, err);
+            return EXIT_FAILED;
+        }
+    };
+    let stdin = io::stdin();
+    match debugger.run(stdin.lock(), &mut io::stdout()) {
+        Ok(()) => EXIT_OK,
+        Err(err) => {
+            eprintln!("day {}: {}", day.number, err);
+            EXIT_FAILED
+        }
+    }
+}
+
+// A debugger carrying on from the snapshot in the file at path
+fn restore_debugger(program: Vec<Instr>, path: &str) -> Result<Debugger, String> {
+    let text = read_file(path).map_err(|err| format!("can't read snapshot {}: {}", path, err))?;
+    let state = snapshot::restore(&text).map_err(|err| format!("invalid snapshot {}: {}", path, err))?;
+    Debugger::resume(program, state)
+}
+
+// Run the first requested part of an assembunny day and print where it spent its time
+fn profile_day(options: &Options) -> i32 {
+    let day = selected_days(options)[0];
+    let start_registers = days::find_assembunny_day(day.number).expect("not an assembunny day");
+    let program = match read_program(day, options) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("day {}: {}", day.number, err);
+            return EXIT_FAILED;
+        }
+    };
+
+    let mut trace_file = match options.trace {
+        Some(ref path) => {
+            match File::create(path) {
+                Ok(file) => Some(io::BufWriter::new(file)),
+                Err(err) => {
+                    eprintln!("can't create trace file {}: {}", path, err);
+                    return EXIT_FAILED;
+                }
+            }
+        }
+        None => None,
+    };
+    let mut trace = trace_file.as_mut().map(|file| Trace::new(file, options.trace_limit));
+
+    let mut program_state = ProgramState::new(program);
+    program_state.registers = start_registers(options.parts[0]);
+    match assembunny::profile_until_halt(&mut program_state, options.max_steps, trace.as_mut()) {
+        Ok(profile) => {
+            print!("{}", profile.report(&program_state.instrs));
+            if program_state.is_halted() {
+                EXIT_OK
+            } else {
+                eprintln!("day {}: still running after {} steps, so the profile stops there",
+                          day.number,
+                          options.max_steps);
+                EXIT_FAILED
+            }
+        }
+        Err(err) => {
+            eprintln!("can't write trace: {}", err);
+            EXIT_FAILED
+        }
+    }
+}
+
+// Print an assembunny day's program with its jump targets labelled
+fn disassemble_day(options: &Options) -> i32 {
+    let day = selected_days(options)[0];
+    match read_program(day, options) {
+        Ok(program) => {
+            print!("{}", assembunny::disassemble(&program));
+            EXIT_OK
+        }
+        Err(err) => {
+            eprintln!("day {}: {}", day.number, err);
+            EXIT_FAILED
+        }
+    }
+}
+
+// Print an assembunny day's control-flow graph, for Graphviz
+fn cfg_day(options: &Options) -> i32 {
+    let day = selected_days(options)[0];
+    match read_program(day, options) {
+        Ok(program) => {
+            print!("{}", assembunny::to_dot(&program));
+            EXIT_OK
+        }
+        Err(err) => {
+            eprintln!("day {}: {}", day.number, err);
+            EXIT_FAILED
+        }
+    }
+}
+
+// Decompile an assembunny day's program, then answer the requested parts from the formulas
+// (both days leave their answer in register a)
+fn decompile_day(options: &Options) -> i32 {
+    let day = selected_days(options)[0];
+    let start_registers = days::find_assembunny_day(day.number).expect("not an assembunny day");
+    let decompiled = match read_program(day, options) {
+        Ok(program) => assembunny::decompile(&program, start_registers(1)),
+        Err(err) => Err(err),
+    };
+    let decompiled = match decompiled {
+        Ok(decompiled) => decompiled,
+        Err(err) => {
+            eprintln!("day {}: {}", day.number, err);
+            return EXIT_FAILED;
+        }
+    };
+
+    print!("{}", decompiled);
+    let mut exit_code = EXIT_OK;
+    for &part in &options.parts {
+        match decompiled.final_value(0, &start_registers(part)) {
+            Some(answer) => println!("part {}: a = {}", part, answer),
+            None => {
+                println!("part {}: the formulas don't cover this part's start", part);
+                exit_code = EXIT_FAILED;
+            }
+        }
+    }
+    exit_code
+}
+
+// Draw day 1's route from its input file
+fn route_day(options: &Options) -> i32 {
+    let day = selected_days(options)[0];
+    let turns = match read_input(day, options) {
+        Ok(input) => Advent1::parse(&input).map_err(|err| format!("invalid input: {}", err)),
+        Err(err) => Err(format!("can't read input: {}", err)),
+    };
+    let drawing = match turns {
+        Ok(turns) if options.drawing == Drawing::Svg => Ok(advent1::render_svg(&turns)),
+        Ok(turns) => advent1::render_ascii(&turns),
+        Err(err) => Err(err),
+    };
+    match drawing {
+        Ok(drawing) => {
+            print!("{}", drawing);
+            EXIT_OK
+        }
+        Err(err) => {
+            eprintln!("day {}: {}", day.number, err);
+            EXIT_FAILED
+        }
+    }
+}
+
+// Run each requested part of an assembunny day on the requested registers and print where
+// they end up. Like the days, a program that doesn't halt within the default limits gives up.
+fn exec_day(options: &Options) -> i32 {
+    let day = selected_days(options)[0];
+    let start_registers = days::find_assembunny_day(day.number).expect("not an assembunny day");
+    let program = match read_program(day, options) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("day {}: {}", day.number, err);
+            return EXIT_FAILED;
+        }
+    };
+
+    let mut exit_code = EXIT_OK;
+    for &part in &options.parts {
+        match exec_part(&program, start_registers(part), options) {
+            Ok(registers) => println!("part {}: {}", part, registers),
+            Err(err) => {
+                println!("part {}: {}", part, err);
+                exit_code = EXIT_FAILED;
+            }
+        }
+    }
+    exit_code
+}
+
+// The final registers, as "a = 1, b = 2..."
+fn exec_part(program: &[Instr], registers: Registers, options: &Options) -> Result<String, String> {
+    let config = Config::default().with_width(options.width, options.checked);
+    let names = config.names().to_vec();
+    let mut machine = Machine::optimized_with_config(config, program.to_vec())?;
+    for (name, &val) in names.iter().zip(&registers) {
+        machine.set_register(name, i128::from(val))?;
+    }
+    for &(ref name, val) in &options.set {
+        machine.set_register(name, val)?;
+    }
+
+    match machine.run_with_limits(Limits::default()) {
+        Ok(Outcome::Halted) => {
+            let values: Vec<_> = names.iter()
+                .zip(machine.register_values())
+                .map(|(name, val)| format!("{} = {}", name, val))
+                .collect();
+            Ok(values.join(", "))
+        }
+        Ok(Outcome::BudgetExceeded) => Err("still running when the step budget ran out".to_string()),
+        Ok(Outcome::Looping { .. }) => Err("never halts".to_string()),
+        Err(overflow) => Err(overflow.to_string()),
+    }
+}
+
+// Time each requested part of an assembunny day on both backends, with and without fused loops.
+// A part is only timed once it has halted within the usual limits, so a bad input can't hang it,
+// and every run must end with the registers that run did.
+fn bench_day(options: &Options) -> i32 {
+    let day = selected_days(options)[0];
+    let start_registers = days::find_assembunny_day(day.number).expect("not an assembunny day");
+    let program = match read_program(day, options) {
+        Ok(program) => program,
+        Err(err) => {
+            eprintln!("day {}: {}", day.number, err);
+            return EXIT_FAILED;
+        }
+    };
+
+    match options.format {
+        Format::Table => {
+            println!("{:>3}  {:<6} {:<22} {:>10} {:>10} {:>10}", "day", "phase", "backend", "min", "median", "max")
+        }
+        Format::Csv => println!("day,phase,backend,min_ns,median_ns,max_ns"),
+    }
+    let mut exit_code = EXIT_OK;
+    for &part in &options.parts {
+        let registers = start_registers(part);
+        let (outcome, finished) = assembunny::execute_with_limits(&program, registers, Limits::default());
+        let problem = match outcome {
+            Outcome::Halted => None,
+            Outcome::BudgetExceeded => Some("still running when the step budget ran out"),
+            Outcome::Looping { .. } => Some("never halts"),
+        };
+        if let Some(problem) = problem {
+            eprintln!("day {} part {}: {}", day.number, part, problem);
+            exit_code = EXIT_FAILED;
+            continue;
+        }
+        let phase = format!("part{}", part);
+        for &(backend, optimized) in &[("interpreter", false),
+                                       ("compiled", false),
+                                       ("interpreter, optimized", true),
+                                       ("compiled, optimized", true)] {
+            let run: fn(&[Instr], Registers, bool) -> Registers =
+                if backend.starts_with("compiled") { run_compiled } else { run_interpreter };
+            let mut mismatch = None;
+            let stats = timing::measure(&options.runs, || {
+                let ended = run(&program, registers, optimized);
+                if ended != finished.registers {
+                    mismatch = Some(ended);
+                }
+                ended
+            });
+            match mismatch {
+                None => print_bench_row(&options.format, day.number, &phase, backend, &stats),
+                Some(ended) => {
+                    eprintln!("day {} part {}: {} ended with registers {:?}, not {:?}",
+                              day.number,
+                              part,
+                              backend,
+                              ended,
+                              finished.registers);
+                    exit_code = EXIT_FAILED;
+                }
+            }
+        }
+    }
+    exit_code
+}
+
+fn run_interpreter(program: &[Instr], registers: Registers, optimized: bool) -> Registers {
+    let mut program_state = if optimized {
+        ProgramState::optimized(program.to_vec())
+    } else {
+        ProgramState::new(program.to_vec())
+    };
+    program_state.registers = registers;
+    program_state.run();
+    program_state.registers
+}
+
+fn run_compiled(program: &[Instr], registers: Registers, optimized: bool) -> Registers {
+    let mut compiled = if optimized {
+        CompiledProgram::optimized(program.to_vec())
+    } else {
+        CompiledProgram::new(program.to_vec())
+    };
+    compiled.registers = registers;
+    compiled.run();
+    compiled.registers
+}
+
+fn print_bench_row(format: &Format, day: u32, phase: &str, backend: &str, stats: &Stats) {
+    match *format {
+        Format::Table => {
+            println!("{:>3}  {:<6} {:<22} {:>10} {:>10} {:>10}",
+                     day,
+                     phase,
+                     backend,
+                     timing::format_duration(stats.min),
+                     timing::format_duration(stats.median),
+                     timing::format_duration(stats.max))
+        }
+        Format::Csv => {
+            println!("{},{},{},{},{},{}",
+                     day,
+                     phase,
+                     backend,
+                     stats.min.as_nanos(),
+                     stats.median.as_nanos(),
+                     stats.max.as_nanos())
+        }
+    }
+}
+
+// Running or timing a single day reads stdin by default, everything else needs an input file
+// for each day
+fn read_input(day: &Day, options: &Options) -> io::Result<String> {
+    match (&options.command, &options.target, &options.input) {
+        (_, _, Some(path)) => read_file(path),
+        (&Command::Run, &Target::Day(_), None) |
+        (&Command::Time, &Target::Day(_), None) => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+        _ => read_file(&format!("{}/input{}.txt", options.input_dir, day.number)),
+    }
+}
+
+fn read_file(path: &str) -> io::Result<String> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+// //////
+// Tests
+#[cfg(test)]
+fn args(s: &str) -> Vec<String> {
+    s.split_whitespace().map(|x| x.to_string()).collect()
+}
+
+#[test]
+fn test_parse_args() {
+    let options = parse_args(&args("run 12 --part 2")).unwrap();
+    assert_eq!(Command::Run, options.command);
+    assert_eq!(Target::Day(12), options.target);
+    assert_eq!(vec![2], options.parts);
+    assert_eq!(None, options.input);
+
+    let options = parse_args(&args("run all --input-dir puzzles")).unwrap();
+    assert_eq!(Target::All, options.target);
+    assert_eq!(vec![1, 2], options.parts);
+    assert_eq!("puzzles", options.input_dir);
+
+    let options = parse_args(&args("verify all --answers mine.toml")).unwrap();
+    assert_eq!(Command::Verify, options.command);
+    assert_eq!(Target::All, options.target);
+    assert_eq!(Some("mine.toml".to_string()), options.answers);
+
+    let options = parse_args(&args("time 14 --repeats 10 --warmup 0 --format csv")).unwrap();
+    assert_eq!(Command::Time, options.command);
+    assert_eq!(Runs {
+                   warmup: 0,
+                   repeats: 10,
+               },
+               options.runs);
+    assert_eq!(Format::Csv, options.format);
+
+    let options = parse_args(&args("debug 23 --part 2 --input day23.txt")).unwrap();
+    assert_eq!(Command::Debug, options.command);
+    assert_eq!(vec![2], options.parts);
+
+    let options = parse_args(&args("debug 23 --restore snap.txt")).unwrap();
+    assert_eq!(Some("snap.txt".to_string()), options.restore);
+
+    let options = parse_args(&args("profile 12 --trace t.txt --trace-limit 50")).unwrap();
+    assert_eq!(Command::Profile, options.command);
+    assert_eq!(Some("t.txt".to_string()), options.trace);
+    assert_eq!(50, options.trace_limit);
+    assert_eq!(1000, parse_args(&args("profile 12 --max-steps 1000")).unwrap().max_steps);
+
+    let options = parse_args(&args("bench 12 --repeats 1 --format csv")).unwrap();
+    assert_eq!(Command::Bench, options.command);
+    assert_eq!(1, options.runs.repeats);
+
+    let options = parse_args(&args("route 1")).unwrap();
+    assert_eq!(Command::Route, options.command);
+    assert_eq!(Drawing::Ascii, options.drawing);
+    assert_eq!(Drawing::Svg, parse_args(&args("route 1 --format svg")).unwrap().drawing);
+
+    let options = parse_args(&args("exec 23 --width i64 --overflow check --set a=13 --set b=-2")).unwrap();
+    assert_eq!(Command::Exec, options.command);
+    assert_eq!(Width::I64, options.width);
+    assert!(options.checked);
+    assert_eq!(vec![("a".to_string(), 13), ("b".to_string(), -2)], options.set);
+
+    let options = parse_args(&args("cfg 23 --input day23.txt")).unwrap();
+    assert_eq!(Command::Cfg, options.command);
+    assert_eq!(Some("day23.txt".to_string()), options.input);
+}
+
+#[test]
+fn test_parse_args_errors() {
+    assert!(parse_args(&args("")).is_err());
+    assert!(parse_args(&args("walk 1")).is_err());
+    assert!(parse_args(&args("run")).is_err());
+    assert!(parse_args(&args("run 26")).is_err());
+    assert!(parse_args(&args("run 1 --part 3")).is_err());
+    assert!(parse_args(&args("run 1 --part")).is_err());
+    assert!(parse_args(&args("run all --input foo.txt")).is_err());
+    assert!(parse_args(&args("run 1 --answers mine.toml")).is_err());
+    assert!(parse_args(&args("run 1 --repeats 5")).is_err());
+    assert!(parse_args(&args("time 1 --repeats 0")).is_err());
+    assert!(parse_args(&args("time 1 --format json")).is_err());
+    assert!(parse_args(&args("debug 13")).is_err());
+    assert!(parse_args(&args("debug all")).is_err());
+    assert!(parse_args(&args("profile 1")).is_err());
+    assert!(parse_args(&args("disasm 25")).is_err());
+    assert!(parse_args(&args("decompile all")).is_err());
+    assert!(parse_args(&args("bench 25")).is_err());
+    assert!(parse_args(&args("cfg 12 --part 2 --trace t.txt")).is_err());
+    assert!(parse_args(&args("run 12 --trace t.txt")).is_err());
+    assert!(parse_args(&args("profile 12 --trace-limit lots")).is_err());
+    assert!(parse_args(&args("profile 12 --max-steps lots")).is_err());
+    assert!(parse_args(&args("run 12 --max-steps 1000")).is_err());
+    assert!(parse_args(&args("profile 12 --restore snap.txt")).is_err());
+    assert!(parse_args(&args("route 2")).is_err());
+    assert!(parse_args(&args("route all")).is_err());
+    assert!(parse_args(&args("route 1 --format csv")).is_err());
+    assert!(parse_args(&args("time 1 --format svg")).is_err());
+    assert!(parse_args(&args("run 1 --format table")).is_err());
+    assert!(parse_args(&args("exec 25")).is_err());
+    assert!(parse_args(&args("exec 23 --width i16")).is_err());
+    assert!(parse_args(&args("exec 23 --overflow saturate")).is_err());
+    assert!(parse_args(&args("exec 23 --set a")).is_err());
+    assert!(parse_args(&args("exec 23 --set a=lots")).is_err());
+    assert!(parse_args(&args("run 23 --width i64")).is_err());
+}
@@ -1,7 +1,106 @@
 // common.rs
 // Work that's common to multiple Advent of Code problems
 
-use std::collections::{VecDeque, HashSet};
+#[macro_use]
+extern crate lazy_static;
+extern crate regex;
+extern crate md5;
+extern crate futures;
+extern crate futures_cpupool;
+extern crate permutohedron;
+extern crate toml;
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{self, Display};
+
+pub use grid::Grid;
+
+pub mod assembunny;
+pub mod days;
+pub mod grid;
+pub mod search;
+pub mod timing;
+pub mod verify;
+
+// One day's puzzle. The input is parsed once and then shared by both parts.
+// A part returns None if the input has no answer.
+pub trait Solution {
+    type Input;
+    type Answer: Display;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Option<Self::Answer>;
+    fn part2(input: &Self::Input) -> Option<Self::Answer>;
+}
+
+// Malformed puzzle input: where it is, what was found there, and what should have been there.
+// Line and column both start at 1.
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, text: &str, expected: &str) -> ParseError {
+        ParseError {
+            line,
+            column,
+            text: text.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+
+    // Parsers for a single line report line 1; this moves the error to the real line
+    pub fn on_line(self, line: usize) -> ParseError {
+        ParseError { line, ..self }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "line {}, column {}: expected {}, found \"{}\"",
+               self.line,
+               self.column,
+               self.expected,
+               self.text)
+    }
+}
+
+impl Error for ParseError {}
+
+// Column where slice starts within line. slice must be a subslice of line
+pub fn column_of(line: &str, slice: &str) -> usize {
+    1 + slice.as_ptr() as usize - line.as_ptr() as usize
+}
+
+// For puzzles whose whole input is a single line: returns that line, trimmed
+pub fn parse_single_line<'a>(input: &'a str, expected: &str) -> Result<&'a str, ParseError> {
+    let mut lines = input.trim().lines();
+    let line = lines.next().unwrap_or("");
+
+    if line.is_empty() {
+        Err(ParseError::new(1, 1, line, expected))
+    } else if let Some(extra) = lines.next() {
+        Err(ParseError::new(2, 1, extra, "end of input"))
+    } else {
+        Ok(line)
+    }
+}
+
+// Parse every line of the input with parse_line, stopping at the first error
+pub fn parse_lines<T, F>(input: &str, parse_line: F) -> Result<Vec<T>, ParseError>
+    where F: Fn(&str) -> Result<T, ParseError>
+{
+    input.lines()
+        .enumerate()
+        .map(|(i, line)| parse_line(line).map_err(|err| err.on_line(i + 1)))
+        .collect()
+}
 
 pub type Location = (i32, i32);
 
@@ -9,31 +108,56 @@ pub type Location = (i32, i32);
 pub fn find_steps<F>(start: Location, goal: Location, is_wall: F) -> Option<usize>
     where F: Fn(Location) -> bool
 {
-    if start == goal {
-        return Some(0);
-    }
-
-    let mut locations = VecDeque::new();
-    let mut visited = HashSet::new();
-    locations.push_back((start, 0));
-    visited.insert(start);
-
-    while let Some((location, steps)) = locations.pop_front() {
-        // find all neighbors
-        for &(i, j) in &[(-1, 0), (1, 0), (0, -1), (0, 1)] {
-            let neighbor = (location.0 + i, location.1 + j);
-            if neighbor == goal {
-                return Some(steps + 1);
-            }
-            if neighbor.0 >= 0 && neighbor.1 >= 0 && !is_wall(neighbor) &&
-               !visited.contains(&neighbor) {
-                locations.push_back((neighbor, steps + 1));
-                visited.insert(neighbor);
-            }
-        }
-    }
+    let successors = |&location: &Location| open_neighbors(location, Some(goal), &is_wall);
+    search::bfs_distance(start, successors, |&location| location == goal)
+}
 
-    None
+// Like find_steps, but returns the locations along a shortest path, start and goal included
+pub fn find_path<F>(start: Location, goal: Location, is_wall: F) -> Option<Vec<Location>>
+    where F: Fn(Location) -> bool
+{
+    let successors = |&location: &Location| open_neighbors(location, Some(goal), &is_wall);
+    search::bfs(start, successors, |&location| location == goal).found.map(|found| found.path)
+}
+
+// Flood outwards from start, up to max_steps away.
+// Returns the number of steps to every location reached, start included.
+pub fn find_distances<F>(start: Location, max_steps: usize, is_wall: F) -> HashMap<Location, usize>
+    where F: Fn(Location) -> bool
+{
+    let successors = |&location: &Location| open_neighbors(location, None, &is_wall);
+    search::distances(start, max_steps, successors, |_| false)
+}
+
+// Number of steps from start to each target (None if it can't be reached).
+// Only floods as far as it needs to reach every target.
+pub fn find_steps_to_targets<F>(start: Location, targets: &[Location], is_wall: F) -> Vec<Option<usize>>
+    where F: Fn(Location) -> bool
+{
+    let mut remaining: HashSet<_> = targets.iter().cloned().collect();
+    let successors = |&location: &Location| open_neighbors(location, None, &is_wall);
+    let distances = search::distances(start, usize::MAX, successors, |location| {
+        remaining.remove(location);
+        remaining.is_empty()
+    });
+
+    targets.iter().map(|target| distances.get(target).cloned()).collect()
+}
+
+// The locations a step away from location that aren't walls or off the top or left of the
+// map. The goal, if there is one, is always open, even if it's in a wall.
+fn open_neighbors<'a, F>(location: Location,
+                         goal: Option<Location>,
+                         is_wall: &'a F)
+                         -> impl Iterator<Item = Location> + 'a
+    where F: Fn(Location) -> bool
+{
+    [(-1, 0), (1, 0), (0, -1), (0, 1)]
+        .iter()
+        .map(move |&(i, j)| (location.0 + i, location.1 + j))
+        .filter(move |&neighbor| {
+            Some(neighbor) == goal || neighbor.0 >= 0 && neighbor.1 >= 0 && !is_wall(neighbor)
+        })
 }
 
 // //////
@@ -41,10 +165,11 @@ pub fn find_steps<F>(start: Location, goal: Location, is_wall: F) -> Option<usiz
 //
 #[cfg(test)]
 mod tests {
-    use super::{Location, find_steps};
+    use super::{Location, ParseError, find_distances, find_path, find_steps, find_steps_to_targets,
+                parse_lines, parse_single_line};
 
     fn is_wall((x, y): Location, favorite: i32) -> bool {
-        (x * x + 3 * x + 2 * x * y + y + y * y + favorite).count_ones() % 2 != 0
+        !(x * x + 3 * x + 2 * x * y + y + y * y + favorite).count_ones().is_multiple_of(2)
     }
 
     #[test]
@@ -54,4 +179,74 @@ mod tests {
         assert_eq!(Some(11), find_steps((1, 1), (7, 4), |loc| is_wall(loc, 10)));
         assert_eq!(None, find_steps((1, 1), (31, 39), |loc| is_wall(loc, 10)));
     }
+
+    #[test]
+    fn test_find_distances() {
+        let distances = find_distances((1, 1), 2, |loc| is_wall(loc, 10));
+        assert_eq!(5, distances.len());
+        assert_eq!(Some(&0), distances.get(&(1, 1)));
+        assert_eq!(Some(&1), distances.get(&(0, 1)));
+        assert_eq!(Some(&2), distances.get(&(2, 2)));
+        assert_eq!(None, distances.get(&(7, 4)));
+
+        // agrees with find_steps everywhere it reached
+        let distances = find_distances((1, 1), 11, |loc| is_wall(loc, 10));
+        assert_eq!(Some(&11), distances.get(&(7, 4)));
+        for (&location, &steps) in &distances {
+            assert_eq!(Some(steps), find_steps((1, 1), location, |loc| is_wall(loc, 10)));
+        }
+    }
+
+    #[test]
+    fn test_find_steps_to_targets() {
+        assert_eq!(vec![Some(11), Some(0), None],
+                   find_steps_to_targets((1, 1), &[(7, 4), (1, 1), (1, 0)], |loc| is_wall(loc, 10)));
+        assert_eq!(Vec::<Option<usize>>::new(),
+                   find_steps_to_targets((1, 1), &[], |loc| is_wall(loc, 10)));
+    }
+
+    #[test]
+    fn test_find_path() {
+        assert_eq!(Some(vec![(13, 37)]),
+                   find_path((13, 37), (13, 37), |loc| is_wall(loc, 10)));
+        assert_eq!(None, find_path((1, 1), (31, 39), |loc| is_wall(loc, 10)));
+
+        let path = find_path((1, 1), (7, 4), |loc| is_wall(loc, 10)).unwrap();
+        assert_eq!(12, path.len());
+        assert_eq!((1, 1), path[0]);
+        assert_eq!((7, 4), path[11]);
+        for step in path.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            assert_eq!(1, (from.0 - to.0).abs() + (from.1 - to.1).abs());
+            assert!(!is_wall(to, 10));
+        }
+        // like find_steps, the path can end in a wall
+        assert_eq!(Some(vec![(1, 1), (1, 0)]), find_path((1, 1), (1, 0), |loc| is_wall(loc, 10)));
+        assert_eq!(Some(1), find_steps((1, 1), (1, 0), |loc| is_wall(loc, 10)));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let parse_digit = |line: &str| {
+            line.parse::<u32>().map_err(|_| ParseError::new(1, 1, line, "a number"))
+        };
+        assert_eq!(Ok(vec![1, 2, 3]), parse_lines("1\n2\n3\n", parse_digit));
+        assert_eq!(Err(ParseError::new(2, 1, "x", "a number")),
+                   parse_lines("1\nx\n3\n", parse_digit));
+    }
+
+    #[test]
+    fn test_parse_single_line() {
+        assert_eq!(Ok("abc"), parse_single_line("  abc\n", "a salt"));
+        assert_eq!(Err(ParseError::new(1, 1, "", "a salt")), parse_single_line("\n", "a salt"));
+        assert_eq!(Err(ParseError::new(2, 1, "def", "end of input")),
+                   parse_single_line("abc\ndef\n", "a salt"));
+    }
+
+    #[test]
+    fn test_parse_error_display() {
+        let err = ParseError::new(3, 7, "R", "L or R followed by a distance");
+        assert_eq!("line 3, column 7: expected L or R followed by a distance, found \"R\"",
+                   err.to_string());
+    }
 }
@@ -0,0 +1,451 @@
+// advent1.rs
+// Manhattan lengths and segment intersection
+
+use {Grid, ParseError, Solution, column_of};
+
+// positions are i64: a couple of turns of up to i32::MAX blocks would overflow i32
+type Vec2 = [i64; 2];
+
+// the walk starts facing north, and turns take it to the other directions
+const NORTH: Vec2 = [0, 1];
+
+pub struct Advent1;
+
+impl Solution for Advent1 {
+    type Input = Vec<Turn>;
+    type Answer = i64;
+
+    fn parse(input: &str) -> Result<Vec<Turn>, ParseError> {
+        parse_turns(input)
+    }
+
+    fn part1(turns: &Vec<Turn>) -> Option<i64> {
+        Some(calc_manhattan_length(turns))
+    }
+
+    fn part2(turns: &Vec<Turn>) -> Option<i64> {
+        Some(find_first_revisited_distance(turns))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Turn {
+    Left(i32),
+    Right(i32),
+}
+
+impl Turn {
+    // returns new direction and the distance to walk in it
+    fn apply(self, dir: Vec2) -> (Vec2, i64) {
+        match self {
+            Turn::Right(dist) => (turn_right(dir), i64::from(dist)),
+            Turn::Left(dist) => (turn_left(dir), i64::from(dist)),
+        }
+    }
+}
+
+// split the input into individual turns
+fn parse_turns(turns: &str) -> Result<Vec<Turn>, ParseError> {
+    turns.trim()
+        .split(", ")
+        .map(|turn| {
+            parse_turn(turn).ok_or_else(|| {
+                ParseError::new(1, column_of(turns, turn), turn, "L or R followed by a distance")
+            })
+        })
+        .collect()
+}
+
+// One straight stretch of the route, between two turns
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Walk {
+    from: Vec2,
+    dir: Vec2,
+    distance: i64, // never negative: walking backwards is walking the other way
+}
+
+impl Walk {
+    fn to(&self) -> Vec2 {
+        [self.from[0] + self.dir[0] * self.distance, self.from[1] + self.dir[1] * self.distance]
+    }
+}
+
+// The route the turns trace, one walk per turn, starting at the origin facing north
+fn walks(turns: &[Turn]) -> impl Iterator<Item = Walk> + '_ {
+    turns.iter().scan((NORTH, [0, 0]), |&mut (ref mut facing, ref mut position), turn| {
+        let (new_facing, dist) = turn.apply(*facing);
+        *facing = new_facing;
+        let walk = if dist < 0 {
+            Walk { from: *position, dir: [-new_facing[0], -new_facing[1]], distance: -dist }
+        } else {
+            Walk { from: *position, dir: new_facing, distance: dist }
+        };
+        *position = walk.to();
+        Some(walk)
+    })
+}
+
+// where the route ends
+fn final_position(turns: &[Turn]) -> Vec2 {
+    walks(turns).last().map_or([0, 0], |walk| walk.to())
+}
+
+// add up all the distance vectors, and return the distance
+fn calc_manhattan_length(turns: &[Turn]) -> i64 {
+    let position = final_position(turns);
+    position[0].abs() + position[1].abs()
+}
+
+// A turn is L or R followed by a number. Returns None if given garbage
+fn parse_turn(turn: &str) -> Option<Turn> {
+    let mut chars = turn.chars();
+    let dir = chars.next();
+    let dist = chars.as_str().parse::<i32>().ok()?;
+
+    match dir {
+        Some('R') => Some(Turn::Right(dist)),
+        Some('L') => Some(Turn::Left(dist)),
+        _ => None,
+    }
+}
+
+// rotate clockwise 90 degrees
+fn turn_right(dir: Vec2) -> Vec2 {
+    [dir[1], -dir[0]]
+}
+
+// rotate counter-clockwise 90 degrees
+fn turn_left(dir: Vec2) -> Vec2 {
+    [-dir[1], dir[0]]
+}
+
+
+// ////////
+// Part 2
+//
+// The path so far is kept as segments: each walk is a horizontal or vertical segment from where
+// it started to where it ended, and the start is a segment of one point. Each new walk is
+// checked against all of them, so the time depends on the number of turns, not on how far they
+// go.
+
+// Everywhere from one point to another along a line, inclusive
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Segment {
+    min: Vec2,
+    max: Vec2,
+}
+
+impl Segment {
+    fn new(from: Vec2, to: Vec2) -> Segment {
+        Segment {
+            min: [from[0].min(to[0]), from[1].min(to[1])],
+            max: [from[0].max(to[0]), from[1].max(to[1])],
+        }
+    }
+
+    // The fewest steps after start that walking in dir reaches this segment, if it's within
+    // distance. Crossing it and running along it are handled alike: each step reaches one
+    // point, and the points on the segment are those within its bounds on both axes.
+    fn first_step_onto(&self, start: Vec2, dir: Vec2, distance: i64) -> Option<i64> {
+        let (along, across) = if dir[0] != 0 { (0, 1) } else { (1, 0) };
+        if start[across] < self.min[across] || start[across] > self.max[across] {
+            return None;
+        }
+        // the steps that land within the segment's bounds along the walk
+        let (first, last) = if dir[along] > 0 {
+            (self.min[along] - start[along], self.max[along] - start[along])
+        } else {
+            (start[along] - self.max[along], start[along] - self.min[along])
+        };
+        let first = first.max(1);
+        if first <= last.min(distance) { Some(first) } else { None }
+    }
+}
+
+// Where the route first comes back to somewhere it's already been, if it ever does
+fn first_revisit(turns: &[Turn]) -> Option<Vec2> {
+    let mut walked = vec![Segment::new([0, 0], [0, 0])];
+
+    for walk in walks(turns) {
+        // that includes the walk before, which a turn of 0 and another turn can lead back along
+        let revisit = walked.iter()
+            .filter_map(|segment| segment.first_step_onto(walk.from, walk.dir, walk.distance))
+            .min();
+        if let Some(steps) = revisit {
+            return Some([walk.from[0] + walk.dir[0] * steps, walk.from[1] + walk.dir[1] * steps]);
+        }
+        walked.push(Segment::new(walk.from, walk.to()));
+    }
+
+    None
+}
+
+fn find_first_revisited_distance(turns: &[Turn]) -> i64 {
+    let position = first_revisit(turns).unwrap_or_else(|| final_position(turns));
+    position[0].abs() + position[1].abs()
+}
+
+// ////////
+// Drawing the route
+//
+// Both drawings mark the start, the end of the route, and the first revisit part 2 stops at.
+
+// Maps wider or taller than this many blocks are refused
+const MAX_MAP_SIZE: i64 = 1000;
+
+// The corners of the route, from the start to the end, and the smallest and largest x and y
+// among them
+fn corners_and_bounds(turns: &[Turn]) -> (Vec<Vec2>, Vec2, Vec2) {
+    let corners: Vec<_> = Some([0, 0]).into_iter().chain(walks(turns).map(|walk| walk.to())).collect();
+    let mut min = [0, 0];
+    let mut max = [0, 0];
+    for corner in &corners {
+        for axis in 0..2 {
+            min[axis] = min[axis].min(corner[axis]);
+            max[axis] = max[axis].max(corner[axis]);
+        }
+    }
+    (corners, min, max)
+}
+
+// The route as text, one character per block with north at the top: - and | along walks, + at
+// corners and crossings, S at the start, E at the end and X at the first revisit
+pub fn render_ascii(turns: &[Turn]) -> Result<String, String> {
+    let (corners, min, max) = corners_and_bounds(turns);
+    let (width, height) = (max[0] - min[0] + 1, max[1] - min[1] + 1);
+    if width > MAX_MAP_SIZE || height > MAX_MAP_SIZE {
+        return Err(format!("the route covers {} by {} blocks, too many for a map", width, height));
+    }
+
+    let location = |position: Vec2| ((position[0] - min[0]) as i32, (max[1] - position[1]) as i32);
+    let mut map = Grid::new(width as usize, height as usize, ' ');
+    for walk in walks(turns) {
+        let (along, across) = if walk.dir[0] != 0 { ('-', '|') } else { ('|', '-') };
+        for step in 0..walk.distance + 1 {
+            let position = [walk.from[0] + walk.dir[0] * step, walk.from[1] + walk.dir[1] * step];
+            let cell = map.get_mut(location(position)).unwrap();
+            *cell = if *cell == across || *cell == '+' { '+' } else { along };
+        }
+    }
+    map.set(location(*corners.last().unwrap()), 'E');
+    map.set(location([0, 0]), 'S');
+    if let Some(revisit) = first_revisit(turns) {
+        map.set(location(revisit), 'X');
+    }
+
+    let lines: Vec<_> = map.render(|&cell| cell).lines().map(|line| line.trim_end().to_string()).collect();
+    Ok(lines.join("\n") + "\n")
+}
+
+// The route as an SVG image, with north at the top: a line through every corner, and a dot at
+// the start (green), the end (red) and the first revisit (blue)
+pub fn render_svg(turns: &[Turn]) -> String {
+    let (corners, min, max) = corners_and_bounds(turns);
+    // SVG's y axis points down, so every y is flipped
+    let size = (max[0] - min[0]).max(max[1] - min[1]).max(1) as f64;
+    let margin = size / 20.0;
+    let points: Vec<_> = corners.iter().map(|corner| format!("{},{}", corner[0], -corner[1])).collect();
+    let dot = |position: Vec2, color: &str, title: &str| {
+        format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"><title>{}</title></circle>\n",
+                position[0],
+                -position[1],
+                size / 80.0,
+                color,
+                title)
+    };
+
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+                          min[0] as f64 - margin,
+                          -max[1] as f64 - margin,
+                          (max[0] - min[0]) as f64 + 2.0 * margin,
+                          (max[1] - min[1]) as f64 + 2.0 * margin);
+    svg.push_str(&format!("<polyline points=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"{}\"/>\n",
+                          points.join(" "),
+                          size / 200.0));
+    svg.push_str(&dot([0, 0], "green", "start"));
+    svg.push_str(&dot(*corners.last().unwrap(), "red", "end"));
+    if let Some(revisit) = first_revisit(turns) {
+        svg.push_str(&dot(revisit, "blue", "first revisit"));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// //////
+// Tests
+#[cfg(test)]
+const SOUTH: Vec2 = [0, -1];
+#[cfg(test)]
+const EAST: Vec2 = [1, 0];
+#[cfg(test)]
+const WEST: Vec2 = [-1, 0];
+
+#[test]
+fn test_turn_right() {
+    assert_eq!(EAST, turn_right(NORTH));
+    assert_eq!(SOUTH, turn_right(EAST));
+    assert_eq!(WEST, turn_right(SOUTH));
+    assert_eq!(NORTH, turn_right(WEST));
+}
+
+#[test]
+fn test_turn_left() {
+    assert_eq!(WEST, turn_left(NORTH));
+    assert_eq!(NORTH, turn_left(EAST));
+    assert_eq!(EAST, turn_left(SOUTH));
+    assert_eq!(SOUTH, turn_left(WEST));
+}
+
+#[test]
+fn test_parse_turn() {
+    assert_eq!(Some(Turn::Right(3)), parse_turn("R3"));
+    assert_eq!((EAST, 3), Turn::Right(3).apply(NORTH));
+    assert_eq!(Some(Turn::Left(23)), parse_turn("L23"));
+    assert_eq!((NORTH, 23), Turn::Left(23).apply(EAST));
+    assert_eq!(None, parse_turn("X3"));
+    assert_eq!(None, parse_turn("R"));
+    assert_eq!(None, parse_turn(""));
+}
+
+#[test]
+fn test_parse_turns() {
+    assert_eq!(Ok(vec![Turn::Right(2), Turn::Left(3)]), parse_turns("R2, L3\n"));
+    assert_eq!(Err(ParseError::new(1, 5, "Lx", "L or R followed by a distance")),
+               parse_turns("R2, Lx, R5"));
+}
+
+#[test]
+fn test_calc_manhattan_length() {
+    assert_eq!(5, calc_manhattan_length(&parse_turns("R2, L3").unwrap()));
+    assert_eq!(2, calc_manhattan_length(&parse_turns("R2, R2, R2").unwrap()));
+    assert_eq!(12, calc_manhattan_length(&parse_turns("R5, L5, R5, R3").unwrap()));
+    // further than i32 goes
+    assert_eq!(4000000000, calc_manhattan_length(&parse_turns("R2000000000, L0, R2000000000").unwrap()));
+}
+
+#[test]
+fn test_walks() {
+    let turns = parse_turns("R2, L3, R-1").unwrap();
+    assert_eq!(vec![Walk { from: [0, 0], dir: EAST, distance: 2 },
+                    Walk { from: [2, 0], dir: NORTH, distance: 3 },
+                    Walk { from: [2, 3], dir: WEST, distance: 1 }],
+               walks(&turns).collect::<Vec<_>>());
+    assert_eq!([1, 3], final_position(&turns));
+    assert_eq!([0, 0], final_position(&[]));
+}
+
+// part 2
+#[test]
+fn test_find_first_revisited_distance() {
+    assert_eq!(4, find_first_revisited_distance(&parse_turns("R8, R4, R4, R8").unwrap()));
+}
+
+#[test]
+fn test_first_step_onto() {
+    let segment = Segment::new([2, 5], [2, -1]);
+    // across it
+    assert_eq!(Some(2), segment.first_step_onto([0, 0], EAST, 10));
+    assert_eq!(None, segment.first_step_onto([0, 0], EAST, 1));
+    assert_eq!(None, segment.first_step_onto([0, 6], EAST, 10));
+    // along it, from either end and from beyond it
+    assert_eq!(Some(2), segment.first_step_onto([2, 7], SOUTH, 3));
+    assert_eq!(Some(1), segment.first_step_onto([2, -1], NORTH, 3));
+    assert_eq!(None, segment.first_step_onto([2, 5], NORTH, 3));
+    // a single point
+    assert_eq!(Some(4), Segment::new([0, 0], [0, 0]).first_step_onto([-4, 0], EAST, 4));
+}
+
+#[test]
+fn test_find_first_revisited_distance_segments() {
+    // crossing the start, and doubling back over the last walk after a turn of 0
+    assert_eq!(0, find_first_revisited_distance(&parse_turns("R2, L2, L2, L4").unwrap()));
+    assert_eq!(4, find_first_revisited_distance(&parse_turns("R5, R0, R2").unwrap()));
+    // walking backwards, as part 1 does for a negative distance
+    assert_eq!(5, find_first_revisited_distance(&parse_turns("R3, R0, R-2").unwrap()));
+    assert_eq!(2, find_first_revisited_distance(&parse_turns("R3, L0, R-1").unwrap()));
+    // however far the walks go
+    assert_eq!(300000000, find_first_revisited_distance(&parse_turns("R100000000, L200000000").unwrap()));
+    assert_eq!(99999995, find_first_revisited_distance(&parse_turns("L100000000, L5, L5, L100000000").unwrap()));
+    assert_eq!(4000000000, find_first_revisited_distance(&parse_turns("R2000000000, L0, R2000000000").unwrap()));
+    let turns = parse_turns("R2147483647, R2147483647, R2147483647, R2147483647").unwrap();
+    assert_eq!(0, find_first_revisited_distance(&turns));
+}
+
+// The way part 2 used to be solved, one block at a time, except that a negative distance now
+// walks backwards as it does in part 1 rather than not moving at all
+#[cfg(test)]
+fn walk_first_revisited_distance(turns: &[Turn]) -> i64 {
+    use std::collections::HashSet;
+
+    let mut dir = NORTH;
+    let mut position = [0, 0];
+    let mut visited = HashSet::new();
+    visited.insert(position);
+
+    'outer: for turn in turns {
+        let (new_dir, distance) = turn.apply(dir);
+        dir = new_dir;
+        let step = if distance < 0 { [-dir[0], -dir[1]] } else { dir };
+        for _ in 0..distance.abs() {
+            position[0] += step[0];
+            position[1] += step[1];
+            if !visited.insert(position) {
+                break 'outer;
+            }
+        }
+    }
+
+    position[0].abs() + position[1].abs()
+}
+
+#[test]
+fn test_segments_match_walking() {
+    // short routes made up from a fixed sequence, so they wind back over themselves a lot
+    let mut seed = 2016u32;
+    for _ in 0..500 {
+        let turns: Vec<_> = (0..12)
+            .map(|_| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                let distance = (seed >> 16) as i32 % 9 - 3;
+                if seed >> 31 == 0 { Turn::Left(distance) } else { Turn::Right(distance) }
+            })
+            .collect();
+        assert_eq!(walk_first_revisited_distance(&turns),
+                   find_first_revisited_distance(&turns),
+                   "{:?}",
+                   turns);
+    }
+}
+
+// drawing
+#[test]
+fn test_render_ascii() {
+    assert_eq!("    E
+    |
+    |
+    |
+S---X---+
+    |   |
+    |   |
+    |   |
+    +---+
+",
+               render_ascii(&parse_turns("R8, R4, R4, R8").unwrap()).unwrap());
+    // ending where it started, with no revisit before that
+    assert_eq!("+-+\n| |\nX-+\n", render_ascii(&parse_turns("L0, R2, R2, R2, R2").unwrap()).unwrap());
+    assert_eq!(Err("the route covers 1 by 1001 blocks, too many for a map".to_string()),
+               render_ascii(&parse_turns("L0, L1000").unwrap()));
+}
+
+#[test]
+fn test_render_svg() {
+    let svg = render_svg(&parse_turns("R8, R4, R4, R8").unwrap());
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-0.4 -4.4 8.8 8.8\">\n"));
+    assert!(svg.contains("<polyline points=\"0,0 8,0 8,4 4,4 4,-4\" "));
+    assert!(svg.contains("<circle cx=\"0\" cy=\"0\" r=\"0.1\" fill=\"green\"><title>start</title></circle>"));
+    assert!(svg.contains("<circle cx=\"4\" cy=\"-4\" r=\"0.1\" fill=\"red\"><title>end</title></circle>"));
+    assert!(svg.contains("<circle cx=\"4\" cy=\"0\" r=\"0.1\" fill=\"blue\"><title>first revisit</title></circle>"));
+    assert!(svg.ends_with("</svg>\n"));
+    // a route that never moves still has room around its dots
+    assert!(render_svg(&[]).contains("viewBox=\"-0.05 -0.05 0.1 0.1\""));
+}
@@ -0,0 +1,336 @@
+// advent10.rs
+// parsing instructions for chip factory
+
+use regex::Regex;
+use {ParseError, Solution, parse_lines};
+
+pub struct Advent10;
+
+impl Solution for Advent10 {
+    type Input = Factory;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Factory, ParseError> {
+        parse_lines(input, check_instruction)?;
+        let mut factory = Factory::new();
+
+        for (i, line) in input.lines().enumerate() {
+            // part 1
+            factory.parse_instruction(line).map_err(|err| err.on_line(i + 1))?;
+            // part 2
+            factory.parse_instruction2(line).map_err(|err| err.on_line(i + 1))?;
+        }
+
+        Ok(factory)
+    }
+
+    // bot ID
+    fn part1(factory: &Factory) -> Option<usize> {
+        factory.clone().find_comparison(61, 17)
+    }
+
+    // output product, if the wiring gets a chip to each output
+    fn part2(factory: &Factory) -> Option<usize> {
+        let mut factory = factory.clone();
+        Some(factory.get_output_val(0)? * factory.get_output_val(1)? * factory.get_output_val(2)?)
+    }
+}
+
+// The regexes below only look for the parts of an instruction they care about,
+// so make sure the whole line is one of the two instruction forms first
+fn check_instruction(line: &str) -> Result<(), ParseError> {
+    lazy_static! {
+        static ref RE_INSTR: Regex =
+            Regex::new(r"^(value \d+ goes to bot \d+|bot \d+ gives low to (bot|output) \d+ and high to (bot|output) \d+)$")
+                .unwrap();
+    }
+    if RE_INSTR.is_match(line) {
+        Ok(())
+    } else {
+        Err(ParseError::new(1, 1, line, "a value or bot instruction"))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Chip {
+    Unassigned,
+    BotLow(usize),
+    BotHigh(usize),
+    Val(usize),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Factory {
+    chip0: Vec<Chip>,
+    chip1: Vec<Chip>,
+    outputs: Vec<Chip>,
+}
+
+impl Factory {
+    fn new() -> Factory {
+        Factory {
+            chip0: Vec::new(),
+            chip1: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    // no need to parse outputs since they aren't used for the answer to part 1.
+    // A bot can't be given more than two chips.
+    fn parse_instruction(&mut self, instr: &str) -> Result<(), ParseError> {
+        lazy_static! {
+            static ref RE_VAL: Regex = Regex::new(r"^value (\d+) goes to bot (\d+)").unwrap();
+            static ref RE_BOT_LOW: Regex = Regex::new(r"^bot (\d+).+low to bot (\d+)").unwrap();
+            static ref RE_BOT_HIGH: Regex = Regex::new(r"^bot (\d+).+high to bot (\d+)").unwrap();
+        }
+        let too_many = |bot_id| {
+            ParseError::new(1, 1, instr, &format!("no more than two chips going to bot {}", bot_id))
+        };
+        if let Some(caps) = RE_VAL.captures(instr) {
+            let val: usize = caps[1].parse().unwrap();
+            let bot_id: usize = caps[2].parse().unwrap();
+            self.give_chip_to_bot(bot_id, Chip::Val(val)).map_err(too_many)?;
+        } else {
+            if let Some(caps) = RE_BOT_LOW.captures(instr) {
+                let src_bot: usize = caps[1].parse().unwrap();
+                let dest_bot: usize = caps[2].parse().unwrap();
+                self.give_chip_to_bot(dest_bot, Chip::BotLow(src_bot)).map_err(too_many)?;
+            }
+            if let Some(caps) = RE_BOT_HIGH.captures(instr) {
+                let src_bot: usize = caps[1].parse().unwrap();
+                let dest_bot: usize = caps[2].parse().unwrap();
+                self.give_chip_to_bot(dest_bot, Chip::BotHigh(src_bot)).map_err(too_many)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Returns the bot's ID as the error if it already has two chips
+    fn give_chip_to_bot(&mut self, bot_id: usize, chip: Chip) -> Result<(), usize> {
+        if bot_id >= self.chip0.len() {
+            self.chip0.resize(bot_id + 1, Chip::Unassigned);
+            self.chip1.resize(bot_id + 1, Chip::Unassigned);
+        }
+
+        let chip0 = &mut self.chip0[bot_id];
+        let chip1 = &mut self.chip1[bot_id];
+
+        if *chip0 == Chip::Unassigned {
+            *chip0 = chip;
+        } else if *chip1 == Chip::Unassigned {
+            *chip1 = chip;
+        } else {
+            return Err(bot_id);
+        }
+        Ok(())
+    }
+
+    // The value of one of a bot's chips, or None if the bot never gets that chip, or the chip
+    // would have to come from the bot itself
+    fn get_chip_val(&mut self, bot_id: usize, chip0: bool) -> Option<usize> {
+        let slot = if chip0 {
+            self.chip0.get_mut(bot_id)?
+        } else {
+            self.chip1.get_mut(bot_id)?
+        };
+        // unassigned until it's worked out, so a loop in the wiring comes back to nothing
+        let chip = std::mem::replace(slot, Chip::Unassigned);
+
+        let chip_val = match chip {
+            Chip::Val(new_val) => new_val,
+            Chip::BotLow(low_bot) => {
+                std::cmp::min(self.get_chip_val(low_bot, false)?,
+                              self.get_chip_val(low_bot, true)?)
+            }
+            Chip::BotHigh(high_bot) => {
+                std::cmp::max(self.get_chip_val(high_bot, false)?,
+                              self.get_chip_val(high_bot, true)?)
+            }
+            Chip::Unassigned => return None,
+        };
+
+        // put the chip back as a value, which also saves working it out again
+        if chip0 {
+            self.chip0[bot_id] = Chip::Val(chip_val);
+        } else {
+            self.chip1[bot_id] = Chip::Val(chip_val);
+        }
+
+        Some(chip_val)
+    }
+
+    // find the bot that compares val0 and val1. Bots that never get two chips don't compare
+    // anything.
+    fn find_comparison(&mut self, val0: usize, val1: usize) -> Option<usize> {
+        for bot_id in 0..self.chip0.len() {
+            let chip_val0 = self.get_chip_val(bot_id, true);
+            let chip_val1 = self.get_chip_val(bot_id, false);
+            if let (Some(chip_val0), Some(chip_val1)) = (chip_val0, chip_val1) {
+                if chip_val0 == val0 && chip_val1 == val1 || (chip_val0 == val1 && chip_val1 == val0) {
+                    return Some(bot_id);
+                }
+            }
+        }
+
+        None
+    }
+
+    // ///////
+    // Part 2
+    // An output can't be given more than one chip
+    fn parse_instruction2(&mut self, instr: &str) -> Result<(), ParseError> {
+        lazy_static! {
+            static ref RE_OUT_LOW: Regex = Regex::new(r"^bot (\d+).+low to output (\d+)").unwrap();
+            static ref RE_OUT_HIGH: Regex = Regex::new(r"^bot (\d+).+high to output (\d+)").unwrap();
+        }
+        let taken = |output_id| {
+            ParseError::new(1, 1, instr, &format!("no more than one chip going to output {}", output_id))
+        };
+        if let Some(caps) = RE_OUT_LOW.captures(instr) {
+            let bot: usize = caps[1].parse().unwrap();
+            let output: usize = caps[2].parse().unwrap();
+            self.assign_chip_to_output(output, Chip::BotLow(bot)).map_err(taken)?;
+        }
+        if let Some(caps) = RE_OUT_HIGH.captures(instr) {
+            let bot: usize = caps[1].parse().unwrap();
+            let output: usize = caps[2].parse().unwrap();
+            self.assign_chip_to_output(output, Chip::BotHigh(bot)).map_err(taken)?;
+        }
+        Ok(())
+    }
+
+    // Returns the output's ID as the error if it already has a chip
+    fn assign_chip_to_output(&mut self, output_id: usize, chip: Chip) -> Result<(), usize> {
+        if output_id >= self.outputs.len() {
+            self.outputs.resize(output_id + 1, Chip::Unassigned);
+        }
+
+        let output_chip = &mut self.outputs[output_id];
+
+        if *output_chip == Chip::Unassigned {
+            *output_chip = chip;
+            Ok(())
+        } else {
+            Err(output_id)
+        }
+    }
+
+    // The value of the chip an output gets, or None if it doesn't get one
+    fn get_output_val(&mut self, output_id: usize) -> Option<usize> {
+        let chip = self.outputs.get(output_id)?.clone();
+
+        match chip {
+            Chip::BotLow(low_bot) => {
+                Some(std::cmp::min(self.get_chip_val(low_bot, false)?,
+                                   self.get_chip_val(low_bot, true)?))
+            }
+            Chip::BotHigh(high_bot) => {
+                Some(std::cmp::max(self.get_chip_val(high_bot, false)?,
+                                   self.get_chip_val(high_bot, true)?))
+            }
+            Chip::Val(new_val) => Some(new_val),
+            Chip::Unassigned => None,
+        }
+    }
+}
+
+// //////
+// Tests
+#[test]
+fn test_get_chip_val() {
+    let mut b = Factory::new();
+    b.give_chip_to_bot(3, Chip::Val(9)).unwrap();
+    b.give_chip_to_bot(3, Chip::Val(10)).unwrap();
+    b.give_chip_to_bot(1, Chip::BotHigh(3)).unwrap();
+    b.give_chip_to_bot(5, Chip::BotLow(3)).unwrap();
+
+    assert_eq!(Some(9), b.get_chip_val(3, true));
+    assert_eq!(Some(10), b.get_chip_val(3, false));
+    assert_eq!(Some(10), b.get_chip_val(1, true));
+    assert_eq!(Some(9), b.get_chip_val(5, true));
+}
+
+#[test]
+fn test_give_chip_to_bot() {
+    let mut b = Factory::new();
+    b.give_chip_to_bot(2, Chip::Val(5)).unwrap();
+    b.give_chip_to_bot(1, Chip::BotLow(2)).unwrap();
+    b.give_chip_to_bot(0, Chip::BotHigh(2)).unwrap();
+    b.give_chip_to_bot(1, Chip::Val(3)).unwrap();
+    b.give_chip_to_bot(0, Chip::BotHigh(1)).unwrap();
+    b.give_chip_to_bot(2, Chip::Val(2)).unwrap();
+
+    assert_eq!(Some(2), b.find_comparison(5, 2));
+    assert_eq!(Some(2), b.find_comparison(2, 5));
+}
+
+#[test]
+fn test_parse_instruction() {
+    let mut b = Factory::new();
+    b.parse_instruction("value 5 goes to bot 2").unwrap();
+    b.parse_instruction("bot 2 gives low to bot 1 and high to bot 0").unwrap();
+    b.parse_instruction("value 3 goes to bot 1").unwrap();
+    b.parse_instruction("bot 1 gives low to output 1 and high to bot 0").unwrap();
+    b.parse_instruction("bot 0 gives low to output 2 and high to output 0").unwrap();
+    b.parse_instruction("value 2 goes to bot 2").unwrap();
+
+    assert_eq!(Some(2), b.find_comparison(5, 2));
+    assert_eq!(Some(2), b.find_comparison(2, 5));
+}
+
+#[test]
+fn test_check_instruction() {
+    assert!(check_instruction("value 5 goes to bot 2").is_ok());
+    assert!(check_instruction("bot 1 gives low to output 1 and high to bot 0").is_ok());
+    assert_eq!(Err(ParseError::new(1, 1, "bot 1 gives low to bin 1", "a value or bot instruction")),
+               check_instruction("bot 1 gives low to bin 1"));
+}
+
+// part 2
+#[test]
+fn test_parse_instruction2() {
+    let mut b = Factory::new();
+    b.parse_instruction("value 5 goes to bot 2").unwrap();
+    b.parse_instruction("bot 2 gives low to bot 1 and high to bot 0").unwrap();
+    b.parse_instruction("value 3 goes to bot 1").unwrap();
+    b.parse_instruction("bot 1 gives low to output 1 and high to bot 0").unwrap();
+    b.parse_instruction("bot 0 gives low to output 2 and high to output 0").unwrap();
+    b.parse_instruction("value 2 goes to bot 2").unwrap();
+    b.parse_instruction2("value 5 goes to bot 2").unwrap();
+    b.parse_instruction2("bot 2 gives low to bot 1 and high to bot 0").unwrap();
+    b.parse_instruction2("value 3 goes to bot 1").unwrap();
+    b.parse_instruction2("bot 1 gives low to output 1 and high to bot 0").unwrap();
+    b.parse_instruction2("bot 0 gives low to output 2 and high to output 0").unwrap();
+    b.parse_instruction2("value 2 goes to bot 2").unwrap();
+
+    assert_eq!(Some(2), b.find_comparison(5, 2));
+    assert_eq!(Some(2), b.find_comparison(2, 5));
+    assert_eq!(Some(5), b.get_output_val(0));
+    assert_eq!(Some(2), b.get_output_val(1));
+    assert_eq!(Some(3), b.get_output_val(2));
+}
+
+#[test]
+fn test_bad_wiring() {
+    // a bot can only hold two chips, and an output one
+    let input = "value 1 goes to bot 0\nvalue 2 goes to bot 0\nvalue 3 goes to bot 0";
+    assert_eq!(Err(ParseError::new(3, 1, "value 3 goes to bot 0", "no more than two chips going to bot 0")),
+               Advent10::parse(input));
+    let input = "bot 0 gives low to output 0 and high to output 0";
+    let expected = "no more than one chip going to output 0";
+    assert_eq!(Err(ParseError::new(1, 1, input, expected)), Advent10::parse(input));
+
+    // bot 1 never gets a second chip, so output 2 never gets one
+    let factory = Advent10::parse("value 5 goes to bot 0\nvalue 2 goes to bot 0\nvalue 3 goes to bot 1\n\
+                                   bot 0 gives low to output 0 and high to output 1\n\
+                                   bot 1 gives low to output 2 and high to output 3")
+        .unwrap();
+    assert_eq!(None, Advent10::part2(&factory));
+    // bots handing chips round in a loop never get to compare them
+    let factory = Advent10::parse("value 5 goes to bot 0\n\
+                                   bot 0 gives low to bot 1 and high to output 0\n\
+                                   bot 1 gives low to bot 0 and high to output 1")
+        .unwrap();
+    assert_eq!(None, Advent10::part1(&factory));
+    assert_eq!(None, Advent10::part2(&factory));
+}
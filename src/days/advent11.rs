@@ -0,0 +1,252 @@
+// advent11.rs
+// radioisotope generators and microchips (elevator puzzle)
+
+use std::collections::HashMap;
+use regex::Regex;
+use search;
+use {ParseError, Solution, column_of};
+
+const FLOOR_NAMES: [&str; 4] = ["first", "second", "third", "fourth"];
+const TOP_FLOOR: u8 = 3;
+
+pub struct Advent11;
+
+impl Solution for Advent11 {
+    type Input = State;
+    // minimum elevator moves to bring everything to the top floor
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<State, ParseError> {
+        parse_floors(input)
+    }
+
+    fn part1(start: &State) -> Option<usize> {
+        find_min_moves(start)
+    }
+
+    fn part2(start: &State) -> Option<usize> {
+        find_min_moves(&start.with_extra_pairs())
+    }
+}
+
+// Every element has a generator and a microchip, and elements are interchangeable: swapping
+// the names of two elements doesn't change how many moves are left. So a state only records
+// which floors each generator/microchip pair is on, kept sorted so that states that only
+// differ by element names are equal and the search visits them once.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct State {
+    elevator: u8,
+    pairs: Vec<(u8, u8)>, // (generator floor, microchip floor)
+}
+
+impl State {
+    fn new(elevator: u8, mut pairs: Vec<(u8, u8)>) -> State {
+        pairs.sort();
+        State { elevator, pairs }
+    }
+
+    // A microchip is fried if it's on a floor with another generator and without its own
+    fn is_safe(&self) -> bool {
+        self.pairs
+            .iter()
+            .all(|&(generator, chip)| generator == chip || self.pairs.iter().all(|&(g, _)| g != chip))
+    }
+
+    fn is_done(&self) -> bool {
+        self.pairs.iter().all(|&(generator, chip)| generator == TOP_FLOOR && chip == TOP_FLOOR)
+    }
+
+    // All safe states reachable with one elevator move, carrying one or two items
+    fn next_states(&self) -> Vec<State> {
+        // (pair index, is microchip) for every item on the elevator's floor
+        let mut items = vec![];
+        for (i, &(generator, chip)) in self.pairs.iter().enumerate() {
+            if generator == self.elevator {
+                items.push((i, false));
+            }
+            if chip == self.elevator {
+                items.push((i, true));
+            }
+        }
+
+        let mut dests = vec![];
+        if self.elevator < TOP_FLOOR {
+            dests.push(self.elevator + 1);
+        }
+        // never worth carrying anything down to floors that are already empty
+        let below_empty = self.pairs.iter().all(|&(g, c)| g >= self.elevator && c >= self.elevator);
+        if self.elevator > 0 && !below_empty {
+            dests.push(self.elevator - 1);
+        }
+
+        let mut next = vec![];
+        for dest in dests {
+            for (i, &first) in items.iter().enumerate() {
+                let seconds = items[i + 1..].iter().map(Some);
+                for second in Some(None).into_iter().chain(seconds) {
+                    let mut pairs = self.pairs.clone();
+                    for &(pair, is_chip) in Some(&first).into_iter().chain(second) {
+                        if is_chip {
+                            pairs[pair].1 = dest;
+                        } else {
+                            pairs[pair].0 = dest;
+                        }
+                    }
+
+                    let state = State::new(dest, pairs);
+                    if state.is_safe() {
+                        next.push(state);
+                    }
+                }
+            }
+        }
+        next
+    }
+
+    // ///////
+    // Part 2
+    // An elerium pair and a dilithium pair turn up on the first floor
+    fn with_extra_pairs(&self) -> State {
+        let mut pairs = self.pairs.clone();
+        pairs.push((0, 0));
+        pairs.push((0, 0));
+        State::new(self.elevator, pairs)
+    }
+}
+
+fn parse_floors(input: &str) -> Result<State, ParseError> {
+    lazy_static! {
+        static ref RE_FLOOR: Regex = Regex::new(r"^The (\w+) floor contains (.+)\.$").unwrap();
+        static ref RE_SEPARATOR: Regex = Regex::new(r",? and |, ").unwrap();
+        static ref RE_ITEM: Regex =
+            Regex::new(r"^an? (\w+)(?: (generator)|-compatible microchip)$").unwrap();
+    }
+
+    // element name -> floor
+    let mut generators = HashMap::new();
+    let mut chips = HashMap::new();
+    let mut line_count = 0;
+
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        let line_number = i + 1;
+        line_count = line_number;
+
+        let floor_name = FLOOR_NAMES.get(i)
+            .ok_or_else(|| ParseError::new(line_number, 1, line, "end of input"))?;
+        let caps = RE_FLOOR.captures(line)
+            .ok_or_else(|| ParseError::new(line_number, 1, line, "The <floor> floor contains <items>."))?;
+        if &caps[1] != *floor_name {
+            let expected = format!("the {} floor", floor_name);
+            return Err(ParseError::new(line_number, column_of(line, &caps[1]), &caps[1], &expected));
+        }
+
+        let contents = caps.get(2).unwrap().as_str();
+        if contents == "nothing relevant" {
+            continue;
+        }
+        for item in RE_SEPARATOR.split(contents) {
+            let item_caps = RE_ITEM.captures(item)
+                .ok_or_else(|| {
+                    ParseError::new(line_number, column_of(line, item), item, "a generator or microchip")
+                })?;
+            let items = if item_caps.get(2).is_some() {
+                &mut generators
+            } else {
+                &mut chips
+            };
+            if items.insert(item_caps[1].to_string(), i as u8).is_some() {
+                return Err(ParseError::new(line_number,
+                                           column_of(line, item),
+                                           item,
+                                           "only one of each item"));
+            }
+        }
+    }
+    if line_count < FLOOR_NAMES.len() {
+        let expected = format!("the {} floor", FLOOR_NAMES[line_count]);
+        return Err(ParseError::new(line_count + 1, 1, "", &expected));
+    }
+
+    // pair each generator with its microchip
+    let mut pairs = vec![];
+    for (element, &generator) in &generators {
+        match chips.get(element) {
+            Some(&chip) => pairs.push((generator, chip)),
+            None => {
+                let line = generator as usize + 1;
+                return Err(ParseError::new(line, 1, element, "a microchip for every generator"));
+            }
+        }
+    }
+    if let Some((element, &chip)) = chips.iter().find(|&(element, _)| !generators.contains_key(element)) {
+        let line = chip as usize + 1;
+        return Err(ParseError::new(line, 1, element, "a generator for every microchip"));
+    }
+
+    Ok(State::new(0, pairs))
+}
+
+fn find_min_moves(start: &State) -> Option<usize> {
+    search::bfs(start.clone(), State::next_states, State::is_done).distance()
+}
+
+// //////
+// Tests
+#[cfg(test)]
+const EXAMPLE: &str = "\
+The first floor contains a hydrogen-compatible microchip and a lithium-compatible microchip.
+The second floor contains a hydrogen generator.
+The third floor contains a lithium generator.
+The fourth floor contains nothing relevant.";
+
+#[test]
+fn test_parse_floors() {
+    assert_eq!(Ok(State::new(0, vec![(1, 0), (2, 0)])), parse_floors(EXAMPLE));
+
+    let input = "\
+The first floor contains a thulium generator, a thulium-compatible microchip, and a plutonium generator.
+The second floor contains a plutonium-compatible microchip.
+The third floor contains nothing relevant.
+The fourth floor contains nothing relevant.";
+    assert_eq!(Ok(State::new(0, vec![(0, 0), (0, 1)])), parse_floors(input));
+}
+
+#[test]
+fn test_parse_floors_errors() {
+    let input = EXAMPLE.replace("lithium generator", "lithium reactor");
+    assert_eq!(Err(ParseError::new(3, 26, "a lithium reactor", "a generator or microchip")),
+               parse_floors(&input));
+
+    let input = EXAMPLE.replace("third", "fifth");
+    assert_eq!(Err(ParseError::new(3, 5, "fifth", "the third floor")), parse_floors(&input));
+
+    let input = EXAMPLE.replace("a lithium generator", "nothing relevant");
+    assert_eq!(Err(ParseError::new(1, 1, "lithium", "a generator for every microchip")),
+               parse_floors(&input));
+
+    let input: Vec<_> = EXAMPLE.lines().take(3).collect();
+    assert_eq!(Err(ParseError::new(4, 1, "", "the fourth floor")),
+               parse_floors(&input.join("\n")));
+}
+
+#[test]
+fn test_is_safe() {
+    assert!(State::new(0, vec![(1, 0), (2, 0)]).is_safe());
+    // a microchip is fine next to other generators while its own generator is with it
+    assert!(State::new(1, vec![(1, 1), (1, 0)]).is_safe());
+    // the second microchip is on floor 1 with the first generator but without its own
+    assert!(!State::new(1, vec![(1, 1), (2, 1)]).is_safe());
+}
+
+#[test]
+fn test_state_canonical() {
+    assert_eq!(State::new(0, vec![(2, 0), (1, 0)]), State::new(0, vec![(1, 0), (2, 0)]));
+}
+
+#[test]
+fn test_find_min_moves() {
+    let start = parse_floors(EXAMPLE).unwrap();
+    assert_eq!(Some(11), find_min_moves(&start));
+    assert_eq!(Some(0), find_min_moves(&State::new(3, vec![(3, 3)])));
+}
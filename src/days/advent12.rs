@@ -0,0 +1,101 @@
+// advent12.rs
+// assembly language
+
+use assembunny::{Instr, Registers, execute_to_halt, parse_program};
+use {ParseError, Solution};
+
+pub struct Advent12;
+
+impl Solution for Advent12 {
+    type Input = Vec<Instr>;
+    // value left in register a
+    type Answer = i32;
+
+    fn parse(input: &str) -> Result<Vec<Instr>, ParseError> {
+        parse_program(input)
+    }
+
+    fn part1(instructions: &Vec<Instr>) -> Option<i32> {
+        execute_to_halt(instructions, start_registers(1)).map(|program_state| program_state.registers[0])
+    }
+
+    fn part2(instructions: &Vec<Instr>) -> Option<i32> {
+        execute_to_halt(instructions, start_registers(2)).map(|program_state| program_state.registers[0])
+    }
+}
+
+// What the registers hold when each part starts
+pub fn start_registers(part: u32) -> Registers {
+    match part {
+        // register c starts as 1 in part 2
+        2 => [0, 0, 1, 0],
+        _ => [0; 4],
+    }
+}
+
+// //////
+// Tests
+#[cfg(test)]
+use assembunny::{ProgramState, parse_instr};
+
+#[test]
+fn test_parse_instruction() {
+    let mut program_state = ProgramState::new(vec![]);
+
+    program_state.execute_instr(parse_instr("cpy 41 a").unwrap());
+    assert_eq!(41, program_state.registers[0]);
+    program_state.execute_instr(parse_instr("cpy -37 d").unwrap());
+    assert_eq!(-37, program_state.registers[3]);
+    program_state.execute_instr(parse_instr("inc a").unwrap());
+    assert_eq!(42, program_state.registers[0]);
+    program_state.execute_instr(parse_instr("cpy a b").unwrap());
+    assert_eq!(42, program_state.registers[1]);
+    program_state.execute_instr(parse_instr("dec b").unwrap());
+    assert_eq!(41, program_state.registers[1]);
+    assert_eq!(5, program_state.program_counter);
+    program_state.execute_instr(parse_instr("jnz c 2").unwrap());
+    assert_eq!(6, program_state.program_counter);
+    program_state.execute_instr(parse_instr("jnz b 2").unwrap());
+    assert_eq!(8, program_state.program_counter);
+    program_state.execute_instr(parse_instr("jnz a -1").unwrap());
+    assert_eq!(7, program_state.program_counter);
+    program_state.execute_instr(parse_instr("jnz 0 -1").unwrap());
+    assert_eq!(8, program_state.program_counter);
+    program_state.execute_instr(parse_instr("jnz 1 -1").unwrap());
+    assert_eq!(7, program_state.program_counter);
+}
+
+#[test]
+fn test_parse_instr_errors() {
+    assert_eq!(Err(ParseError::new(1, 1, "mul a b", "an instruction")), parse_instr("mul a b"));
+    assert_eq!(Err(ParseError::new(1, 1, "inc e", "an instruction")), parse_instr("inc e"));
+    assert_eq!(Err(ParseError::new(1, 5, "99999999999", "a 32-bit integer")),
+               parse_instr("cpy 99999999999 a"));
+}
+
+#[test]
+fn test_execute_until_halt() {
+    let instructions = vec![
+        parse_instr("cpy 41 a").unwrap(),
+        parse_instr("inc a").unwrap(),
+        parse_instr("inc a").unwrap(),
+        parse_instr("dec a").unwrap(),
+        parse_instr("jnz a 2").unwrap(),
+        parse_instr("dec a").unwrap(),
+    ];
+
+    let program_state = execute_to_halt(&instructions, start_registers(1)).unwrap();
+    assert_eq!(42, program_state.registers[0]);
+    assert_eq!(6, program_state.program_counter);
+}
+
+#[test]
+fn test_execute_until_halt_never_halts() {
+    // a bad jump that goes round the same three instructions forever
+    let instructions = vec![
+        parse_instr("inc a").unwrap(),
+        parse_instr("dec a").unwrap(),
+        parse_instr("jnz 1 -2").unwrap(),
+    ];
+    assert_eq!(None, execute_to_halt(&instructions, start_registers(1)));
+}
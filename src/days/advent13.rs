@@ -0,0 +1,68 @@
+// advent13.rs
+// Office maze path searching
+
+use search;
+use {Grid, Location, ParseError, Solution, find_steps, parse_single_line};
+
+pub struct Advent13;
+
+impl Solution for Advent13 {
+    // office designer's favorite number
+    type Input = i32;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<i32, ParseError> {
+        let line = parse_single_line(input, "a favorite number")?;
+        line.parse().map_err(|_| ParseError::new(1, 1, line, "a favorite number"))
+    }
+
+    // min steps to 31, 39
+    fn part1(&favorite: &i32) -> Option<usize> {
+        find_steps((1, 1), (31, 39), |loc| is_wall(loc, favorite))
+    }
+
+    // max reachable locations in 50 steps
+    fn part2(&favorite: &i32) -> Option<usize> {
+        Some(count_reachable_locations((1, 1), 50, favorite))
+    }
+}
+
+// ///////
+// Part 1
+fn is_wall((x, y): Location, favorite: i32) -> bool {
+    !(x * x + 3 * x + 2 * x * y + y + y * y + favorite).count_ones().is_multiple_of(2)
+}
+
+// just for fun
+fn _print_office(width: usize, length: usize, favorite: i32) {
+    let office = Grid::from_fn(width, length, |loc| is_wall(loc, favorite));
+    println!("{}", office.render(|&wall| if wall { '#' } else { '.' }));
+}
+
+// ///////
+// Part 2
+// Count how many locations are reachable in a maximum number of steps
+fn count_reachable_locations(start: Location, max_steps: usize, favorite: i32) -> usize {
+    search::count_reachable(start, max_steps, |&(x, y)| {
+        vec![(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+            .into_iter()
+            .filter(move |&(x, y)| x >= 0 && y >= 0 && !is_wall((x, y), favorite))
+    })
+}
+
+// //////
+// Tests
+
+#[test]
+fn test_is_wall() {
+    assert!(!is_wall((0, 0), 10));
+    assert!(is_wall((1, 0), 10));
+    assert!(!is_wall((2, 0), 10));
+    assert!(is_wall((3, 0), 10));
+    assert!(is_wall((4, 0), 10));
+    assert!(is_wall((5, 0), 10));
+    assert!(!is_wall((0, 1), 10));
+    assert!(!is_wall((1, 1), 10));
+    assert!(is_wall((2, 1), 10));
+    assert!(!is_wall((3, 1), 10));
+}
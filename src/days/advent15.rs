@@ -0,0 +1,151 @@
+// advent15.rs
+// aligning discs
+
+use regex::Regex;
+use {ParseError, Solution, parse_lines};
+
+pub struct Advent15;
+
+impl Solution for Advent15 {
+    type Input = Vec<Disc>;
+    // drop time
+    type Answer = usize;
+
+    // assume discs come in order, because they do in my input
+    fn parse(input: &str) -> Result<Vec<Disc>, ParseError> {
+        parse_lines(input, Disc::from_description)
+    }
+
+    fn part1(discs: &Vec<Disc>) -> Option<usize> {
+        Some(calc_drop_time(discs))
+    }
+
+    fn part2(discs: &Vec<Disc>) -> Option<usize> {
+        let mut discs = discs.clone();
+        discs.push(Disc {
+            position: 0,
+            count: 11,
+        });
+        Some(calc_drop_time(&discs))
+    }
+}
+
+#[derive(Clone)]
+pub struct Disc {
+    position: usize,
+    count: usize,
+}
+
+impl Disc {
+    fn from_description(desc: &str) -> Result<Disc, ParseError> {
+        lazy_static! {
+            static ref RE_DESC: Regex =
+                Regex::new(r"^Disc #\d+ has (\d+) positions; at time=0, it is at position (\d+)\.$").unwrap();
+        }
+        let invalid = || {
+            ParseError::new(1,
+                            1,
+                            desc,
+                            "Disc #<n> has <n> positions; at time=0, it is at position <n>.")
+        };
+        let caps = RE_DESC.captures(desc).ok_or_else(invalid)?;
+        let count: usize = caps[1].parse().map_err(|_| invalid())?;
+        let position: usize = caps[2].parse().map_err(|_| invalid())?;
+        if count == 0 || position >= count {
+            return Err(invalid());
+        }
+
+        Ok(Disc {
+            position,
+            count,
+        })
+    }
+
+    fn incr(&mut self) {
+        self.incr_by(1);
+    }
+
+    fn incr_by(&mut self, incr: usize) {
+        self.position = (self.position + incr) % self.count;
+    }
+}
+
+// move discs to the positions they'll be in when the capsule arrives
+fn adjust_discs(discs: &mut [Disc]) {
+    for (i, disc) in discs.iter_mut().enumerate() {
+        disc.incr_by(i + 1);
+    }
+}
+
+fn are_discs_aligned(discs: &[Disc]) -> bool {
+    discs.iter().all(|disc| disc.position == 0)
+}
+
+fn calc_drop_time(discs: &[Disc]) -> usize {
+    let mut discs = Vec::from(discs);
+    adjust_discs(&mut discs);
+    let mut drop_time = 0;
+    while !are_discs_aligned(&discs) {
+        for disc in &mut discs {
+            disc.incr();
+        }
+        drop_time += 1;
+    }
+    drop_time
+}
+
+
+// //////
+// Tests
+#[test]
+fn test_disc_from_description() {
+    let disc = Disc::from_description("Disc #1 has 5 positions; at time=0, it is at position 4.")
+        .unwrap();
+    assert_eq!(4, disc.position);
+    assert_eq!(5, disc.count);
+    assert!(Disc::from_description("Disc #1 has 0 positions; at time=0, it is at position 0.")
+        .is_err());
+    assert!(Disc::from_description("Disc #1 has 5 positions").is_err());
+}
+
+#[test]
+fn test_disc_incr() {
+    let mut disc = Disc {
+        position: 4,
+        count: 5,
+    };
+    disc.incr();
+    assert_eq!(0, disc.position);
+    disc.incr_by(37);
+    assert_eq!(2, disc.position);
+}
+
+#[test]
+fn test_adjust_discs() {
+    let disc = Disc {
+        position: 0,
+        count: 3,
+    };
+    let mut discs = vec![];
+    for _ in 0..4 {
+        discs.push(disc.clone());
+    }
+    adjust_discs(&mut discs);
+    assert_eq!(1, discs[0].position);
+    assert_eq!(2, discs[1].position);
+    assert_eq!(0, discs[2].position);
+    assert_eq!(1, discs[3].position);
+}
+
+#[test]
+fn test_calc_drop_time() {
+    let discs = vec![Disc {
+                         position: 4,
+                         count: 5,
+                     },
+                     Disc {
+                         position: 1,
+                         count: 2,
+                     }];
+    assert_eq!(5, calc_drop_time(&discs));
+}
@@ -0,0 +1,206 @@
+// advent17.rs
+// Vault maze path searching
+
+use search;
+use {ParseError, Solution, parse_single_line};
+
+pub struct Advent17;
+
+impl Solution for Advent17 {
+    type Input = String;
+    // part 1 is the shortest path, part 2 the longest path length
+    type Answer = String;
+
+    fn parse(input: &str) -> Result<String, ParseError> {
+        parse_single_line(input, "a passcode").map(|passcode| passcode.to_string())
+    }
+
+    fn part1(passcode: &String) -> Option<String> {
+        find_path_to_goal(passcode)
+    }
+
+    fn part2(passcode: &String) -> Option<String> {
+        find_longest_path_to_goal(passcode).map(|len| len.to_string())
+    }
+}
+
+// ///////
+// Part 1
+
+// Calculate shortest path from 0,0 to 3,3 if such a path exists
+fn find_path_to_goal(passcode: &str) -> Option<String> {
+    let start = MazeState::from_passcode(passcode);
+    let result = search::bfs(start, MazeState::next_states, MazeState::is_vault);
+
+    // the path so far is part of every state, so the last state has the whole thing
+    result.found.map(|found| found.path.last().unwrap().path[passcode.len()..].to_string())
+}
+
+// The path is part of the state, so no state can be reached twice
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct MazeState {
+    x: u32,
+    y: u32,
+    path: String,
+}
+
+impl MazeState {
+    fn from_passcode(passcode: &str) -> MazeState {
+        MazeState {
+            x: 0,
+            y: 0,
+            path: passcode.to_string(),
+        }
+    }
+
+    fn is_open(&self, dir: Dir) -> bool {
+        let is_wall = match dir {
+            Dir::Up => self.y == 0,
+            Dir::Down => self.y == 3,
+            Dir::Left => self.x == 0,
+            Dir::Right => self.x == 3,
+        };
+        if is_wall {
+            false
+        } else {
+            let hash = *md5::compute(self.path.as_bytes());
+            let nibble = match dir {
+                Dir::Up => hash[0] >> 4,
+                Dir::Down => hash[0] & 0xf,
+                Dir::Left => hash[1] >> 4,
+                Dir::Right => hash[1] & 0xf,
+            };
+            nibble >= 0xb
+        }
+    }
+
+    fn is_vault(&self) -> bool {
+        self.x == 3 && self.y == 3
+    }
+
+    // the vault is a dead end, the path stops as soon as it gets there
+    fn next_states(&self) -> Vec<MazeState> {
+        if self.is_vault() {
+            return vec![];
+        }
+
+        Dir::iter()
+            .filter(|&&dir| self.is_open(dir))
+            .map(|&dir| {
+                let mut next = self.clone();
+                next.move_dir(dir);
+                next
+            })
+            .collect()
+    }
+
+    fn move_dir(&mut self, dir: Dir) {
+        match dir {
+            Dir::Up => {
+                self.y -= 1;
+            }
+            Dir::Down => {
+                self.y += 1;
+            }
+            Dir::Left => {
+                self.x -= 1;
+            }
+            Dir::Right => {
+                self.x += 1;
+            }
+        }
+        self.path.push(dir as u8 as char);
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Dir {
+    Up = 'U' as isize,
+    Down = 'D' as isize,
+    Left = 'L' as isize,
+    Right = 'R' as isize,
+}
+
+impl Dir {
+    pub fn iter() -> std::slice::Iter<'static, Dir> {
+        static DIRECTIONS: [Dir; 4] = [Dir::Up, Dir::Down, Dir::Left, Dir::Right];
+        DIRECTIONS.iter()
+    }
+}
+
+// ///////
+// Part 2
+// Walk every path, noting the longest one that ends in the vault. No state is reached twice, so
+// there's nothing to remember but the paths still to walk.
+fn find_longest_path_to_goal(passcode: &str) -> Option<usize> {
+    let mut states = vec![MazeState::from_passcode(passcode)];
+    let mut longest = None;
+
+    while let Some(state) = states.pop() {
+        if state.is_vault() {
+            longest = longest.max(Some(state.path.len() - passcode.len()));
+        }
+        states.extend(state.next_states());
+    }
+
+    longest
+}
+
+// //////
+// Tests
+
+#[test]
+fn test_is_open() {
+    let mut state = MazeState::from_passcode("hijkl");
+    assert!(!state.is_open(Dir::Up));
+    assert!(state.is_open(Dir::Down));
+    assert!(!state.is_open(Dir::Left));
+    assert!(!state.is_open(Dir::Right));
+
+    state.move_dir(Dir::Down);
+    assert!(state.is_open(Dir::Up));
+    assert!(!state.is_open(Dir::Down));
+    assert!(!state.is_open(Dir::Left));
+    assert!(state.is_open(Dir::Right));
+
+    let saved_state = state.clone();
+
+    state.move_dir(Dir::Right);
+    assert!(!state.is_open(Dir::Up));
+    assert!(!state.is_open(Dir::Down));
+    assert!(!state.is_open(Dir::Left));
+    assert!(!state.is_open(Dir::Right));
+
+    state = saved_state;
+    state.move_dir(Dir::Up);
+    assert!(!state.is_open(Dir::Up));
+    assert!(!state.is_open(Dir::Down));
+    assert!(!state.is_open(Dir::Left));
+    assert!(state.is_open(Dir::Right));
+
+    state.move_dir(Dir::Right);
+    assert!(!state.is_open(Dir::Up));
+    assert!(!state.is_open(Dir::Down));
+    assert!(!state.is_open(Dir::Left));
+    assert!(!state.is_open(Dir::Right));
+}
+
+#[test]
+fn test_find_path_to_goal() {
+    assert_eq!(None, find_path_to_goal("hijkl"));
+    assert_eq!("DDRRRD", find_path_to_goal("ihgpwlah").unwrap());
+    assert_eq!("DDUDRLRRUDRD",
+               find_path_to_goal("kglvqrro").unwrap());
+    assert_eq!("DRURDRUDDLLDLUURRDULRLDUUDDDRR",
+               find_path_to_goal("ulqzkmiv").unwrap());
+}
+
+// part 2
+#[test]
+#[ignore]
+fn test_find_longest_path_to_goal() {
+    assert_eq!(None, find_longest_path_to_goal("hijkl"));
+    assert_eq!(Some(370), find_longest_path_to_goal("ihgpwlah"));
+    assert_eq!(Some(492), find_longest_path_to_goal("kglvqrro"));
+    assert_eq!(Some(830), find_longest_path_to_goal("ulqzkmiv"));
+}
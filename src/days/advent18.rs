@@ -0,0 +1,94 @@
+// advent18.rs
+// Safe tiles vs traps
+
+use {Grid, ParseError, Solution, parse_single_line};
+
+pub struct Advent18;
+
+impl Solution for Advent18 {
+    // first row
+    type Input = String;
+    // safe tile count
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<String, ParseError> {
+        let line = parse_single_line(input, "a row of tiles")?;
+        match line.chars().enumerate().find(|&(_, c)| c != '.' && c != '^') {
+            Some((i, c)) => Err(ParseError::new(1, i + 1, &c.to_string(), "'.' or '^'")),
+            None => Ok(line.to_string()),
+        }
+    }
+
+    fn part1(first_row: &String) -> Option<usize> {
+        let mut trap_room = TrapRoom::create_from_str(first_row);
+        trap_room.grow(40);
+        Some(trap_room.count_safe_tiles())
+    }
+
+    fn part2(first_row: &String) -> Option<usize> {
+        let mut trap_room = TrapRoom::create_from_str(first_row);
+        trap_room.grow(400000);
+        Some(trap_room.count_safe_tiles())
+    }
+}
+
+// ///////
+// Part 1
+
+// each row has a safe tile added at both ends, so every real tile has two neighbours above it
+struct TrapRoom {
+    traps: Grid<bool>,
+}
+
+impl TrapRoom {
+    fn create_from_str(s: &str) -> TrapRoom {
+        let mut row = vec![false];
+        row.extend(s.chars()
+            .filter_map(|c| match c {
+                '.' => Some(false),
+                '^' => Some(true),
+                _ => None,
+            }));
+        row.push(false);
+        let mut traps = Grid::new(row.len(), 0, false);
+        traps.push_row(row);
+        TrapRoom { traps }
+    }
+
+    fn add_row(&mut self) {
+        let mut new_row = Vec::with_capacity(self.traps.width());
+        let old_row = self.traps.row(self.traps.height() - 1);
+        new_row.push(false);
+        new_row.extend(old_row.windows(3).map(|w| w[0] != w[2]));
+        new_row.push(false);
+        self.traps.push_row(new_row);
+    }
+
+    fn grow(&mut self, len: usize) {
+        while self.traps.height() < len {
+            self.add_row();
+        }
+    }
+
+    fn count_safe_tiles(&self) -> usize {
+        let row_len = self.traps.width() as i32;
+        self.traps
+            .iter()
+            .filter(|&((x, _), &trap)| x > 0 && x < row_len - 1 && !trap)
+            .count()
+    }
+}
+
+// //////
+// Tests
+
+#[test]
+fn test_count_safe_tiles() {
+    let mut room1 = TrapRoom::create_from_str("..^^.");
+    room1.grow(3);
+    assert_eq!(6, room1.count_safe_tiles());
+
+    let mut room2 = TrapRoom::create_from_str(".^^.^.^^^^");
+    room2.grow(10);
+    assert_eq!(38, room2.count_safe_tiles());
+}
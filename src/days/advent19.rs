@@ -0,0 +1,110 @@
+// advent19.rs
+// Elf present-stealing
+
+use {ParseError, Solution, parse_single_line};
+
+pub struct Advent19;
+
+impl Solution for Advent19 {
+    type Input = usize;
+    // the elf that got all the presents
+    type Answer = u32;
+
+    fn parse(input: &str) -> Result<usize, ParseError> {
+        let line = parse_single_line(input, "a number of elves")?;
+        match line.parse() {
+            Ok(elf_count) if elf_count > 0 => Ok(elf_count),
+            _ => Err(ParseError::new(1, 1, line, "a number of elves")),
+        }
+    }
+
+    fn part1(&elf_count: &usize) -> Option<u32> {
+        Some(find_last_elf(elf_count))
+    }
+
+    fn part2(&elf_count: &usize) -> Option<u32> {
+        Some(find_last_elf2(elf_count))
+    }
+}
+
+// ///////
+// Part 1
+
+fn find_last_elf(elf_count: usize) -> u32 {
+    let mut elves: Vec<_> = (1..elf_count as u32 + 1).collect();
+    loop {
+        elves.retain(|&elf_number| elf_number > 0);
+        let elf_count = elves.len();
+        if elf_count == 1 {
+            return elves[0];
+        }
+        #[allow(clippy::needless_range_loop)]
+        for thief in 0..elf_count {
+            let victim = (thief + 1) % elf_count;
+            if elves[thief] > 0 {
+                elves[victim] = 0;
+            }
+        }
+    }
+}
+
+// ///////
+// Part 2
+
+fn find_last_elf2(elf_count: usize) -> u32 {
+    let mut elves: Vec<_> = (1..elf_count as u32 + 1).collect();
+    let mut victim = elf_count / 2;
+    let mut victim_incr = if elf_count.is_multiple_of(2) { 1 } else { 2 };
+    loop {
+        let removed_below_victim = elves.iter().take(victim).filter(|&&x| x == 0).count();
+        elves.retain(|&x| x > 0);
+        let remaining = elves.len();
+        if remaining == 1 {
+            return elves[0];
+        }
+        victim = (victim - removed_below_victim) % remaining;
+        for _ in 0..remaining / 2 {
+            elves[victim] = 0;
+            victim = (victim + victim_incr) % remaining;
+            victim_incr = 3 - victim_incr;
+        }
+    }
+}
+
+// This version was really slow
+fn _find_last_elf2_slow(elf_count: usize) -> u32 {
+    let mut elves: Vec<_> = (1..elf_count as u32 + 1).collect();
+    let mut thief = 0;
+    for remaining in (2..elf_count + 1).rev() {
+        let victim = (thief + remaining / 2) % remaining;
+        elves.remove(victim);
+        thief = if victim > thief {
+            (thief + 1) % (remaining - 1)
+        } else {
+            thief % (remaining - 1)
+        };
+    }
+    assert_eq!(1, elves.len());
+    elves[0]
+}
+
+
+// //////
+// Tests
+
+#[test]
+fn test_find_last_elf() {
+    assert_eq!(3, find_last_elf(5));
+}
+
+#[test]
+fn test_find_last_elf2() {
+    assert_eq!(2, find_last_elf2(5));
+}
+
+#[test]
+fn test_find_last_elf2_opt() {
+    for i in 2..100 {
+        assert_eq!(_find_last_elf2_slow(i), find_last_elf2(i));
+    }
+}
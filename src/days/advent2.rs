@@ -0,0 +1,167 @@
+// advent2.rs
+// bathroom keypad decoding
+
+use {Grid, Location, ParseError, Solution, column_of, parse_lines};
+
+pub struct Advent2;
+
+impl Solution for Advent2 {
+    type Input = Vec<String>;
+    type Answer = String;
+
+    fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+        parse_lines(input, parse_line)
+    }
+
+    fn part1(lines: &Vec<String>) -> Option<String> {
+        Keypad::new(KEYPAD1).code('5', lines)
+    }
+
+    fn part2(lines: &Vec<String>) -> Option<String> {
+        Keypad::new(KEYPAD2).code('5', lines)
+    }
+}
+
+// The keypads, drawn as they look: a blank or * is a hole with no key
+const KEYPAD1: &str = "123
+456
+789";
+
+const KEYPAD2: &str = "**1**
+*234*
+56789
+*ABC*
+**D**";
+
+// a line of moves, each one U, D, L or R
+fn parse_line(line: &str) -> Result<String, ParseError> {
+    let moves = line.trim();
+    match moves.char_indices().find(|&(_, c)| !"UDLR".contains(c)) {
+        Some((i, c)) => {
+            let bad_move = &moves[i..i + c.len_utf8()];
+            Err(ParseError::new(1, column_of(line, bad_move), bad_move, "U, D, L or R"))
+        }
+        None => Ok(line.to_string()),
+    }
+}
+
+// A keypad of any shape, read from a character map with one row of keys per line. Rows can be
+// different lengths; anything past the end of a row is a hole.
+pub struct Keypad {
+    keys: Grid<Option<char>>,
+}
+
+impl Keypad {
+    pub fn new(map: &str) -> Keypad {
+        let rows: Vec<Vec<char>> = map.lines().map(|line| line.chars().collect()).collect();
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        Keypad {
+            keys: Grid::from_fn(width, rows.len(), |(x, y)| {
+                match rows[y as usize].get(x as usize) {
+                    Some(&' ') | Some(&'*') | None => None,
+                    Some(&key) => Some(key),
+                }
+            }),
+        }
+    }
+
+    // The key at pos, or None for a hole or somewhere off the keypad
+    fn key_at(&self, pos: Location) -> Option<char> {
+        self.keys.get(pos).cloned().and_then(|key| key)
+    }
+
+    fn find(&self, key: char) -> Option<Location> {
+        self.keys.iter().find(|&(_, &cell)| cell == Some(key)).map(|(pos, _)| pos)
+    }
+
+    // Follow a line of moves from pos. A move that would leave the keys is skipped, so a finger
+    // can't cross a hole.
+    fn follow(&self, pos: Location, moves: &str) -> Location {
+        moves.trim().chars().fold(pos, |(x, y), c| {
+            let next = match c {
+                'U' => (x, y - 1),
+                'D' => (x, y + 1),
+                'L' => (x - 1, y),
+                'R' => (x + 1, y),
+                _ => panic!("unexpected char in input"),
+            };
+            if self.key_at(next).is_some() { next } else { (x, y) }
+        })
+    }
+
+    // Start on the start key, and press a key at the end of each line of moves. Returns None if
+    // there's no start key.
+    pub fn code(&self, start: char, lines: &[String]) -> Option<String> {
+        let mut pos = self.find(start)?;
+        let mut code = String::new();
+
+        for line in lines {
+            pos = self.follow(pos, line);
+            code.push(self.key_at(pos)?);
+        }
+
+        Some(code)
+    }
+}
+
+// //////
+// Tests
+#[test]
+fn test_keypad() {
+    let keypad = Keypad::new(KEYPAD1);
+    assert_eq!(Some('1'), keypad.key_at((0, 0)));
+    assert_eq!(Some('6'), keypad.key_at((2, 1)));
+    assert_eq!(Some('8'), keypad.key_at((1, 2)));
+    assert_eq!(None, keypad.key_at((3, 0)));
+    assert_eq!(None, keypad.key_at((0, -1)));
+    assert_eq!(Some((1, 1)), keypad.find('5'));
+    assert_eq!(None, keypad.find('A'));
+}
+
+#[test]
+fn test_parse_line() {
+    assert_eq!(Ok("ULL".to_string()), parse_line("ULL"));
+    assert_eq!(Err(ParseError::new(1, 3, "X", "U, D, L or R")), parse_line("UUXD"));
+}
+
+#[test]
+fn test_follow() {
+    let keypad = Keypad::new(KEYPAD1);
+    assert_eq!((0, 0), keypad.follow((1, 1), "ULL"));
+    assert_eq!((2, 2), keypad.follow((0, 0), "RRDDD"));
+    assert_eq!((1, 2), keypad.follow((2, 2), "LURDL"));
+    assert_eq!((1, 1), keypad.follow((1, 2), "UUUUD"));
+}
+
+#[test]
+fn test_code() {
+    let lines: Vec<_> = ["ULL", "RRDDD", "LURDL", "UUUUD"].iter().map(|line| line.to_string()).collect();
+    assert_eq!(Some("1985".to_string()), Keypad::new(KEYPAD1).code('5', &lines));
+    assert_eq!(Some("5DB3".to_string()), Keypad::new(KEYPAD2).code('5', &lines));
+    assert_eq!(None, Keypad::new(KEYPAD1).code('0', &lines));
+}
+
+// part 2
+#[test]
+fn test_follow_diamond() {
+    let keypad = Keypad::new(KEYPAD2);
+    assert_eq!((0, 2), keypad.follow((0, 2), "ULL"));
+    assert_eq!((2, 4), keypad.follow((0, 2), "RRDDD"));
+    assert_eq!((2, 3), keypad.follow((2, 4), "LURDL"));
+    assert_eq!((2, 1), keypad.follow((2, 3), "UUUUD"));
+    assert_eq!(Some('5'), keypad.key_at((0, 2)));
+    assert_eq!(None, keypad.key_at((0, 0)));
+}
+
+#[test]
+fn test_keypad_with_gaps() {
+    // a U shape with short rows, which can only be crossed along the bottom
+    let keypad = Keypad::new("1 2\n3 4\n567\n\n8");
+    assert_eq!(Some('2'), keypad.key_at((2, 0)));
+    assert_eq!(None, keypad.key_at((1, 1)));
+    assert_eq!(None, keypad.key_at((2, 4)));
+    assert_eq!((0, 0), keypad.follow((0, 0), "RRR"));
+    assert_eq!((2, 0), keypad.follow((0, 0), "DRRDDRRUUUU"));
+    // the blank line leaves 8 cut off
+    assert_eq!((0, 4), keypad.follow((0, 4), "UUR"));
+}
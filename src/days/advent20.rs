@@ -0,0 +1,138 @@
+// advent20.rs
+// find ip not in range
+
+use {ParseError, Solution, column_of, parse_lines};
+
+pub struct Advent20;
+
+impl Solution for Advent20 {
+    // blocked IP ranges, sorted by start of range
+    type Input = Vec<(u32, u32)>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Vec<(u32, u32)>, ParseError> {
+        let mut blacklist = parse_lines(input, parse_range)?;
+        blacklist.sort_by_key(|x| x.0);
+
+        Ok(blacklist)
+    }
+
+    // first available IP
+    fn part1(blacklist: &Vec<(u32, u32)>) -> Option<usize> {
+        find_first_unblocked_ip(blacklist).map(|ip| ip as usize)
+    }
+
+    // total unblocked IPs
+    fn part2(blacklist: &Vec<(u32, u32)>) -> Option<usize> {
+        Some(find_total_unblocked_ips(blacklist))
+    }
+}
+
+// "low-high", both inclusive
+fn parse_range(line: &str) -> Result<(u32, u32), ParseError> {
+    let line = line.trim();
+    let mut ends = line.splitn(2, '-');
+    let low = ends.next().unwrap();
+    let high = ends.next()
+        .ok_or_else(|| ParseError::new(1, 1, line, "an IP range like 5-8"))?;
+
+    let parse_ip = |ip: &str| {
+        ip.parse::<u32>().map_err(|_| ParseError::new(1, column_of(line, ip), ip, "an IP address"))
+    };
+    let (low, high) = (parse_ip(low)?, parse_ip(high)?);
+    if low > high {
+        return Err(ParseError::new(1, 1, line, "a range whose start isn't after its end"));
+    }
+
+    Ok((low, high))
+}
+
+// ///////
+// Part 1
+
+#[cfg(test)]
+const MAX_IP: u32 = 10;
+
+#[cfg(not(test))]
+const MAX_IP: u32 = u32::MAX;
+
+fn find_first_unblocked_ip(blacklist: &[(u32, u32)]) -> Option<u32> {
+    if let Some(&(lowest, _)) = blacklist.first() {
+        if lowest > 0 {
+            return Some(0);
+        }
+    }
+
+    let mut highest = 0;
+    for &(low, high) in blacklist {
+        if low > highest + 1 {
+            return Some(highest + 1);
+        }
+        highest = std::cmp::max(highest, high);
+        if highest == MAX_IP {
+            break;
+        }
+    }
+
+    if highest < MAX_IP {
+        Some(highest + 1)
+    } else {
+        None
+    }
+}
+
+// ///////
+// Part 2
+
+fn find_total_unblocked_ips(blacklist: &[(u32, u32)]) -> usize {
+    let mut total_available = 0;
+
+    if let Some(&(lowest, _)) = blacklist.first() {
+        if lowest > 0 {
+            total_available = 1;
+        }
+    }
+
+    let mut highest = 0;
+    for &(low, high) in blacklist {
+        if highest < MAX_IP {
+            total_available += (highest + 1..low).count();
+        }
+        highest = std::cmp::max(highest, high);
+        if highest == MAX_IP {
+            break;
+        }
+    }
+
+    if highest < MAX_IP {
+        total_available + (highest + 1..MAX_IP).count()
+    } else {
+        total_available
+    }
+}
+
+
+
+
+// //////
+// Tests
+#[test]
+fn test_parse_range() {
+    assert_eq!(Ok((5, 8)), parse_range("5-8"));
+    assert_eq!(Err(ParseError::new(1, 3, "x", "an IP address")), parse_range("5-x"));
+    assert_eq!(Err(ParseError::new(1, 1, "58", "an IP range like 5-8")), parse_range("58"));
+    assert_eq!(Err(ParseError::new(1, 1, "8-5", "a range whose start isn't after its end")),
+               parse_range("8-5"));
+}
+
+#[test]
+fn test_find_first_unblocked_ip() {
+    let blacklist = vec![(0, 2), (4, 7), (5, 8)];
+    assert_eq!(Some(3), find_first_unblocked_ip(&blacklist));
+}
+
+#[test]
+fn test_find_total_unblocked_ips() {
+    let blacklist = vec![(0, 2), (4, 7), (5, 8)];
+    assert_eq!(2, find_total_unblocked_ips(&blacklist));
+}
@@ -0,0 +1,299 @@
+// advent21.rs
+// password scrambling
+
+use std::collections::VecDeque;
+use regex::Regex;
+use {ParseError, Solution, parse_lines};
+
+pub struct Advent21;
+
+impl Solution for Advent21 {
+    type Input = Vec<Instr>;
+    type Answer = String;
+
+    fn parse(input: &str) -> Result<Vec<Instr>, ParseError> {
+        parse_lines(input, parse_instruction)
+    }
+
+    fn part1(instructions: &Vec<Instr>) -> Option<String> {
+        Some(scramble_password(PASSWORD, instructions))
+    }
+
+    fn part2(instructions: &Vec<Instr>) -> Option<String> {
+        Some(unscramble_password(SCRAMBLED, instructions))
+    }
+}
+
+// Both passwords have the same letters, and instructions are checked against them
+const PASSWORD: &str = "abcdefgh";
+const SCRAMBLED: &str = "fbgdceah";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Instr {
+    SwapPos(usize, usize),
+    SwapLetter(u8, u8),
+    RotateLeft(usize),
+    RotateRight(usize),
+    RotateLetter(u8),
+    Reverse(usize, usize), // from the first position through the second
+    Move(usize, usize),
+}
+
+lazy_static! {
+    static ref RE_SWAP_POS: Regex = Regex::new(r"^swap position (\d+) with position (\d+)$").unwrap();
+    static ref RE_SWAP_LETTER: Regex = Regex::new(r"^swap letter ([a-z]) with letter ([a-z])$").unwrap();
+    static ref RE_ROT_LSTEP: Regex = Regex::new(r"^rotate left (\d+) steps?$").unwrap();
+    static ref RE_ROT_RSTEP: Regex = Regex::new(r"^rotate right (\d+) steps?$").unwrap();
+    static ref RE_ROT_LETTER: Regex = Regex::new(r"^rotate based on position of letter ([a-z])$").unwrap();
+    static ref RE_REV: Regex = Regex::new(r"^reverse positions (\d+) through (\d+)$").unwrap();
+    static ref RE_MOVE: Regex = Regex::new(r"^move position (\d+) to position (\d+)$").unwrap();
+}
+
+// An instruction, with every position inside the password and every letter in it
+fn parse_instruction(line: &str) -> Result<Instr, ParseError> {
+    let len = PASSWORD.len();
+    let position = |m: Option<regex::Match>, from: usize| {
+        let m = m.unwrap();
+        match m.as_str().parse() {
+            Ok(pos) if pos >= from && pos < len => Ok(pos),
+            _ => {
+                let expected = format!("a position from {} to {}", from, len - 1);
+                Err(ParseError::new(1, m.start() + 1, m.as_str(), &expected))
+            }
+        }
+    };
+    let letter = |m: Option<regex::Match>| {
+        let m = m.unwrap();
+        if PASSWORD.contains(m.as_str()) {
+            Ok(m.as_str().as_bytes()[0])
+        } else {
+            let expected = format!("a letter in {}", PASSWORD);
+            Err(ParseError::new(1, m.start() + 1, m.as_str(), &expected))
+        }
+    };
+    // rotations can be any size
+    let count = |m: Option<regex::Match>| {
+        let m = m.unwrap();
+        m.as_str().parse().map_err(|_| ParseError::new(1, m.start() + 1, m.as_str(), "a step count"))
+    };
+
+    if let Some(caps) = RE_SWAP_POS.captures(line) {
+        Ok(Instr::SwapPos(position(caps.get(1), 0)?, position(caps.get(2), 0)?))
+    } else if let Some(caps) = RE_SWAP_LETTER.captures(line) {
+        Ok(Instr::SwapLetter(letter(caps.get(1))?, letter(caps.get(2))?))
+    } else if let Some(caps) = RE_ROT_LSTEP.captures(line) {
+        Ok(Instr::RotateLeft(count(caps.get(1))?))
+    } else if let Some(caps) = RE_ROT_RSTEP.captures(line) {
+        Ok(Instr::RotateRight(count(caps.get(1))?))
+    } else if let Some(caps) = RE_ROT_LETTER.captures(line) {
+        Ok(Instr::RotateLetter(letter(caps.get(1))?))
+    } else if let Some(caps) = RE_REV.captures(line) {
+        let from = position(caps.get(1), 0)?;
+        Ok(Instr::Reverse(from, position(caps.get(2), from)?))
+    } else if let Some(caps) = RE_MOVE.captures(line) {
+        Ok(Instr::Move(position(caps.get(1), 0)?, position(caps.get(2), 0)?))
+    } else {
+        Err(ParseError::new(1, 1, line, "a swap, rotate, reverse or move instruction"))
+    }
+}
+
+// part 1
+fn scramble_password(password: &str, instructions: &[Instr]) -> String {
+    parse_instructions(password, instructions, true)
+}
+
+// part 2
+fn unscramble_password(password: &str, instructions: &[Instr]) -> String {
+    parse_instructions(password, instructions, false)
+}
+
+// part 1 and 2 combined. The password must be long enough for the instructions' positions and
+// have all their letters, as the puzzle's passwords do.
+fn parse_instructions(password: &str, instructions: &[Instr], scramble: bool) -> String {
+    let mut letters: VecDeque<u8> = password.as_bytes().iter().cloned().collect();
+
+    // create reverse rotation by letter mapping for unscrambling (only for part 2)
+    // Maps x to Some(y) iff there's an unique right rotation count y that could have landed at x
+    let mut rot_by_letter = vec![];
+    if !scramble {
+        let len = password.len();
+        let mut pairs = vec![];
+        for from in 0..len {
+            let rcount = 1 + from + if from >= 4 { 1 } else { 0 };
+            let to = (from + rcount) % len;
+            let lcount = (len + from - to) % len;
+            pairs.push((to, lcount));
+        }
+        rot_by_letter.resize(len, None);
+        let mut present = std::collections::HashSet::new();
+        for (to, count) in pairs {
+            if present.contains(&to) {
+                rot_by_letter[to] = None;
+            } else {
+                rot_by_letter[to] = Some(count);
+                present.insert(to);
+            }
+        }
+    }
+
+    // It seems ugly to box the iterator like this but I can't think of another way to select
+    // between iterator types below.
+    let iter: Box<dyn Iterator<Item = &Instr>> = if scramble {
+        Box::new(instructions.iter())
+    } else {
+        Box::new(instructions.iter().rev())
+    };
+    let find = |letters: &VecDeque<u8>, letter| {
+        letters.iter().position(|&x| x == letter).expect("letter not in password")
+    };
+
+    for &instr in iter {
+        match instr {
+            Instr::SwapPos(pos1, pos2) => letters.swap(pos1, pos2),
+            Instr::SwapLetter(letter1, letter2) => {
+                let pos1 = find(&letters, letter1);
+                let pos2 = find(&letters, letter2);
+                letters.swap(pos1, pos2);
+            }
+            Instr::Reverse(pos1, pos2) => {
+                let dist = (pos2 - pos1).div_ceil(2);
+                for i in 0..dist {
+                    letters.swap(pos1 + i, pos2 - i);
+                }
+            }
+            Instr::RotateLeft(count) => {
+                let dir = if scramble {
+                    Rotate::Left
+                } else {
+                    Rotate::Right
+                };
+                rotate_letters(&mut letters, count, dir);
+            }
+            Instr::RotateRight(count) => {
+                let dir = if scramble {
+                    Rotate::Right
+                } else {
+                    Rotate::Left
+                };
+                rotate_letters(&mut letters, count, dir);
+            }
+            Instr::Move(pos1, pos2) => {
+                // not a very efficient way to do this
+                if scramble {
+                    let c = letters.remove(pos1).unwrap();
+                    letters.insert(pos2, c);
+                } else {
+                    let c = letters.remove(pos2).unwrap();
+                    letters.insert(pos1, c);
+                }
+            }
+            Instr::RotateLetter(letter) => {
+                let pos = find(&letters, letter);
+                let count = if scramble {
+                    1 + pos + if pos >= 4 { 1 } else { 0 }
+                } else {
+                    rot_by_letter[pos].expect("no unique rotation for letter")
+                };
+                rotate_letters(&mut letters, count, Rotate::Right);
+            }
+        }
+    }
+
+    String::from_utf8(letters.into_iter().collect()).unwrap()
+}
+
+enum Rotate {
+    Right,
+    Left,
+}
+
+fn rotate_letters(letters: &mut VecDeque<u8>, count: usize, dir: Rotate) {
+    let count = count % letters.len();
+    match dir {
+        Rotate::Right => {
+            for _ in 0..count {
+                let c = letters.pop_back().unwrap();
+                letters.push_front(c);
+            }
+        }
+        Rotate::Left => {
+            for _ in 0..count {
+                let c = letters.pop_front().unwrap();
+                letters.push_back(c);
+            }
+        }
+    }
+}
+
+// //////
+// Tests
+#[test]
+fn test_parse_instruction() {
+    assert_eq!(Ok(Instr::RotateLeft(1)), parse_instruction("rotate left 1 step"));
+    assert_eq!(Ok(Instr::RotateRight(30)), parse_instruction("rotate right 30 steps"));
+    assert_eq!(Ok(Instr::SwapLetter(b'd', b'b')), parse_instruction("swap letter d with letter b"));
+    assert_eq!(Ok(Instr::Reverse(3, 3)), parse_instruction("reverse positions 3 through 3"));
+    assert_eq!(Err(ParseError::new(1,
+                                   1,
+                                   "shuffle everything",
+                                   "a swap, rotate, reverse or move instruction")),
+               parse_instruction("shuffle everything"));
+    // well-formed, but impossible with an 8-letter password
+    assert_eq!(Err(ParseError::new(1, 15, "9", "a position from 0 to 7")),
+               parse_instruction("swap position 9 with position 2"));
+    assert_eq!(Err(ParseError::new(1, 13, "z", "a letter in abcdefgh")),
+               parse_instruction("swap letter z with letter b"));
+    assert_eq!(Err(ParseError::new(1, 36, "x", "a letter in abcdefgh")),
+               parse_instruction("rotate based on position of letter x"));
+    assert_eq!(Err(ParseError::new(1, 29, "1", "a position from 3 to 7")),
+               parse_instruction("reverse positions 3 through 1"));
+    assert_eq!(Err(ParseError::new(1, 29, "8", "a position from 0 to 7")),
+               parse_instruction("move position 1 to position 8"));
+}
+
+#[test]
+fn test_scramble_password() {
+    let mut instructions = Vec::new();
+
+    // example from the webpage
+    instructions.push(parse_instruction("swap position 4 with position 0").unwrap());
+    assert_eq!("ebcda", scramble_password("abcde", &instructions));
+    instructions.push(parse_instruction("swap letter d with letter b").unwrap());
+    assert_eq!("edcba", scramble_password("abcde", &instructions));
+    instructions.push(parse_instruction("reverse positions 0 through 4").unwrap());
+    assert_eq!("abcde", scramble_password("abcde", &instructions));
+    instructions.push(parse_instruction("rotate left 1 step").unwrap());
+    assert_eq!("bcdea", scramble_password("abcde", &instructions));
+    instructions.push(parse_instruction("move position 1 to position 4").unwrap());
+    assert_eq!("bdeac", scramble_password("abcde", &instructions));
+    instructions.push(parse_instruction("move position 3 to position 0").unwrap());
+    assert_eq!("abdec", scramble_password("abcde", &instructions));
+    instructions.push(parse_instruction("rotate based on position of letter b").unwrap());
+    assert_eq!("ecabd", scramble_password("abcde", &instructions));
+    instructions.push(parse_instruction("rotate based on position of letter d").unwrap());
+    assert_eq!("decab", scramble_password("abcde", &instructions));
+
+    // additional tests
+    instructions.push(parse_instruction("rotate right 3 steps").unwrap());
+    assert_eq!("cabde", scramble_password("abcde", &instructions));
+    instructions.push(parse_instruction("reverse positions 0 through 1").unwrap());
+    assert_eq!("acbde", scramble_password("abcde", &instructions));
+}
+
+#[test]
+fn test_unscramble_password() {
+    let instructions = vec![
+        // example from the webpage
+        parse_instruction("swap position 4 with position 0").unwrap(),
+        parse_instruction("swap letter d with letter b").unwrap(),
+        parse_instruction("reverse positions 0 through 4").unwrap(),
+        parse_instruction("rotate left 1 step").unwrap(),
+        parse_instruction("move position 1 to position 4").unwrap(),
+        parse_instruction("move position 3 to position 0").unwrap(),
+        parse_instruction("rotate based on position of letter b").unwrap(),
+    ];
+    assert_eq!("abcde", unscramble_password("ecabd", &instructions));
+
+    // Note: No unambiguous way to reverse rotate on position of d!
+    // instructions.push(parse_instruction("rotate based on position of letter d").unwrap());
+}
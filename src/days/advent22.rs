@@ -0,0 +1,267 @@
+// advent22.rs
+// disk space (sliding blocks puzzle)
+
+use std::collections::HashSet;
+use std::cmp::max;
+use regex::Regex;
+use {Grid, Location, ParseError, Solution, find_steps, parse_lines};
+
+pub struct Advent22;
+
+impl Solution for Advent22 {
+    type Input = Vec<Node>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Vec<Node>, ParseError> {
+        let nodes = parse_lines(input, |line| {
+            // skip the df command and its column headers
+            if line.starts_with("root@") || line.starts_with("Filesystem") {
+                Ok(None)
+            } else {
+                node_from_str(line).map(Some)
+            }
+        })?;
+
+        Ok(nodes.into_iter().flatten().collect())
+    }
+
+    // number of viable pairs
+    fn part1(nodes: &Vec<Node>) -> Option<usize> {
+        Some(find_viable_pairs(nodes).len())
+    }
+
+    // steps to goal
+    fn part2(nodes: &Vec<Node>) -> Option<usize> {
+        calc_fewest_steps_to_goal(nodes)
+    }
+}
+
+#[derive(PartialEq)]
+pub struct Node {
+    loc: Location,
+    used: usize,
+    avail: usize,
+}
+
+fn is_viable_pair((n1, n2): (&Node, &Node)) -> bool {
+    n1.used <= n2.avail && n1 != n2 && n1.used != 0
+}
+
+fn find_viable_pairs(nodes: &[Node]) -> Vec<(&Node, &Node)> {
+    let mut viable = vec![];
+    for n1 in nodes {
+        viable.extend(nodes.iter().map(|n2| (n1, n2)).filter(|&x| is_viable_pair(x)))
+    }
+    viable
+}
+
+fn node_from_str(s: &str) -> Result<Node, ParseError> {
+    lazy_static! {
+        static ref RE_NODE: Regex =
+            Regex::new(r"^/dev/grid/node-x([\d]+)-y([\d]+)\s+[\d]+T\s+([\d]+)T\s+([\d]+)T\s+[\d]+%\s*$").unwrap();
+    }
+    let invalid = || ParseError::new(1, 1, s, "/dev/grid/node-x<n>-y<n> <size>T <used>T <avail>T <use>%");
+
+    let caps = RE_NODE.captures(s).ok_or_else(invalid)?;
+    let x = caps[1].parse().map_err(|_| invalid())?;
+    let y = caps[2].parse().map_err(|_| invalid())?;
+    let used = caps[3].parse().map_err(|_| invalid())?;
+    let avail = caps[4].parse().map_err(|_| invalid())?;
+    Ok(Node {
+        loc: (x, y),
+        used,
+        avail,
+    })
+}
+
+// ///////
+// Part 2
+
+// this function can only find an answer if these conditions are met:
+// 1. Exactly 1 empty node
+// 2. All other nodes either can't be moved at all, or can only be moved to the empty node
+// 3. All nodes in the first 2 rows are moveable
+//
+// Returns None if we can't find an answer
+fn calc_fewest_steps_to_goal(nodes: &[Node]) -> Option<usize> {
+    let mut empties = Vec::new();
+    let mut moveable = HashSet::new();
+    let mut multiple_pairs = HashSet::new();
+
+    for n1 in nodes {
+        if n1.used == 0 {
+            empties.push(n1.loc);
+        }
+        for (n1, n2) in nodes.iter().map(|n2| (n1, n2)).filter(|&x| is_viable_pair(x)) {
+            if !moveable.insert(n1.loc) {
+                multiple_pairs.insert(n1.loc);
+            }
+            if !moveable.insert(n2.loc) {
+                multiple_pairs.insert(n2.loc);
+            }
+        }
+    }
+
+    // return None if our assumptions are violated
+    if empties.len() != 1 || multiple_pairs.len() != 1 ||
+       empties.first() != multiple_pairs.iter().next() {
+        // the algorithm is not good enough to handle this :(
+        return None;
+    }
+    let empty_node = empties[0];
+
+    // find grid dimensions
+    let (x_max, y_max) = nodes.iter().fold((0, 0), |(x, y), n| (max(x, n.loc.0), max(y, n.loc.1)));
+    let width = x_max + 1;
+    let height = y_max + 1;
+    if width == 1 {
+        // goal is already at 0, 0
+        return Some(0);
+    }
+
+    // build static grid (true if it's a wall, false if it isn't)
+    let mut grid = Grid::new(width as usize, height as usize, true);
+    for loc in moveable {
+        grid.set(loc, false);
+    }
+
+    // algorithm might fail if there are unmoveable nodes in the first 2 rows
+    if (0..2.min(grid.height())).any(|y| grid.row(y).iter().any(|&x| x)) {
+        return None;
+    }
+
+    // move empty space just to the left of the goal node
+    let dist = find_steps(empty_node,
+                          (width - 2, 0),
+                          |loc| grid.get(loc) != Some(&false));
+
+    // simple calculation to determine how many more steps
+    let steps = (5 * width - 9) as usize;
+
+    dist.map(|x| x + steps)
+}
+
+// //////
+// Tests
+#[test]
+fn test_parse() {
+    let input = "root@ebhq-gridcenter# df -h
+Filesystem              Size  Used  Avail  Use%
+/dev/grid/node-x0-y0     10T    8T     2T   80%
+/dev/grid/node-x1-y2     11T    6T     5T   54%";
+    let nodes = Advent22::parse(input).ok().unwrap();
+    assert_eq!(2, nodes.len());
+    assert_eq!((1, 2), nodes[1].loc);
+    assert_eq!(6, nodes[1].used);
+    assert_eq!(5, nodes[1].avail);
+
+    let err = Advent22::parse("/dev/grid/node-x0-y0     10T    8T     2T   80%\n/dev/grid/node-x1")
+        .err()
+        .unwrap();
+    assert_eq!(2, err.line);
+}
+
+#[test]
+fn test_is_viable_pair() {
+    let node_0_10 = Node {
+        loc: (0, 0),
+        used: 0,
+        avail: 10,
+    };
+    let node_10_10 = Node {
+        loc: (0, 1),
+        used: 10,
+        avail: 10,
+    };
+    let node_11_10 = Node {
+        loc: (1, 0),
+        used: 11,
+        avail: 10,
+    };
+
+    // false because first node empty
+    assert!(!is_viable_pair((&node_0_10, &node_10_10)));
+    assert!(!is_viable_pair((&node_0_10, &node_11_10)));
+    // false because nodes identical
+    assert!(!is_viable_pair((&node_10_10, &node_10_10)));
+    // false because second node doesn't have enough space
+    assert!(!is_viable_pair((&node_11_10, &node_10_10)));
+    assert!(!is_viable_pair((&node_11_10, &node_0_10)));
+
+    assert!(is_viable_pair((&node_10_10, &node_11_10)));
+    assert!(is_viable_pair((&node_10_10, &node_0_10)));
+}
+
+#[test]
+fn test_find_viable_pairs() {
+    const NODE_0_10: Node = Node {
+        loc: (0, 0),
+        used: 0,
+        avail: 10,
+    };
+    const NODE_10_10: Node = Node {
+        loc: (0, 1),
+        used: 10,
+        avail: 10,
+    };
+    const NODE_11_10: Node = Node {
+        loc: (1, 0),
+        used: 11,
+        avail: 10,
+    };
+    let nodes = vec![NODE_0_10, NODE_10_10, NODE_11_10];
+
+    let viable = find_viable_pairs(&nodes);
+    assert_eq!(2, viable.len());
+}
+
+#[test]
+fn test_calc_fewest_steps_to_goal() {
+    let nodes = vec![Node {
+                         loc: (0, 0),
+                         used: 8,
+                         avail: 2,
+                     },
+                     Node {
+                         loc: (0, 1),
+                         used: 6,
+                         avail: 5,
+                     },
+                     Node {
+                         loc: (0, 2),
+                         used: 28,
+                         avail: 4,
+                     },
+                     Node {
+                         loc: (1, 0),
+                         used: 7,
+                         avail: 2,
+                     },
+                     Node {
+                         loc: (1, 1),
+                         used: 0,
+                         avail: 8,
+                     },
+                     Node {
+                         loc: (1, 2),
+                         used: 7,
+                         avail: 4,
+                     },
+                     Node {
+                         loc: (2, 0),
+                         used: 6,
+                         avail: 4,
+                     },
+                     Node {
+                         loc: (2, 1),
+                         used: 8,
+                         avail: 1,
+                     },
+                     Node {
+                         loc: (2, 2),
+                         used: 6,
+                         avail: 3,
+                     }];
+
+    assert_eq!(Some(7), calc_fewest_steps_to_goal(&nodes));
+}
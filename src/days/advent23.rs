@@ -0,0 +1,75 @@
+// advent23.rs
+// assembly language, self-modifying code
+// (the tgl instruction lives in the assembunny module along with day 12's instructions)
+
+use assembunny::{Instr, Registers, execute_to_halt, parse_program};
+use {ParseError, Solution};
+
+pub struct Advent23;
+
+impl Solution for Advent23 {
+    type Input = Vec<Instr>;
+    // value left in register a
+    type Answer = i32;
+
+    fn parse(input: &str) -> Result<Vec<Instr>, ParseError> {
+        parse_program(input)
+    }
+
+    fn part1(instructions: &Vec<Instr>) -> Option<i32> {
+        execute_to_halt(instructions, start_registers(1)).map(|program_state| program_state.registers[0])
+    }
+
+    fn part2(instructions: &Vec<Instr>) -> Option<i32> {
+        execute_to_halt(instructions, start_registers(2)).map(|program_state| program_state.registers[0])
+    }
+}
+
+// The number of eggs goes in register a
+fn egg_count(part: u32) -> i32 {
+    if part == 2 { 12 } else { 7 }
+}
+
+// What the registers hold when each part starts
+pub fn start_registers(part: u32) -> Registers {
+    [egg_count(part), 0, 0, 0]
+}
+
+// //////
+// Tests
+#[cfg(test)]
+use assembunny::parse_instr;
+
+#[test]
+fn test_execute_until_halt() {
+    let instructions = vec![
+        parse_instr("cpy 2 a").unwrap(),
+        parse_instr("tgl a").unwrap(),
+        parse_instr("tgl a").unwrap(),
+        parse_instr("tgl a").unwrap(),
+        parse_instr("cpy 1 a").unwrap(),
+        parse_instr("dec a").unwrap(),
+        parse_instr("dec a").unwrap(),
+    ];
+
+    let program_state = execute_to_halt(&instructions, start_registers(1)).unwrap();
+    assert_eq!(3, program_state.registers[0]);
+    assert_eq!(7, program_state.program_counter);
+}
+
+#[test]
+fn test_execute_until_halt_day12() {
+    // check that day 12's test case still works too
+    let instructions = vec![
+        parse_instr("cpy 41 a").unwrap(),
+        parse_instr("inc a").unwrap(),
+        parse_instr("inc a").unwrap(),
+        parse_instr("dec a").unwrap(),
+        parse_instr("jnz a 2").unwrap(),
+        parse_instr("dec a").unwrap(),
+    ];
+
+    let program_state = execute_to_halt(&instructions, [0; 4]).unwrap();
+    assert_eq!(42, program_state.registers[0]);
+    assert_eq!(6, program_state.program_counter);
+}
@@ -0,0 +1,175 @@
+// advent24.rs
+// cleaning ducts (like traveling salesman)
+
+use std::collections::HashMap;
+use {Grid, Location, ParseError, Solution, find_steps_to_targets};
+
+pub struct Advent24;
+
+impl Solution for Advent24 {
+    type Input = Maze;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Maze, ParseError> {
+        Maze::parse(input)
+    }
+
+    // shortest path
+    fn part1(maze: &Maze) -> Option<usize> {
+        PointDistances::from_maze(maze).map(|distances| distances.find_shortest_path())
+    }
+
+    // shortest path that returns to the start
+    fn part2(maze: &Maze) -> Option<usize> {
+        PointDistances::from_maze(maze).map(|distances| distances.find_shortest_path_return())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tile {
+    Wall,
+    Open,
+    Point(usize),
+}
+
+pub struct Maze {
+    tiles: Grid<Tile>,
+    points: Vec<Location>,
+}
+
+impl Maze {
+    fn parse(input: &str) -> Result<Maze, ParseError> {
+        let tiles = Grid::parse(input, "'#', '.' or a digit", |c| match c {
+            '#' => Some(Tile::Wall),
+            '.' => Some(Tile::Open),
+            '0'..='9' => Some(Tile::Point(c as usize - '0' as usize)),
+            _ => None,
+        })?;
+
+        // find points of interest within the maze
+        let mut points = vec![];
+        let mut point_map = HashMap::new();
+        for (loc, &tile) in tiles.iter() {
+            if let Tile::Point(point_idx) = tile {
+                if point_map.insert(point_idx, loc).is_some() {
+                    let (x, y) = loc;
+                    return Err(ParseError::new(y as usize + 1,
+                                               x as usize + 1,
+                                               &point_idx.to_string(),
+                                               "a new point"));
+                }
+            }
+        }
+
+        // points are numbered from 0 with no gaps, and we need somewhere to go after 0
+        let point_count = point_map.keys().max().map_or(0, |&max| max + 1);
+        for point_idx in 0..point_count.max(2) {
+            match point_map.get(&point_idx) {
+                Some(&loc) => points.push(loc),
+                None => {
+                    let expected = format!("point {} somewhere in the maze", point_idx);
+                    return Err(ParseError::new(tiles.height().max(1), 1, "", &expected));
+                }
+            }
+        }
+
+        Ok(Maze { tiles, points })
+    }
+
+    fn is_wall(&self, loc: Location) -> bool {
+        self.tiles.get(loc).is_none_or(|&tile| tile == Tile::Wall)
+    }
+}
+
+struct PointDistances {
+    distances: Vec<usize>,
+    point_count: usize,
+}
+
+impl PointDistances {
+    fn from_maze(maze: &Maze) -> Option<PointDistances> {
+        let point_count = maze.points.len();
+        let mut distances = vec![0; point_count*point_count];
+        // one flood of the maze from each point finds its distance to all the others
+        for p1 in 0..point_count {
+            let steps = find_steps_to_targets(maze.points[p1], &maze.points, |point| maze.is_wall(point));
+            for (p2, dist) in steps.into_iter().enumerate() {
+                // no path between points, can't handle this
+                distances[p1 * point_count + p2] = dist?;
+            }
+        }
+
+        Some(PointDistances {
+            distances,
+            point_count,
+        })
+    }
+
+    fn get(&self, p1: usize, p2: usize) -> usize {
+        self.distances[p1 + p2 * self.point_count]
+    }
+
+    // part 1
+    fn find_shortest_path(&self) -> usize {
+        let mut indices: Vec<_> = (1..self.point_count).collect();
+        permutohedron::Heap::new(&mut indices[..])
+            .map(|x| self.get(0, x[0]) + x.windows(2).fold(0, |acc, x| acc + self.get(x[0], x[1])))
+            .min()
+            .unwrap()
+    }
+
+    // part 2
+    fn find_shortest_path_return(&self) -> usize {
+        let mut indices: Vec<_> = (1..self.point_count).collect();
+        permutohedron::Heap::new(&mut indices[..])
+            .map(|x| {
+                self.get(0, x[self.point_count - 2]) + self.get(0, x[0]) +
+                x.windows(2).fold(0, |acc, x| acc + self.get(x[0], x[1]))
+            })
+            .min()
+            .unwrap()
+    }
+}
+
+// //////
+// Tests
+#[cfg(test)]
+const EXAMPLE: &str = "\
+###########
+#0.1.....2#
+#.#######.#
+#4.......3#
+###########";
+
+#[test]
+fn test_maze() {
+    let maze = Maze::parse(EXAMPLE).unwrap();
+    assert_eq!(5, maze.tiles.height());
+    assert_eq!(11, maze.tiles.width());
+    assert_eq!((1, 1), maze.points[0]);
+    assert_eq!((9, 3), maze.points[3]);
+    assert!(maze.is_wall((0, 0)));
+    assert!(!maze.is_wall((2, 1)));
+    assert!(maze.is_wall((11, 1)));
+}
+
+#[test]
+fn test_maze_errors() {
+    let maze = |rows: &[&str]| Maze::parse(&rows.join("\n"));
+
+    assert_eq!(Err(ParseError::new(2, 1, "#0.1#", "a row of width 6")),
+               maze(&["######", "#0.1#"]).map(|_| ()));
+    assert_eq!(Err(ParseError::new(1, 3, "x", "'#', '.' or a digit")),
+               maze(&["#0x1#"]).map(|_| ()));
+    assert_eq!(Err(ParseError::new(1, 4, "0", "a new point")),
+               maze(&["#0.0#"]).map(|_| ()));
+    assert_eq!(Err(ParseError::new(1, 1, "", "point 1 somewhere in the maze")),
+               maze(&["#0.2#"]).map(|_| ()));
+}
+
+#[test]
+fn test_distances() {
+    let maze = Maze::parse(EXAMPLE).unwrap();
+    let distances = PointDistances::from_maze(&maze).unwrap();
+    assert_eq!(14, distances.find_shortest_path());
+}
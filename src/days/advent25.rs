@@ -0,0 +1,118 @@
+// advent25.rs
+// assembly language with output, clock signal
+// (the out instruction lives in the assembunny module along with day 12's instructions)
+
+use std::collections::HashSet;
+use assembunny::{Instr, ProgramState, Signal, parse_program};
+use {ParseError, Solution};
+
+pub struct Advent25;
+
+impl Solution for Advent25 {
+    type Input = Vec<Instr>;
+    // part 1 is the lowest initial value of register a, there's no part 2 puzzle
+    type Answer = String;
+
+    fn parse(input: &str) -> Result<Vec<Instr>, ParseError> {
+        parse_program(input)
+    }
+
+    fn part1(instructions: &Vec<Instr>) -> Option<String> {
+        find_clock_input(instructions, SEARCH_STEPS, MAX_CLOCK_INPUT).map(|a| a.to_string())
+    }
+
+    // day 25 only has one puzzle, the second star comes free with the other 49
+    fn part2(_: &Vec<Instr>) -> Option<String> {
+        Some("Merry Christmas!".to_string())
+    }
+}
+
+// The search for register a gives up after this many steps in total, or once a gets
+// this big, so a program that never sends a clock signal can't hang it
+const SEARCH_STEPS: u64 = 100_000_000;
+const MAX_CLOCK_INPUT: i32 = 1_000_000;
+
+// Does the program send 0, 1, 0, 1... forever when it starts with this value in register a?
+// Takes the steps it runs out of budget, and gives None if they run out first.
+//
+// The program is deterministic, so if it's ever in exactly the same state right after sending
+// a value as it was after sending an earlier one, it will repeat everything in between forever.
+// Every value up to that point was checked, so the signal never goes wrong.
+fn is_clock_signal(instructions: &[Instr], a: i32, budget: &mut u64) -> Option<bool> {
+    let mut program_state = ProgramState::optimized(instructions.to_vec());
+    program_state.registers[0] = a;
+    let mut seen = HashSet::new();
+    let mut expected = 0;
+    let mut is_clock = false;
+
+    let steps = program_state.run_with_output_steps(*budget, |val, state| {
+        if val != expected {
+            return Signal::Stop;
+        }
+        expected = 1 - expected;
+        if !seen.insert(state.clone()) {
+            is_clock = true;
+            return Signal::Stop;
+        }
+        Signal::Continue
+    })?;
+
+    *budget -= steps;
+    Some(is_clock)
+}
+
+// Lowest positive initial value of register a that makes a clock signal. None if there isn't
+// one up to max_input, or the search runs out of max_steps before it can tell.
+fn find_clock_input(instructions: &[Instr], max_steps: u64, max_input: i32) -> Option<i32> {
+    let mut budget = max_steps;
+    for a in 1..=max_input {
+        if is_clock_signal(instructions, a, &mut budget)? {
+            return Some(a);
+        }
+    }
+    None
+}
+
+// //////
+// Tests
+#[cfg(test)]
+fn parse_program_lines(lines: &[&str]) -> Vec<Instr> {
+    parse_program(&lines.join("\n")).unwrap()
+}
+
+#[test]
+fn test_find_clock_input() {
+    // b = a - 2, then send b and flip it between 0 and 1 forever
+    let instructions = parse_program_lines(&["cpy a b",
+                                             "dec b",
+                                             "dec b",
+                                             "out b",
+                                             "jnz b 3",
+                                             "inc b",
+                                             "jnz 1 -3",
+                                             "dec b",
+                                             "jnz 1 -5"]);
+    let mut budget = 1000;
+    assert_eq!(Some(false), is_clock_signal(&instructions, 1, &mut budget));
+    assert_eq!(Some(true), is_clock_signal(&instructions, 2, &mut budget));
+    assert_eq!(Some(false), is_clock_signal(&instructions, 3, &mut budget));
+    assert!(budget < 1000);
+    assert_eq!(Some(2), find_clock_input(&instructions, SEARCH_STEPS, MAX_CLOCK_INPUT));
+    assert_eq!(None, find_clock_input(&instructions, 1000, 1));
+
+    // halting after a correct start isn't a clock signal
+    let instructions = parse_program_lines(&["dec a", "out a", "inc a", "out a"]);
+    assert_eq!(Some(false), is_clock_signal(&instructions, 1, &mut 1000));
+}
+
+#[test]
+fn test_find_clock_input_gives_up() {
+    // never sends anything, so the steps run out
+    let instructions = parse_program_lines(&["jnz 1 0"]);
+    assert_eq!(None, is_clock_signal(&instructions, 1, &mut 1000));
+    assert_eq!(None, find_clock_input(&instructions, 1000, MAX_CLOCK_INPUT));
+
+    // always sends 1 straight away, so every a up to the cap is tried
+    let instructions = parse_program_lines(&["out 1", "jnz 1 -1"]);
+    assert_eq!(None, find_clock_input(&instructions, SEARCH_STEPS, 1000));
+}
@@ -0,0 +1,77 @@
+// advent3.rs
+// impossible triangles
+
+use {ParseError, Solution, column_of, parse_lines};
+
+pub struct Advent3;
+
+impl Solution for Advent3 {
+    // "triples", each a 3-element Vec<i32>
+    type Input = Vec<Vec<i32>>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Vec<Vec<i32>>, ParseError> {
+        parse_lines(input, parse_triple)
+    }
+
+    fn part1(triples: &Vec<Vec<i32>>) -> Option<usize> {
+        Some(triples.iter().filter(|t| is_triangle_possible(t)).count())
+    }
+
+    fn part2(triples: &Vec<Vec<i32>>) -> Option<usize> {
+        if !triples.len().is_multiple_of(3) {
+            return None;
+        }
+
+        // handle chunks of 3 triples at a time (so conceptually 3x3 matrices)
+        // create new triples by column and test those, count how many of the 3 columns are
+        // triangles then sum up results from all the chunks
+        let possible_triangles = triples.chunks(3)
+            .map(|m3x3| {
+                (0..3)
+                    .filter(|col| {
+                        is_triangle_possible(&m3x3.iter()
+                            .map(|row| row[*col])
+                            .collect::<Vec<i32>>())
+                    })
+                    .count()
+            })
+            .sum();
+        Some(possible_triangles)
+    }
+}
+
+fn parse_triple(line: &str) -> Result<Vec<i32>, ParseError> {
+    let triple = line.split_whitespace()
+        .map(|s| s.parse().map_err(|_| ParseError::new(1, column_of(line, s), s, "a number")))
+        .collect::<Result<Vec<i32>, _>>()?;
+
+    if triple.len() == 3 {
+        Ok(triple)
+    } else {
+        Err(ParseError::new(1, 1, line, "3 side lengths"))
+    }
+}
+
+fn is_triangle_possible(tri: &[i32]) -> bool {
+    tri[0] + tri[1] > tri[2] && tri[0] + tri[2] > tri[1] && tri[1] + tri[2] > tri[0]
+}
+
+// //////
+// Tests
+#[test]
+fn test_parse_triple() {
+    assert_eq!(Ok(vec![5, 10, 25]), parse_triple("  5  10  25"));
+    assert_eq!(Err(ParseError::new(1, 6, "1O", "a number")), parse_triple("  5  1O  25"));
+    assert_eq!(Err(ParseError::new(1, 1, "5 10", "3 side lengths")), parse_triple("5 10"));
+}
+
+#[test]
+fn test_is_triangle_possible() {
+    assert!(!is_triangle_possible(&{
+        [5, 10, 25]
+    }));
+    assert!(is_triangle_possible(&{
+        [5, 4, 3]
+    }));
+}
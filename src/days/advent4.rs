@@ -0,0 +1,144 @@
+// advent4.rs
+// parsing and shift ciphers
+
+use std::collections::HashMap;
+use regex::Regex;
+use {ParseError, Solution, parse_lines};
+
+pub struct Advent4;
+
+impl Solution for Advent4 {
+    type Input = Vec<String>;
+    type Answer = i32;
+
+    fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+        parse_lines(input, parse_room)
+    }
+
+    fn part1(rooms: &Vec<String>) -> Option<i32> {
+        Some(rooms.iter()
+            .filter(|room| is_real_room(room))
+            .map(|room| parse_sector_id(room))
+            .sum())
+    }
+
+    fn part2(rooms: &Vec<String>) -> Option<i32> {
+        // I had to decrypt everything and read through the output to figure out what
+        // to search for here. Poorly specified problem.
+        rooms.iter()
+            .filter(|room| is_real_room(room))
+            .find(|room| decrypt_room(room).contains("northpole object storage"))
+            .map(|room| parse_sector_id(room))
+    }
+}
+
+fn parse_room(room: &str) -> Result<String, ParseError> {
+    lazy_static! {
+        static ref RE_ROOM: Regex = Regex::new(r"^[a-z-]+-\d+\[[a-z]+\]$").unwrap();
+    }
+
+    if RE_ROOM.is_match(room.trim()) {
+        Ok(room.trim().to_string())
+    } else {
+        Err(ParseError::new(1, 1, room, "encrypted-name-<sector ID>[<checksum>]"))
+    }
+}
+
+fn is_real_room(room: &str) -> bool {
+    let mut freq_map = HashMap::new();
+
+    // count chars
+    for c in room.chars() {
+        if c == '-' {
+            continue;
+        }
+        if !c.is_alphabetic() {
+            break;
+        }
+        *freq_map.entry(c).or_insert(0) += 1;
+    }
+
+    let mut char_counts: Vec<(char, usize)> = freq_map.iter().map(|(k, v)| (*k, *v)).collect();
+    char_counts.sort_by(|a, b| {
+        if b.1 == a.1 {
+            a.0.cmp(&b.0)
+        } else {
+            b.1.cmp(&a.1)
+        }
+    });
+
+    let mut correct_checksum = char_counts.iter().map(|&(k, _)| k).take(5).collect::<String>();
+    correct_checksum.push(']');
+
+    room.contains(&correct_checksum)
+}
+
+fn parse_sector_id(room: &str) -> i32 {
+    room.rsplit('-').next().unwrap().split('[').next().unwrap().parse().unwrap()
+}
+
+// ///////
+// Part 2
+
+fn decrypt_room(room: &str) -> String {
+    let re = Regex::new(r"([a-z-]+)-(\d+)\[").unwrap(); // won't panic because it's a valid RE
+
+    let cap = re.captures(room).unwrap();   // could panic if the RE doesn't match
+
+    // unwraps are safe here because the RE matched
+    let text = &cap[1];
+    let num: u32 = cap[2].parse().unwrap();
+    let shift = (num % 26) as u8;
+
+    text.chars().map(|c| decrypt_char(c, shift)).collect()
+}
+
+fn decrypt_char(c: char, shift: u8) -> char {
+    if c == '-' {
+        ' '
+    } else {
+        let offset = b'a';
+        let new = (c as u8 - offset + shift) % 26;
+        (new + offset) as char
+    }
+}
+
+// //////
+// Tests
+
+#[test]
+fn test_parse_room() {
+    assert_eq!(Ok("a-b-c-d-e-f-g-h-987[abcde]".to_string()),
+               parse_room("a-b-c-d-e-f-g-h-987[abcde]"));
+    assert!(parse_room("a-b-c-d-e-f-g-h-987").is_err());
+    assert!(parse_room("a-b-c-d-e-f-g-h-[abcde]").is_err());
+}
+
+// part 1
+#[test]
+fn test_is_real_room() {
+    assert!(is_real_room("aaaaa-bbb-z-y-x-123[abxyz]"));
+    assert!(is_real_room("a-b-c-d-e-f-g-h-987[abcde]"));
+    assert!(is_real_room("not-a-real-room-404[oarel]"));
+    assert!(!is_real_room("totally-real-room-200[decoy]"));
+}
+
+#[test]
+fn test_parse_sector_id() {
+    assert_eq!(123, parse_sector_id("aaaaa-bbb-z-y-x-123[abxyz]"));
+    assert_eq!(987, parse_sector_id("a-b-c-d-e-f-g-h-987[abcde]"));
+    assert_eq!(404, parse_sector_id("not-a-real-room-404[oarel]"));
+    assert_eq!(200, parse_sector_id("totally-real-room-200[decoy]"));
+}
+
+// part 2
+#[test]
+fn test_decrypt_room() {
+    assert_eq!("very encrypted name",
+               decrypt_room("qzmt-zixmtkozy-ivhz-343[dummy]"));
+}
+
+#[test]
+fn test_decrypt_char() {
+    assert_eq!('v', decrypt_char('q', 5));
+}
@@ -0,0 +1,87 @@
+// advent6.rs
+// repetition codes
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use {ParseError, Solution};
+
+pub struct Advent6;
+
+impl Solution for Advent6 {
+    type Input = Vec<String>;
+    type Answer = String;
+
+    // all messages must be the same length
+    fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+        let mut messages: Vec<String> = vec![];
+
+        for (i, line) in input.lines().enumerate() {
+            let message = line.trim();
+            if let Some(first) = messages.first() {
+                if message.len() != first.len() {
+                    let expected = format!("a message of length {}", first.len());
+                    return Err(ParseError::new(i + 1, 1, line, &expected));
+                }
+            }
+            messages.push(message.to_string());
+        }
+
+        Ok(messages)
+    }
+
+    fn part1(messages: &Vec<String>) -> Option<String> {
+        let messages: Vec<&str> = messages.iter().map(|x| x.as_str()).collect();
+        Some(correct_errors(&messages, false))
+    }
+
+    fn part2(messages: &Vec<String>) -> Option<String> {
+        let messages: Vec<&str> = messages.iter().map(|x| x.as_str()).collect();
+        Some(correct_errors(&messages, true))
+    }
+}
+
+fn correct_errors(messages: &[&str], reverse_sort: bool) -> String {
+    if messages.is_empty() {
+        return "".to_string();
+    }
+
+    let message_len = messages[0].len();
+    let mut histograms = Vec::with_capacity(message_len);
+    for _ in 0..message_len {
+        histograms.push(HashMap::new());
+    }
+
+    for message in messages {
+        for (c, histogram) in message.chars().zip(histograms.iter_mut()) {
+            *histogram.entry(c).or_insert(0) += 1;
+        }
+    }
+
+    let mut corrected = String::new();
+    for histogram in histograms {
+        let mut char_counts: Vec<(char, usize)> = histogram.iter().map(|(k, v)| (*k, *v)).collect();
+        if reverse_sort {
+            // part 2
+            char_counts.sort_by_key(|&(_, count)| count);
+        } else {
+            // part 1
+            char_counts.sort_by_key(|&(_, count)| Reverse(count));
+        }
+
+        corrected.push(char_counts[0].0);
+    }
+
+    corrected
+}
+
+// ///////
+// Tests
+
+#[test]
+fn test_correct_errors() {
+    let v = vec!["eedadn", "drvtee", "eandsr", "raavrd", "atevrs", "tsrnev", "sdttsa", "rasrtv",
+                 "nssdts", "ntnada", "svetve", "tesnvt", "vntsnd", "vrdear", "dvrsen", "enarar"];
+
+    assert_eq!("easter", correct_errors(&v, false));
+    assert_eq!("advent", correct_errors(&v, true));
+}
@@ -0,0 +1,164 @@
+// advent7.rs
+// IPv7, ABBA detection
+
+use std::collections::HashSet;
+use {ParseError, Solution, parse_lines};
+
+pub struct Advent7;
+
+impl Solution for Advent7 {
+    type Input = Vec<String>;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+        parse_lines(input, parse_addr)
+    }
+
+    // total TLS addresses
+    fn part1(addrs: &Vec<String>) -> Option<usize> {
+        Some(addrs.iter().filter(|addr| supports_tls(addr)).count())
+    }
+
+    // total SSL addresses
+    fn part2(addrs: &Vec<String>) -> Option<usize> {
+        Some(addrs.iter().filter(|addr| supports_ssl(addr)).count())
+    }
+}
+
+// Addresses are lowercase letters with hypernet sequences in brackets.
+// Brackets must be balanced and can't nest, otherwise splitting on them won't work.
+fn parse_addr(addr: &str) -> Result<String, ParseError> {
+    let mut open_bracket = None;
+
+    for (i, c) in addr.char_indices() {
+        let expected = match (c, open_bracket) {
+            ('a'..='z', _) => continue,
+            ('[', None) => {
+                open_bracket = Some(i);
+                continue;
+            }
+            (']', Some(_)) => {
+                open_bracket = None;
+                continue;
+            }
+            ('[', Some(_)) => "] to close the hypernet sequence",
+            _ => "a letter or [",
+        };
+        return Err(ParseError::new(1, i + 1, &addr[i..i + c.len_utf8()], expected));
+    }
+
+    if let Some(i) = open_bracket {
+        Err(ParseError::new(1, i + 1, &addr[i..], "a hypernet sequence closed by ]"))
+    } else {
+        Ok(addr.to_string())
+    }
+}
+
+// detect if address has ABBA in supernet sequences and no ABBA in hypernet sequences
+fn supports_tls(addr: &str) -> bool {
+    // assuming well-formed addresses that have correctly balanced [ and ]:
+    // This means odd substrings are hypernet sequences and even substrings are not
+    let (supernets, hypernets): (Vec<_>, Vec<_>) =
+        addr.split(['[', ']']).enumerate().partition(|&(i, _)| i % 2 == 0);
+
+    hypernets.iter().all(|&(_, x)| !has_abba(x)) && supernets.iter().any(|&(_, x)| has_abba(x))
+}
+
+// detect if string has characters in the form ABBA
+fn has_abba(s: &str) -> bool {
+    s.as_bytes().windows(4).any(|w| w[0] == w[3] && w[1] == w[2] && w[0] != w[1])
+}
+
+// ///////
+// Part 2
+
+fn supports_ssl(addr: &str) -> bool {
+    // Split IPV7 address into supernets and hypernets
+    let (supernets, hypernets): (Vec<_>, Vec<_>) =
+        addr.split(['[', ']']).enumerate().partition(|&(i, _)| i % 2 == 0);
+
+    // Find all ABAs in supernets and store them as a set of BABs
+    let mut babs = HashSet::new();
+    for &(_, supernet) in &supernets {
+        get_bab_from_aba(supernet, &mut babs);
+    }
+
+    // Search all hypernets for any BAB
+    for &(_, hypernet) in &hypernets {
+        for bab in &babs {
+            if hypernet.contains(bab) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+// find any ABAs in the string and add the corresponding BABs to the set
+fn get_bab_from_aba(s: &str, babs: &mut HashSet<String>) {
+    for bab in s.as_bytes()
+        .windows(3)
+        .filter_map(|w| if w[0] == w[2] && w[0] != w[1] {
+            String::from_utf8(vec![w[1], w[0], w[1]]).ok()
+        } else {
+            None
+        }) {
+        babs.insert(bab);
+    }
+}
+
+// ///////
+// Tests
+
+#[test]
+fn test_parse_addr() {
+    assert_eq!(Ok("abba[mnop]qrst".to_string()), parse_addr("abba[mnop]qrst"));
+    assert_eq!(Err(ParseError::new(1, 8, "[", "] to close the hypernet sequence")),
+               parse_addr("abba[mn[op]qrst"));
+    assert_eq!(Err(ParseError::new(1, 5, "]", "a letter or [")), parse_addr("abba]qrst"));
+    assert_eq!(Err(ParseError::new(1, 5, "[mnop", "a hypernet sequence closed by ]")),
+               parse_addr("abba[mnop"));
+}
+
+#[test]
+fn test_supports_tls() {
+    assert!(!supports_tls("[mnop]qrst"));
+    assert!(supports_tls("abba[mnop]qrst"));
+    assert!(!supports_tls("abcd[bddb]xyyx"));
+    assert!(!supports_tls("aaaa[qwer]tyui"));
+    assert!(supports_tls("ioxxoj[asdfgh]zxcvbn"));
+}
+
+#[test]
+fn test_has_abba() {
+    assert!(!has_abba(""));
+    assert!(!has_abba("abb"));
+    assert!(!has_abba("abcd"));
+    assert!(has_abba("abba"));
+    assert!(has_abba("wfabbatu"));
+    assert!(!has_abba("aaaa"));
+    assert!(!has_abba("abbb"));
+    assert!(!has_abba("abaa"));
+}
+
+// part 2
+#[test]
+fn test_supports_ssl() {
+    assert!(supports_ssl("aba[bab]xyz"));
+    assert!(!supports_ssl("xyz[xyz]xyz"));
+    assert!(supports_ssl("aaa[kek]eke"));
+    assert!(supports_ssl("zazbz[bzb]cdb"));
+}
+
+#[test]
+fn test_get_bab_from_aba() {
+    let mut test_set = HashSet::new();
+    let mut babs = HashSet::new();
+    get_bab_from_aba("", &mut babs);
+    assert_eq!(test_set, babs);
+    get_bab_from_aba("zazbz", &mut babs);
+    test_set.insert("aza".to_string());
+    test_set.insert("bzb".to_string());
+    assert_eq!(test_set, babs);
+}
@@ -0,0 +1,150 @@
+// advent8.rs
+// parsing instructions for small LCD
+
+use regex::Regex;
+use {Grid, ParseError, Solution, parse_lines};
+
+#[cfg(not(test))]
+const SCREEN_WIDTH: usize = 50;
+#[cfg(not(test))]
+const SCREEN_HEIGHT: usize = 6;
+
+#[cfg(test)]
+const SCREEN_WIDTH: usize = 7;
+#[cfg(test)]
+const SCREEN_HEIGHT: usize = 3;
+
+type LightGrid = Grid<bool>;
+
+pub struct Advent8;
+
+impl Solution for Advent8 {
+    type Input = Vec<Cmd>;
+    // part 1 is a count, part 2 is the screen display
+    type Answer = String;
+
+    fn parse(input: &str) -> Result<Vec<Cmd>, ParseError> {
+        parse_lines(input, parse_cmd)
+    }
+
+    fn part1(cmds: &Vec<Cmd>) -> Option<String> {
+        Some(count_lights(&build_grid(cmds)).to_string())
+    }
+
+    fn part2(cmds: &Vec<Cmd>) -> Option<String> {
+        Some(print_grid(&build_grid(cmds)))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Cmd {
+    Rect(usize, usize), // columns, rows
+    RotateRow(usize, usize), // row, rotation
+    RotateCol(usize, usize), // column, rotation
+}
+
+fn parse_cmd(cmd: &str) -> Result<Cmd, ParseError> {
+    lazy_static! {
+        static ref RE_RECT: Regex = Regex::new(r"^rect (\d+)x(\d+)$").unwrap();
+        static ref RE_ROW: Regex = Regex::new(r"^rotate row y=(\d+) by (\d+)$").unwrap();
+        static ref RE_COL: Regex = Regex::new(r"^rotate column x=(\d+) by (\d+)$").unwrap();
+    }
+
+    let cmd = cmd.trim();
+    // positions must be on the screen, but sizes and rotations can be anything
+    let number = |m: Option<regex::Match>, limit: usize, what: &str| {
+        let m = m.unwrap();
+        match m.as_str().parse() {
+            Ok(n) if n < limit => Ok(n),
+            _ => {
+                let expected = if limit == usize::MAX {
+                    format!("a {}", what)
+                } else {
+                    format!("a {} less than {}", what, limit)
+                };
+                Err(ParseError::new(1, m.start() + 1, m.as_str(), &expected))
+            }
+        }
+    };
+
+    if let Some(caps) = RE_RECT.captures(cmd) {
+        Ok(Cmd::Rect(number(caps.get(1), usize::MAX, "width")?,
+                     number(caps.get(2), usize::MAX, "height")?))
+    } else if let Some(caps) = RE_ROW.captures(cmd) {
+        Ok(Cmd::RotateRow(number(caps.get(1), SCREEN_HEIGHT, "row")?,
+                          number(caps.get(2), usize::MAX, "rotation")?))
+    } else if let Some(caps) = RE_COL.captures(cmd) {
+        Ok(Cmd::RotateCol(number(caps.get(1), SCREEN_WIDTH, "column")?,
+                          number(caps.get(2), usize::MAX, "rotation")?))
+    } else {
+        let expected = "rect AxB, rotate row y=A by B or rotate column x=A by B";
+        Err(ParseError::new(1, 1, cmd, expected))
+    }
+}
+
+fn build_grid(cmds: &[Cmd]) -> LightGrid {
+    let mut grid = Grid::new(SCREEN_WIDTH, SCREEN_HEIGHT, false);
+
+    for cmd in cmds {
+        apply_cmd(cmd, &mut grid);
+    }
+
+    grid
+}
+
+fn apply_cmd(cmd: &Cmd, grid: &mut LightGrid) {
+    match *cmd {
+        Cmd::Rect(cols, rows) => {
+            for y in 0..rows.min(SCREEN_HEIGHT) {
+                for x in 0..cols.min(SCREEN_WIDTH) {
+                    grid.set((x as i32, y as i32), true);
+                }
+            }
+        }
+        Cmd::RotateRow(row, rot) => grid.rotate_row(row, rot),
+        Cmd::RotateCol(col, rot) => grid.rotate_col(col, rot),
+    }
+}
+
+fn count_lights(grid: &LightGrid) -> u32 {
+    grid.iter().filter(|&(_, &light)| light).count() as u32
+}
+
+// ///////
+// Part 2
+
+// the screen display, one line per row
+fn print_grid(grid: &LightGrid) -> String {
+    grid.render(|&cell| if cell { '#' } else { '.' })
+}
+
+// //////
+// Tests
+
+#[test]
+fn test_parse_cmd() {
+    assert_eq!(Ok(Cmd::Rect(3, 2)), parse_cmd("rect 3x2"));
+    assert_eq!(Ok(Cmd::RotateCol(1, 1)), parse_cmd("rotate column x=1 by 1"));
+    assert_eq!(Ok(Cmd::RotateRow(0, 4)), parse_cmd("rotate row y=0 by 4"));
+    assert_eq!(Err(ParseError::new(1, 14, "3", "a row less than 3")),
+               parse_cmd("rotate row y=3 by 4"));
+    assert!(parse_cmd("rotate diagonal x=1 by 1").is_err());
+}
+
+#[test]
+fn test_apply_cmd() {
+    let mut grid = Grid::new(SCREEN_WIDTH, SCREEN_HEIGHT, false);
+    const SOLUTION: &str = ".#..#.#\n#.#....\n.#.....";
+
+    apply_cmd(&Cmd::Rect(3, 2), &mut grid);
+    apply_cmd(&Cmd::RotateCol(1, 1), &mut grid);
+    apply_cmd(&Cmd::RotateRow(0, 4), &mut grid);
+    apply_cmd(&Cmd::RotateCol(1, 1), &mut grid);
+
+    assert_eq!(SOLUTION, print_grid(&grid));
+    assert_eq!(6, count_lights(&grid));
+    // rotations as big as a usize are fine
+    apply_cmd(&parse_cmd("rotate column x=0 by 18446744073709551614").unwrap(), &mut grid);
+    apply_cmd(&parse_cmd("rotate row y=2 by 18446744073709551613").unwrap(), &mut grid);
+    assert_eq!("##..#.#\n..#....\n#......", print_grid(&grid));
+}
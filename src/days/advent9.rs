@@ -0,0 +1,162 @@
+// advent9.rs
+// simple RLE decompression
+
+use regex::Regex;
+use {ParseError, Solution, column_of, parse_single_line};
+
+pub struct Advent9;
+
+impl Solution for Advent9 {
+    // compressed text
+    type Input = String;
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<String, ParseError> {
+        let line = parse_single_line(input, "compressed text")?;
+        check_markers(line, line)?;
+        Ok(line.to_string())
+    }
+
+    fn part1(compressed: &String) -> Option<usize> {
+        Some(calc_decompressed_length(compressed))
+    }
+
+    fn part2(compressed: &String) -> Option<usize> {
+        Some(calc_decompressed_length2(compressed))
+    }
+}
+
+lazy_static! {
+    static ref MARKER_RE: Regex = Regex::new(r"^\((\d+)x(\d+)\)").unwrap();
+}
+
+// Check that every marker in a section is well-formed and that the data it repeats
+// doesn't run past the end of the section (both parts rely on this)
+fn check_markers(line: &str, section: &str) -> Result<(), ParseError> {
+    let mut pos = 0;
+
+    while let Some(start) = section[pos..].find('(').map(|i| pos + i) {
+        let rest = &section[start..];
+        let caps = MARKER_RE.captures(rest)
+            .ok_or_else(|| ParseError::new(1, column_of(line, rest), rest, "a marker like (AxB)"))?;
+        let count: usize = caps[1].parse()
+            .map_err(|_| ParseError::new(1, column_of(line, rest), &caps[1], "a character count"))?;
+
+        let data_start = start + caps[0].len();
+        if count == 0 || data_start + count > section.len() {
+            let expected = format!("{} characters after the marker", count);
+            return Err(ParseError::new(1, column_of(line, rest), &caps[0], &expected));
+        }
+        check_markers(line, &section[data_start..data_start + count])?;
+        pos = data_start + count;
+    }
+    Ok(())
+}
+
+// ///////
+// Part 1
+fn calc_decompressed_length(s: &str) -> usize {
+    let mut iter = s.chars();
+    let mut length = 0;
+
+    while let Some(c) = iter.next() {
+        if c == '(' {
+            let count = parse_usize(&mut iter);
+            let repeat = parse_usize(&mut iter);
+            length += count * repeat;
+            // don't forget to skip the part we repeat
+            iter.nth(count - 1);
+        } else {
+            length += 1;
+        }
+    }
+    length
+}
+
+// consumes an integer and the next character after it (should be either 'x' or ')')
+fn parse_usize(iter: &mut std::str::Chars) -> usize {
+    let mut num = 0;
+    for c in iter {
+        if c.is_ascii_digit() {
+            let digit = c as usize - '0' as usize;
+            num = num * 10 + digit;
+        } else {
+            break;
+        }
+    }
+    num
+}
+
+// ///////
+// Part 2
+fn calc_decompressed_length2(s: &str) -> usize {
+    let mut length = 0;
+    let mut iter = s.chars().enumerate();
+
+    while let Some((pos, c)) = iter.next() {
+        if c == '(' {
+            let mut offset = pos + 1;
+            let count = parse_usize2(&s[offset..], &mut offset);
+            let repeat = parse_usize2(&s[offset..], &mut offset);
+            length += repeat * calc_decompressed_length2(&s[offset..offset + count]);
+            // skip the part we consumed and the part we repeat
+            iter.nth(offset - pos - 2 + count);
+        } else {
+            length += 1;
+        }
+    }
+    length
+}
+
+// consumes an integer and the next character after it (should be either 'x' or ')')
+fn parse_usize2(s: &str, chars_consumed: &mut usize) -> usize {
+    let mut num = 0;
+    for c in s.chars() {
+        *chars_consumed += 1;
+        if c.is_ascii_digit() {
+            let digit = c as usize - '0' as usize;
+            num = num * 10 + digit;
+        } else {
+            break;
+        }
+    }
+    num
+}
+
+
+// //////
+// Tests
+
+#[test]
+fn test_parse() {
+    assert_eq!(Ok("A(1x5)BC".to_string()), Advent9::parse("A(1x5)BC\n"));
+    assert_eq!(Err(ParseError::new(1, 2, "(1y5)BC", "a marker like (AxB)")),
+               Advent9::parse("A(1y5)BC"));
+    assert_eq!(Err(ParseError::new(1, 2, "(3x5)", "3 characters after the marker")),
+               Advent9::parse("A(3x5)BC"));
+    assert_eq!(Err(ParseError::new(1, 6, "(3x2)", "3 characters after the marker")),
+               Advent9::parse("(6x1)(3x2)AB"));
+}
+
+#[test]
+fn test_calc_decompressed_length() {
+    assert_eq!(6, calc_decompressed_length("ADVENT"));
+    assert_eq!(7, calc_decompressed_length("A(1x5)BC"));
+    assert_eq!(9, calc_decompressed_length("(3x3)XYZ"));
+    assert_eq!(11, calc_decompressed_length("A(2x2)BCD(2x2)EFG"));
+    assert_eq!(6, calc_decompressed_length("(6x1)(1x3)A"));
+    assert_eq!(18, calc_decompressed_length("X(8x2)(3x3)ABCY"));
+}
+
+// part 2
+#[test]
+fn test_calc_decompressed_length2() {
+    assert_eq!(6, calc_decompressed_length2("ADVENT"));
+    assert_eq!(7, calc_decompressed_length2("A(1x5)BC"));
+    assert_eq!(9, calc_decompressed_length2("(3x3)XYZ"));
+    assert_eq!(20, calc_decompressed_length2("X(8x2)(3x3)ABCY"));
+    assert_eq!(241920,
+               calc_decompressed_length2("(27x12)(20x12)(13x14)(7x10)(1x12)A"));
+    assert_eq!(445,
+               calc_decompressed_length2("(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN"));
+}
@@ -0,0 +1,205 @@
+// days/mod.rs
+// Every day's solver, collected in one table so a single runner can reach them all
+
+use std::io::{self, Read};
+use assembunny::Registers;
+use timing::{self, DayTiming, Runs};
+use {ParseError, Solution};
+
+pub mod advent1;
+pub mod advent2;
+pub mod advent3;
+pub mod advent4;
+pub mod advent5;
+pub mod advent6;
+pub mod advent7;
+pub mod advent8;
+pub mod advent9;
+pub mod advent10;
+pub mod advent11;
+pub mod advent12;
+pub mod advent13;
+pub mod advent14;
+pub mod advent15;
+pub mod advent16;
+pub mod advent17;
+pub mod advent18;
+pub mod advent19;
+pub mod advent20;
+pub mod advent21;
+pub mod advent22;
+pub mod advent23;
+pub mod advent24;
+pub mod advent25;
+
+// One answer per requested part.
+// Each answer is None if that part has no answer (or isn't a valid part number).
+pub type Answers = Vec<Option<String>>;
+
+pub struct Day {
+    pub number: u32,
+    // Parse the input, then solve each requested part.
+    pub solve: fn(&str, &[u32]) -> Result<Answers, ParseError>,
+    // Time parsing and each requested part separately.
+    pub time: fn(&str, &[u32], &Runs) -> Result<DayTiming, ParseError>,
+}
+
+fn solve<S: Solution>(input: &str, parts: &[u32]) -> Result<Answers, ParseError> {
+    let parsed = S::parse(input)?;
+
+    Ok(parts.iter()
+        .map(|&part| match part {
+            1 => S::part1(&parsed).map(|answer| answer.to_string()),
+            2 => S::part2(&parsed).map(|answer| answer.to_string()),
+            _ => None,
+        })
+        .collect())
+}
+
+fn time<S: Solution>(input: &str, parts: &[u32], runs: &Runs) -> Result<DayTiming, ParseError> {
+    let parsed = S::parse(input)?;
+
+    Ok(DayTiming {
+        parse: timing::measure(runs, || S::parse(input)),
+        parts: parts.iter()
+            .filter_map(|&part| match part {
+                1 => Some((part, timing::measure(runs, || S::part1(&parsed)))),
+                2 => Some((part, timing::measure(runs, || S::part2(&parsed)))),
+                _ => None,
+            })
+            .collect(),
+    })
+}
+
+macro_rules! days {
+    ($($number:expr => $solution:path),*) => {
+        &[$(Day { number: $number, solve: solve::<$solution>, time: time::<$solution> }),*]
+    }
+}
+
+pub static DAYS: &[Day] = days![1 => advent1::Advent1,
+                                2 => advent2::Advent2,
+                                3 => advent3::Advent3,
+                                4 => advent4::Advent4,
+                                5 => advent5::Advent5,
+                                6 => advent6::Advent6,
+                                7 => advent7::Advent7,
+                                8 => advent8::Advent8,
+                                9 => advent9::Advent9,
+                                10 => advent10::Advent10,
+                                11 => advent11::Advent11,
+                                12 => advent12::Advent12,
+                                13 => advent13::Advent13,
+                                14 => advent14::Advent14,
+                                15 => advent15::Advent15,
+                                16 => advent16::Advent16,
+                                17 => advent17::Advent17,
+                                18 => advent18::Advent18,
+                                19 => advent19::Advent19,
+                                20 => advent20::Advent20,
+                                21 => advent21::Advent21,
+                                22 => advent22::Advent22,
+                                23 => advent23::Advent23,
+                                24 => advent24::Advent24,
+                                25 => advent25::Advent25];
+
+pub fn find_day(number: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.number == number)
+}
+
+// The registers a part starts with
+pub type StartRegisters = fn(u32) -> Registers;
+
+// Days whose input is an assembunny program
+pub static ASSEMBUNNY_DAYS: &[(u32, StartRegisters)] = &[(12, advent12::start_registers),
+                                                          (23, advent23::start_registers)];
+
+pub fn find_assembunny_day(number: u32) -> Option<StartRegisters> {
+    ASSEMBUNNY_DAYS.iter().find(|&&(day, _)| day == number).map(|&(_, start_registers)| start_registers)
+}
+
+// Solve the requested parts and print each answer.
+// Multi-line answers (like day 8's screen) start on the line after the label.
+// Returns false if the input was malformed or any part had no answer.
+pub fn run_day(day: &Day, parts: &[u32], input: &str) -> bool {
+    let answers = match (day.solve)(input, parts) {
+        Ok(answers) => answers,
+        Err(err) => {
+            eprintln!("day {}: invalid input: {}", day.number, err);
+            return false;
+        }
+    };
+    let mut all_solved = true;
+
+    for (&part, answer) in parts.iter().zip(answers) {
+        match answer {
+            Some(ref answer) if answer.contains('\n') => {
+                println!("day {} part {}:\n{}", day.number, part, answer)
+            }
+            Some(answer) => println!("day {} part {}: {}", day.number, part, answer),
+            None => {
+                println!("day {} part {}: no answer", day.number, part);
+                all_solved = false;
+            }
+        }
+    }
+
+    all_solved
+}
+
+// Entry point for the per-day binaries: solve both parts using input from stdin.
+// Returns the process exit code.
+pub fn main_for_day(number: u32) -> i32 {
+    let day = find_day(number).expect("no solver for this day");
+    let mut input = String::new();
+    if let Err(err) = io::stdin().read_to_string(&mut input) {
+        eprintln!("error reading input: {}", err);
+        return 1;
+    }
+
+    if run_day(day, &[1, 2], &input) { 0 } else { 1 }
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::{find_assembunny_day, find_day};
+    use timing::Runs;
+
+    #[test]
+    fn test_find_day() {
+        assert_eq!(1, find_day(1).unwrap().number);
+        assert_eq!(25, find_day(25).unwrap().number);
+        assert_eq!(11, find_day(11).unwrap().number);
+        assert!(find_day(26).is_none());
+    }
+
+    #[test]
+    fn test_find_assembunny_day() {
+        assert_eq!([0, 0, 1, 0], find_assembunny_day(12).unwrap()(2));
+        assert_eq!([7, 0, 0, 0], find_assembunny_day(23).unwrap()(1));
+        assert!(find_assembunny_day(13).is_none());
+    }
+
+    #[test]
+    fn test_solve() {
+        let day = find_day(1).unwrap();
+        assert_eq!(Ok(vec![Some("8".to_string()), Some("4".to_string())]),
+                   (day.solve)("R8, R4, R4, R8", &[1, 2]));
+        assert_eq!(Ok(vec![Some("4".to_string())]), (day.solve)("R8, R4, R4, R8", &[2]));
+        assert_eq!(1, (day.solve)("R8, X4", &[1]).unwrap_err().line);
+    }
+
+    #[test]
+    fn test_time() {
+        let day = find_day(1).unwrap();
+        let runs = Runs {
+            warmup: 0,
+            repeats: 2,
+        };
+        let timing = (day.time)("R8, R4, R4, R8", &[2], &runs).unwrap();
+        assert_eq!(vec![2], timing.parts.iter().map(|&(part, _)| part).collect::<Vec<_>>());
+        assert!((day.time)("R8, X4", &[1, 2], &runs).is_err());
+    }
+}
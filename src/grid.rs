@@ -0,0 +1,289 @@
+// grid.rs
+// A rectangular 2D grid of cells, addressed by Location (x across, y down, both from 0)
+
+use {Location, ParseError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>, // row by row
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    // Shift every cell in row y right by `by`, wrapping around to the start of the row.
+    // Panics if there's no row y.
+    pub fn rotate_row(&mut self, y: usize, by: usize) {
+        assert!(y < self.height, "row {} is outside the grid", y);
+        if self.width > 0 {
+            self.cells[y * self.width..(y + 1) * self.width].rotate_right(by % self.width);
+        }
+    }
+
+    // Shift every cell in column x down by `by`, wrapping around to the top of the column.
+    // Panics if there's no column x.
+    pub fn rotate_col(&mut self, x: usize, by: usize) {
+        assert!(x < self.width, "column {} is outside the grid", x);
+        if self.height > 0 {
+            let by = by % self.height;
+            let column: Vec<_> = (0..self.height).map(|y| self.cells[y * self.width + x].clone()).collect();
+            for (y, cell) in column.into_iter().enumerate() {
+                self.cells[(y + by) % self.height * self.width + x] = cell;
+            }
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn<F>(width: usize, height: usize, cell: F) -> Grid<T>
+        where F: FnMut(Location) -> T
+    {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x as i32, y as i32)))
+            .map(cell)
+            .collect();
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    // Build a grid from a character map, one row per line. cell turns each character into a
+    // cell, or returns None if it isn't allowed (expected describes what is).
+    // Every row must be as wide as the first.
+    pub fn parse<F>(input: &str, expected: &str, mut cell: F) -> Result<Grid<T>, ParseError>
+        where F: FnMut(char) -> Option<T>
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+
+        for (y, line) in input.lines().enumerate() {
+            let line_width = line.chars().count();
+            if *width.get_or_insert(line_width) != line_width {
+                let expected = format!("a row of width {}", width.unwrap());
+                return Err(ParseError::new(y + 1, 1, line, &expected));
+            }
+            for (x, c) in line.chars().enumerate() {
+                match cell(c) {
+                    Some(value) => cells.push(value),
+                    None => return Err(ParseError::new(y + 1, x + 1, &c.to_string(), expected)),
+                }
+            }
+            height += 1;
+        }
+
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Location) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    fn index(&self, location: Location) -> Option<usize> {
+        if self.contains(location) {
+            Some(location.1 as usize * self.width + location.0 as usize)
+        } else {
+            None
+        }
+    }
+
+    // None if location is outside the grid
+    pub fn get(&self, location: Location) -> Option<&T> {
+        self.index(location).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, location: Location) -> Option<&mut T> {
+        match self.index(location) {
+            Some(i) => Some(&mut self.cells[i]),
+            None => None,
+        }
+    }
+
+    // Returns false (and changes nothing) if location is outside the grid
+    pub fn set(&mut self, location: Location, value: T) -> bool {
+        match self.get_mut(location) {
+            Some(cell) => {
+                *cell = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    // Add a row to the bottom of the grid. It must be as wide as the others.
+    pub fn push_row(&mut self, row: Vec<T>) {
+        assert_eq!(self.width, row.len(), "row doesn't fit the grid");
+        self.cells.extend(row);
+        self.height += 1;
+    }
+
+    // Every cell with its location, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Location, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| (((i % width) as i32, (i / width) as i32), cell))
+    }
+
+    // Locations above, below, left and right of location that are inside the grid
+    pub fn neighbors4(&self, (x, y): Location) -> impl Iterator<Item = Location> + '_ {
+        [(0, -1), (-1, 0), (1, 0), (0, 1)]
+            .iter()
+            .map(move |&(dx, dy)| (x + dx, y + dy))
+            .filter(move |&loc| self.contains(loc))
+    }
+
+    // Like neighbors4, with the diagonals as well
+    pub fn neighbors8(&self, (x, y): Location) -> impl Iterator<Item = Location> + '_ {
+        [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)]
+            .iter()
+            .map(move |&(dx, dy)| (x + dx, y + dy))
+            .filter(move |&loc| self.contains(loc))
+    }
+
+    // Draw the grid as text, one line per row (no trailing newline)
+    pub fn render<F>(&self, mut cell: F) -> String
+        where F: FnMut(&T) -> char
+    {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for y in 0..self.height {
+            if y > 0 {
+                text.push('\n');
+            }
+            text.extend(self.row(y).iter().map(&mut cell));
+        }
+        text
+    }
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lights(input: &str) -> Grid<bool> {
+        Grid::parse(input, "'#' or '.'", |c| match c {
+                '#' => Some(true),
+                '.' => Some(false),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn draw(grid: &Grid<bool>) -> String {
+        grid.render(|&on| if on { '#' } else { '.' })
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = lights("#..\n.#.");
+        assert_eq!(3, grid.width());
+        assert_eq!(2, grid.height());
+        assert_eq!(Some(&true), grid.get((0, 0)));
+        assert_eq!(Some(&true), grid.get((1, 1)));
+        assert_eq!(Some(&false), grid.get((2, 1)));
+        assert_eq!("#..\n.#.", draw(&grid));
+
+        let empty = lights("");
+        assert_eq!((0, 0), (empty.width(), empty.height()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parse = |input| Grid::parse(input, "'#' or '.'", |c| if c == 'x' { None } else { Some(c) });
+        assert_eq!(Err(ParseError::new(2, 1, "...", "a row of width 2")), parse("..\n..."));
+        assert_eq!(Err(ParseError::new(2, 2, "x", "'#' or '.'")), parse("..\n.x"));
+    }
+
+    #[test]
+    fn test_get_set() {
+        let mut grid = Grid::new(3, 2, 0);
+        assert!(grid.set((2, 1), 5));
+        assert!(!grid.set((3, 1), 5));
+        assert!(!grid.set((0, -1), 5));
+        assert_eq!(Some(&5), grid.get((2, 1)));
+        assert_eq!(None, grid.get((-1, 0)));
+        assert_eq!(None, grid.get((0, 2)));
+        *grid.get_mut((0, 0)).unwrap() += 2;
+        assert_eq!(&[2, 0, 0], grid.row(0));
+
+        grid.push_row(vec![7, 8, 9]);
+        assert_eq!(3, grid.height());
+        assert_eq!(Some(&8), grid.get((1, 2)));
+    }
+
+    #[test]
+    fn test_from_fn_and_iter() {
+        let grid = Grid::from_fn(3, 2, |(x, y)| x + 10 * y);
+        assert_eq!(vec![((0, 0), &0), ((1, 0), &1), ((2, 0), &2), ((0, 1), &10), ((1, 1), &11), ((2, 1), &12)],
+                   grid.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::new(3, 3, ());
+        assert_eq!(vec![(1, 0), (0, 1), (2, 1), (1, 2)], grid.neighbors4((1, 1)).collect::<Vec<_>>());
+        assert_eq!(vec![(1, 0), (0, 1)], grid.neighbors4((0, 0)).collect::<Vec<_>>());
+        assert_eq!(8, grid.neighbors8((1, 1)).count());
+        assert_eq!(vec![(1, 0), (0, 1), (1, 1)], grid.neighbors8((0, 0)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_rotate() {
+        // the day 8 example
+        let mut grid = lights("###....\n###....\n.......");
+        grid.rotate_col(1, 1);
+        assert_eq!("#.#....\n###....\n.#.....", draw(&grid));
+        grid.rotate_row(0, 4);
+        assert_eq!("....#.#\n###....\n.#.....", draw(&grid));
+        grid.rotate_col(1, 1);
+        assert_eq!(".#..#.#\n#.#....\n.#.....", draw(&grid));
+        grid.rotate_row(2, 14);
+        assert_eq!(".#..#.#\n#.#....\n.#.....", draw(&grid));
+        // any rotation is fine, however big
+        grid.rotate_col(0, usize::MAX - 1);
+        assert_eq!("##..#.#\n..#....\n.#.....", draw(&grid));
+        grid.rotate_row(1, usize::MAX);
+        assert_eq!("##..#.#\n...#...\n.#.....", draw(&grid));
+    }
+
+    #[test]
+    #[should_panic(expected = "column 7 is outside the grid")]
+    fn test_rotate_col_outside() {
+        lights("###....\n###....\n.......").rotate_col(7, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "row 3 is outside the grid")]
+    fn test_rotate_row_outside() {
+        lights("###....\n###....\n.......").rotate_row(3, 1);
+    }
+}
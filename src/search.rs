@@ -0,0 +1,384 @@
+// search.rs
+// Generic searches over any state space: breadth-first, Dijkstra and A*
+//
+// States can be anything hashable. The caller supplies a successor function that lists the
+// states reachable in one move (with the cost of each move for Dijkstra and A*) and a goal test.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+// A cheapest path to a goal, start and goal included
+#[derive(Debug, PartialEq)]
+pub struct Found<S> {
+    pub distance: usize,
+    pub path: Vec<S>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SearchResult<S> {
+    // None if no goal is reachable
+    pub found: Option<Found<S>>,
+    // number of distinct states reached, including the start
+    pub visited: usize,
+}
+
+impl<S> SearchResult<S> {
+    pub fn distance(&self) -> Option<usize> {
+        self.found.as_ref().map(|found| found.distance)
+    }
+}
+
+// Every state reached so far, with the best known way to get there
+struct Node<S> {
+    state: S,
+    parent: Option<usize>,
+    distance: usize,
+}
+
+struct SearchTree<S> {
+    nodes: Vec<Node<S>>,
+    index: HashMap<S, usize>,
+}
+
+impl<S: Clone + Eq + Hash> SearchTree<S> {
+    fn new(start: S) -> SearchTree<S> {
+        let mut index = HashMap::new();
+        index.insert(start.clone(), 0);
+        SearchTree {
+            nodes: vec![Node {
+                            state: start,
+                            parent: None,
+                            distance: 0,
+                        }],
+            index,
+        }
+    }
+
+    // Record a path to state through parent, if it's the first or the shortest so far.
+    // Returns the state's node index in that case.
+    fn reach(&mut self, state: S, parent: usize, distance: usize) -> Option<usize> {
+        if let Some(&idx) = self.index.get(&state) {
+            let node = &mut self.nodes[idx];
+            if distance >= node.distance {
+                return None;
+            }
+            node.parent = Some(parent);
+            node.distance = distance;
+            return Some(idx);
+        }
+
+        let idx = self.nodes.len();
+        self.index.insert(state.clone(), idx);
+        self.nodes.push(Node {
+            state,
+            parent: Some(parent),
+            distance,
+        });
+        Some(idx)
+    }
+
+    fn result(&self, goal: Option<usize>) -> SearchResult<S> {
+        let visited = self.nodes.len();
+        let found = goal.map(|goal| {
+            let distance = self.nodes[goal].distance;
+            let mut path = vec![];
+            let mut idx = Some(goal);
+            while let Some(i) = idx {
+                path.push(self.nodes[i].state.clone());
+                idx = self.nodes[i].parent;
+            }
+            path.reverse();
+            Found { distance, path }
+        });
+
+        SearchResult { found, visited }
+    }
+}
+
+// Fewest moves from start to a goal state
+pub fn bfs<S, F, I, G>(start: S, successors: F, is_goal: G) -> SearchResult<S>
+    where S: Clone + Eq + Hash,
+          F: FnMut(&S) -> I,
+          I: IntoIterator<Item = S>,
+          G: FnMut(&S) -> bool
+{
+    let (tree, goal) = breadth_first(start, successors, is_goal);
+    tree.result(goal)
+}
+
+// Like bfs, but only the number of moves. Nothing is kept but the states seen, and a goal is
+// recognized as soon as it's reached rather than when its turn comes, so this is the cheaper
+// search when the path isn't needed.
+pub fn bfs_distance<S, F, I, G>(start: S, mut successors: F, mut is_goal: G) -> Option<usize>
+    where S: Clone + Eq + Hash,
+          F: FnMut(&S) -> I,
+          I: IntoIterator<Item = S>,
+          G: FnMut(&S) -> bool
+{
+    if is_goal(&start) {
+        return Some(0);
+    }
+    let mut seen = HashSet::new();
+    seen.insert(start.clone());
+    let mut queue = VecDeque::new();
+    queue.push_back((start, 0));
+
+    while let Some((state, distance)) = queue.pop_front() {
+        for next in successors(&state) {
+            if is_goal(&next) {
+                return Some(distance + 1);
+            }
+            if !seen.contains(&next) {
+                seen.insert(next.clone());
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+
+    None
+}
+
+// How many states are reachable from start in at most max_distance moves, start included
+pub fn count_reachable<S, F, I>(start: S, max_distance: usize, successors: F) -> usize
+    where S: Clone + Eq + Hash,
+          F: FnMut(&S) -> I,
+          I: IntoIterator<Item = S>
+{
+    distances(start, max_distance, successors, |_| false).len()
+}
+
+// Fewest moves from start to every state reachable in at most max_distance moves, start
+// included. The search stops early once done returns true, but everything it has reached by
+// then has its distance.
+pub fn distances<S, F, I, D>(start: S,
+                             max_distance: usize,
+                             mut successors: F,
+                             mut done: D)
+                             -> HashMap<S, usize>
+    where S: Clone + Eq + Hash,
+          F: FnMut(&S) -> I,
+          I: IntoIterator<Item = S>,
+          D: FnMut(&S) -> bool
+{
+    // no parents to keep, just how far each state is
+    let mut distances = HashMap::new();
+    distances.insert(start.clone(), 0);
+    let mut queue = VecDeque::new();
+    queue.push_back((start, 0));
+
+    while let Some((state, distance)) = queue.pop_front() {
+        if done(&state) {
+            break;
+        }
+        if distance >= max_distance {
+            continue;
+        }
+        for next in successors(&state) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+
+    distances
+}
+
+// The search tree, and the node of the goal if one was found
+fn breadth_first<S, F, I, G>(start: S, mut successors: F, mut is_goal: G) -> (SearchTree<S>, Option<usize>)
+    where S: Clone + Eq + Hash,
+          F: FnMut(&S) -> I,
+          I: IntoIterator<Item = S>,
+          G: FnMut(&S) -> bool
+{
+    let mut tree = SearchTree::new(start);
+    let mut queue = VecDeque::new();
+    queue.push_back(0);
+
+    while let Some(idx) = queue.pop_front() {
+        if is_goal(&tree.nodes[idx].state) {
+            return (tree, Some(idx));
+        }
+        let distance = tree.nodes[idx].distance;
+        for next in successors(&tree.nodes[idx].state) {
+            // the first path BFS finds to a state is always a shortest one
+            if !tree.index.contains_key(&next) {
+                let next_idx = tree.reach(next, idx, distance + 1).unwrap();
+                queue.push_back(next_idx);
+            }
+        }
+    }
+
+    (tree, None)
+}
+
+// Cheapest path from start to a goal state, where successors also gives the cost of each move
+pub fn dijkstra<S, F, I, G>(start: S, successors: F, is_goal: G) -> SearchResult<S>
+    where S: Clone + Eq + Hash,
+          F: FnMut(&S) -> I,
+          I: IntoIterator<Item = (S, usize)>,
+          G: FnMut(&S) -> bool
+{
+    astar(start, successors, |_| 0, is_goal)
+}
+
+// Like dijkstra, but the heuristic's estimate of the cost left to reach a goal guides the
+// search towards it. The answer is only guaranteed to be cheapest if the heuristic never
+// overestimates.
+pub fn astar<S, F, I, H, G>(start: S,
+                            mut successors: F,
+                            mut heuristic: H,
+                            mut is_goal: G)
+                            -> SearchResult<S>
+    where S: Clone + Eq + Hash,
+          F: FnMut(&S) -> I,
+          I: IntoIterator<Item = (S, usize)>,
+          H: FnMut(&S) -> usize,
+          G: FnMut(&S) -> bool
+{
+    let mut open = BinaryHeap::new();
+    open.push(Reverse((heuristic(&start), 0, 0)));
+    let mut tree = SearchTree::new(start);
+
+    while let Some(Reverse((_, distance, idx))) = open.pop() {
+        // skip entries for states that have been reached more cheaply since they were queued
+        if distance > tree.nodes[idx].distance {
+            continue;
+        }
+        if is_goal(&tree.nodes[idx].state) {
+            return tree.result(Some(idx));
+        }
+
+        for (next, cost) in successors(&tree.nodes[idx].state) {
+            let next_distance = distance + cost;
+            let estimate = next_distance + heuristic(&next);
+            if let Some(next_idx) = tree.reach(next, idx, next_distance) {
+                open.push(Reverse((estimate, next_distance, next_idx)));
+            }
+        }
+    }
+
+    tree.result(None)
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Location;
+
+    // the maze from the day 13 example
+    fn is_open((x, y): Location) -> bool {
+        x >= 0 && y >= 0 && (x * x + 3 * x + 2 * x * y + y + y * y + 10).count_ones().is_multiple_of(2)
+    }
+
+    fn neighbors((x, y): Location) -> Vec<Location> {
+        vec![(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)].into_iter().filter(|&loc| is_open(loc)).collect()
+    }
+
+    fn manhattan((x1, y1): Location, (x2, y2): Location) -> usize {
+        ((x1 - x2).abs() + (y1 - y2).abs()) as usize
+    }
+
+    #[test]
+    fn test_bfs() {
+        let result = bfs((1, 1), |&loc| neighbors(loc), |&loc| loc == (7, 4));
+        let found = result.found.unwrap();
+        assert_eq!(11, found.distance);
+        assert_eq!(12, found.path.len());
+        assert_eq!((1, 1), found.path[0]);
+        assert_eq!((7, 4), found.path[11]);
+        for step in found.path.windows(2) {
+            assert_eq!(1, manhattan(step[0], step[1]));
+            assert!(is_open(step[1]));
+        }
+        assert!(result.visited > found.path.len());
+    }
+
+    #[test]
+    fn test_bfs_start_is_goal() {
+        let result = bfs((1, 1), |&loc| neighbors(loc), |&loc| loc == (1, 1));
+        assert_eq!(Some(Found {
+                       distance: 0,
+                       path: vec![(1, 1)],
+                   }),
+                   result.found);
+        assert_eq!(1, result.visited);
+    }
+
+    #[test]
+    fn test_bfs_unreachable() {
+        // a 1-dimensional world where states can only count up to 5
+        let result = bfs(0, |&n| if n < 5 { vec![n + 1] } else { vec![] }, |&n| n == 10);
+        assert_eq!(None, result.found);
+        assert_eq!(6, result.visited);
+    }
+
+    #[test]
+    fn test_bfs_distance() {
+        assert_eq!(Some(11), bfs_distance((1, 1), |&loc| neighbors(loc), |&loc| loc == (7, 4)));
+        assert_eq!(Some(0), bfs_distance((1, 1), |&loc| neighbors(loc), |&loc| loc == (1, 1)));
+        assert_eq!(None, bfs_distance(0, |&n| if n < 5 { vec![n + 1] } else { vec![] }, |&n| n == 10));
+    }
+
+    #[test]
+    fn test_count_reachable() {
+        assert_eq!(1, count_reachable((1, 1), 0, |&loc| neighbors(loc)));
+        assert_eq!(3, count_reachable((1, 1), 1, |&loc| neighbors(loc)));
+        assert_eq!(6, count_reachable(0, 5, |&n| vec![n + 1]));
+    }
+
+    #[test]
+    fn test_distances() {
+        let nearby = distances((1, 1), 2, |&loc| neighbors(loc), |_| false);
+        assert_eq!(count_reachable((1, 1), 2, |&loc| neighbors(loc)), nearby.len());
+        assert_eq!(Some(&0), nearby.get(&(1, 1)));
+        assert_eq!(Some(&2), nearby.get(&(2, 2)));
+
+        // stopping at 3 leaves out everything further away
+        let counted = distances(0, usize::MAX, |&n| vec![n + 1, n + 2], |&n| n == 3);
+        assert_eq!(Some(&2), counted.get(&3));
+        assert_eq!(None, counted.get(&6));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // going straight to 10 costs 20, but stepping costs 1 per step
+        let successors = |&n: &u32| {
+            let mut next = vec![(n + 1, 1)];
+            if n == 0 {
+                next.push((10, 20));
+            }
+            next
+        };
+        let result = dijkstra(0, successors, |&n| n == 10);
+        let found = result.found.unwrap();
+        assert_eq!(10, found.distance);
+        assert_eq!((0..11).collect::<Vec<_>>(), found.path);
+
+        // a cheaper way to a state found later replaces the first one
+        let successors = |&n: &u32| match n {
+            0 => vec![(2, 10), (1, 1)],
+            1 => vec![(2, 1)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        };
+        let result = dijkstra(0, successors, |&n| n == 3);
+        assert_eq!(vec![0, 1, 2, 3], result.found.unwrap().path);
+    }
+
+    #[test]
+    fn test_astar() {
+        let goal = (7, 4);
+        let successors = |&loc: &Location| neighbors(loc).into_iter().map(|n| (n, 1));
+        let result = astar((1, 1), successors, |&loc| manhattan(loc, goal), |&loc| loc == goal);
+        assert_eq!(Some(11), result.distance());
+
+        let plain = dijkstra((1, 1), successors, |&loc| loc == goal);
+        assert_eq!(Some(11), plain.distance());
+        // the heuristic should steer the search past some of the maze
+        assert!(result.visited <= plain.visited);
+    }
+}
@@ -0,0 +1,121 @@
+// timing.rs
+// Wall-clock timing of each phase of a day's solution
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+// How many times to run each phase: warm-up runs aren't measured
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Runs {
+    pub warmup: usize,
+    pub repeats: usize,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    // times must not be empty
+    pub fn from_times(times: &mut [Duration]) -> Stats {
+        times.sort();
+        let mid = times.len() / 2;
+        let median = if times.len().is_multiple_of(2) {
+            (times[mid - 1] + times[mid]) / 2
+        } else {
+            times[mid]
+        };
+
+        Stats {
+            min: times[0],
+            median,
+            max: times[times.len() - 1],
+        }
+    }
+}
+
+// Timings for one day: parsing the input, then each requested part
+#[derive(Debug)]
+pub struct DayTiming {
+    pub parse: Stats,
+    pub parts: Vec<(u32, Stats)>,
+}
+
+// Run f the requested number of times, keeping its result alive so the work isn't optimized away
+pub fn measure<T, F: FnMut() -> T>(runs: &Runs, mut f: F) -> Stats {
+    for _ in 0..runs.warmup {
+        black_box(f());
+    }
+
+    let mut times: Vec<_> = (0..runs.repeats.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+
+    Stats::from_times(&mut times)
+}
+
+// Short human-readable duration, e.g. "512ns", "37.2µs", "1.50ms", "20.41s"
+pub fn format_duration(d: Duration) -> String {
+    let nanos = d.as_nanos();
+    if nanos < 1_000 {
+        format!("{}ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.1}µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.2}ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2}s", d.as_secs_f64())
+    }
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_stats_from_times() {
+        let stats = Stats::from_times(&mut [ms(5), ms(1), ms(3)]);
+        assert_eq!(Stats {
+                       min: ms(1),
+                       median: ms(3),
+                       max: ms(5),
+                   },
+                   stats);
+
+        let stats = Stats::from_times(&mut [ms(8), ms(2), ms(4), ms(1)]);
+        assert_eq!(ms(3), stats.median);
+    }
+
+    #[test]
+    fn test_measure() {
+        let mut calls = 0;
+        let runs = Runs {
+            warmup: 2,
+            repeats: 3,
+        };
+        let stats = measure(&runs, || calls += 1);
+        assert_eq!(5, calls);
+        assert!(stats.min <= stats.median && stats.median <= stats.max);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("512ns", format_duration(Duration::from_nanos(512)));
+        assert_eq!("37.2µs", format_duration(Duration::from_nanos(37_200)));
+        assert_eq!("1.50ms", format_duration(Duration::from_micros(1_500)));
+        assert_eq!("20.41s", format_duration(Duration::from_millis(20_410)));
+    }
+}
@@ -0,0 +1,192 @@
+// verify.rs
+// Check solver output against a file of recorded answers
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use toml::Value;
+
+// Recorded answers, keyed by day and part. The file looks like:
+//
+// [day1]
+// part1 = 242
+// part2 = "150"
+//
+// [day8]
+// part2 = """
+// #..#.
+// .##.."""
+//
+// Each answer is compared with the solver's output as text, ignoring trailing whitespace
+// on every line so multi-line answers can be written naturally.
+#[derive(Debug, PartialEq)]
+pub struct AnswerKey {
+    answers: HashMap<(u32, u32), String>,
+}
+
+impl AnswerKey {
+    pub fn parse(manifest: &str) -> Result<AnswerKey, String> {
+        let root: Value = manifest.parse().map_err(|err| format!("{}", err))?;
+        let days = root.as_table().ok_or("expected a table of days")?;
+        let mut answers = HashMap::new();
+
+        for (day_key, parts) in days {
+            let day = parse_key(day_key, "day")?;
+            let parts = parts.as_table()
+                .ok_or_else(|| format!("expected [{}] to be a table of parts", day_key))?;
+
+            for (part_key, answer) in parts {
+                let part = parse_key(part_key, "part")?;
+                let answer = match *answer {
+                    Value::String(ref s) => s.clone(),
+                    Value::Integer(n) => n.to_string(),
+                    _ => {
+                        return Err(format!("expected {}.{} to be a string or integer",
+                                           day_key,
+                                           part_key))
+                    }
+                };
+                answers.insert((day, part), answer);
+            }
+        }
+
+        Ok(AnswerKey { answers })
+    }
+
+    pub fn get(&self, day: u32, part: u32) -> Option<&str> {
+        self.answers.get(&(day, part)).map(|s| s.as_str())
+    }
+
+    // Compare one part's output with the recorded answer
+    pub fn check(&self, day: u32, part: u32, actual: Option<&str>) -> Check {
+        match (self.get(day, part), actual) {
+            (None, actual) => Check::Missing(actual.map(|s| s.to_string())),
+            (Some(expected), Some(actual)) if normalize(expected) == normalize(actual) => Check::Pass,
+            (Some(expected), actual) => {
+                Check::Fail(Diff {
+                    expected: normalize(expected),
+                    actual: actual.map(normalize),
+                })
+            }
+        }
+    }
+}
+
+// "day12" -> 12
+fn parse_key(key: &str, prefix: &str) -> Result<u32, String> {
+    key.strip_prefix(prefix)
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(|| format!("expected a key like {}1, found '{}'", prefix, key))
+}
+
+fn normalize(answer: &str) -> String {
+    let lines: Vec<_> = answer.trim_matches('\n').lines().map(|l| l.trim_end()).collect();
+    lines.join("\n")
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Check {
+    Pass,
+    Fail(Diff),
+    // no recorded answer, so there's nothing to compare with (holds what the solver found)
+    Missing(Option<String>),
+}
+
+// Expected and actual output, displayed line by line: matching lines are indented,
+// lines only in the recorded answer start with '-' and lines only in the output with '+'
+#[derive(Debug, PartialEq)]
+pub struct Diff {
+    expected: String,
+    actual: Option<String>,
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let expected: Vec<_> = self.expected.lines().collect();
+        let actual: Vec<_> = match self.actual {
+            Some(ref actual) => actual.lines().collect(),
+            None => vec![],
+        };
+
+        for i in 0..expected.len().max(actual.len()) {
+            match (expected.get(i), actual.get(i)) {
+                (Some(e), Some(a)) if e == a => writeln!(f, "  {}", e)?,
+                (e, a) => {
+                    if let Some(e) = e {
+                        writeln!(f, "- {}", e)?;
+                    }
+                    if let Some(a) = a {
+                        writeln!(f, "+ {}", a)?;
+                    }
+                }
+            }
+        }
+        if self.actual.is_none() {
+            writeln!(f, "+ (no answer)")?;
+        }
+        Ok(())
+    }
+}
+
+// //////
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+[day1]
+part1 = 242
+part2 = "150"
+
+[day8]
+part2 = """
+#..#
+.##.
+"""
+"#;
+
+    #[test]
+    fn test_parse() {
+        let key = AnswerKey::parse(MANIFEST).unwrap();
+        assert_eq!(Some("242"), key.get(1, 1));
+        assert_eq!(Some("150"), key.get(1, 2));
+        assert_eq!(Some("#..#\n.##.\n"), key.get(8, 2));
+        assert_eq!(None, key.get(8, 1));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(AnswerKey::parse("[day1]\npart1 = ").is_err());
+        assert!(AnswerKey::parse("[dayone]\npart1 = 1").is_err());
+        assert!(AnswerKey::parse("[day1]\npart = 1").is_err());
+        assert!(AnswerKey::parse("[day1]\npart1 = 1.5").is_err());
+        assert!(AnswerKey::parse("day1 = 5").is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let key = AnswerKey::parse(MANIFEST).unwrap();
+        assert_eq!(Check::Pass, key.check(1, 1, Some("242")));
+        assert_eq!(Check::Pass, key.check(8, 2, Some("#..#  \n.##.")));
+        assert_eq!(Check::Missing(Some("6".to_string())), key.check(8, 1, Some("6")));
+        assert_eq!(Check::Missing(None), key.check(2, 1, None));
+        assert_eq!(Check::Fail(Diff {
+                       expected: "242".to_string(),
+                       actual: None,
+                   }),
+                   key.check(1, 1, None));
+    }
+
+    #[test]
+    fn test_diff_display() {
+        let key = AnswerKey::parse(MANIFEST).unwrap();
+        match key.check(8, 2, Some("#..#\n.#..")) {
+            Check::Fail(diff) => assert_eq!("  #..#\n- .##.\n+ .#..\n", diff.to_string()),
+            check => panic!("expected a failure, got {:?}", check),
+        }
+        match key.check(1, 2, None) {
+            Check::Fail(diff) => assert_eq!("- 150\n+ (no answer)\n", diff.to_string()),
+            check => panic!("expected a failure, got {:?}", check),
+        }
+    }
+}